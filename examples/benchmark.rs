@@ -0,0 +1,112 @@
+//Compares how long each way of running a program takes on the
+//samples in `test_inputs`, or on the files given as arguments:
+//
+//    cargo run --release --example benchmark [files...]
+//
+//The C++ column includes compiling the program, and is
+//skipped when no C++ compiler is installed
+use std::{
+    fs,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use juvinil::{
+    bytecode::{compiler, vm},
+    code_generation::cpp::CppGenerator,
+    interpreter,
+    lexical_analysis::lex,
+    syntax_analysis::{ast::Program, parser::Parser},
+    toolchain::{self, Toolchain},
+};
+
+//Samples that never end
+const SKIPPED: [&str; 1] = ["test_inputs/for.jv"];
+
+fn main() {
+    let mut files: Vec<String> = std::env::args().skip(1).collect();
+    if files.is_empty() {
+        files = fs::read_dir("test_inputs")
+            .expect("Couldn't read test_inputs")
+            .map(|entry| entry.unwrap().path().to_string_lossy().into_owned())
+            .filter(|path| path.ends_with(".jv") && !SKIPPED.contains(&path.as_str()))
+            .collect();
+        files.sort();
+    }
+
+    let toolchain = Toolchain::detect(None).ok();
+
+    println!(
+        "{:<32} {:>12} {:>12} {:>12}",
+        "file", "interpreter", "vm", "c++"
+    );
+
+    for file in files {
+        let source = fs::read_to_string(&file).expect("Couldn't read the file");
+        let Some(program) = parse(source) else {
+            println!("{:<32} {:>12}", file, "invalid");
+            continue;
+        };
+
+        let interpreted = time(|| {
+            let _ = interpreter::run_captured(&program, "");
+        });
+        let bytecode = time(|| {
+            let module = compiler::compile(&program);
+            let _ = vm::run_captured(&module, "");
+        });
+        let native = match &toolchain {
+            Some(toolchain) => format_duration(run_native(toolchain, &program)),
+            None => "-".into(),
+        };
+
+        println!(
+            "{:<32} {:>12} {:>12} {:>12}",
+            file,
+            format_duration(Some(interpreted)),
+            format_duration(Some(bytecode)),
+            native
+        );
+    }
+}
+
+fn parse(source: String) -> Option<Program> {
+    let tokens = lex::tokenize(source).ok()?;
+    Parser::new(tokens).ok()?.parse().ok()
+}
+
+fn time(run: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    run();
+    start.elapsed()
+}
+
+//Generates, compiles and runs the C++ code of the program
+fn run_native(toolchain: &Toolchain, program: &Program) -> Option<Duration> {
+    let base = std::env::temp_dir().join(format!("juvinil-benchmark-{}", std::process::id()));
+    let source = format!("{}.cpp", base.to_string_lossy());
+    let executable = toolchain::executable_path(&base.to_string_lossy());
+
+    let start = Instant::now();
+    fs::write(&source, CppGenerator::new().generate(program)).ok()?;
+    let compiled = toolchain.compile(&source, &executable);
+    let ran = compiled.is_ok()
+        && Command::new(&executable)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()
+            .is_ok();
+    let elapsed = start.elapsed();
+
+    let _ = fs::remove_file(&source);
+    let _ = fs::remove_file(&executable);
+
+    ran.then_some(elapsed)
+}
+
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.2}ms", duration.as_secs_f64() * 1000.0),
+        None => "failed".into(),
+    }
+}
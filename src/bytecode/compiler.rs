@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+
+use crate::{
+    interpreter::{runtime, value::Value},
+    syntax_analysis::{
+        ast::{Expr, ExprKind, Program, Stmt, StmtKind},
+        builtins::BUILTINS,
+    },
+};
+
+use super::{Function, Module, Op};
+
+//Jumps of `break` and `continue` inside a loop, whose
+//targets are only known once the whole loop is compiled
+#[derive(Default)]
+struct Loop {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+//Compiles a parsed program into a module, one function at a time
+pub struct Compiler<'p> {
+    program: &'p Program,
+    module: Module,
+    globals: HashMap<String, u32>,
+    function: Function,                //Function being compiled
+    scopes: Vec<HashMap<String, u32>>, //Local slots of each nested block
+    loops: Vec<Loop>,
+    line: u32, //Line of the statement or expression being compiled
+}
+
+//Compiles a parsed program into bytecode
+pub fn compile(program: &Program) -> Module {
+    Compiler::new(program).compile()
+}
+
+impl<'p> Compiler<'p> {
+    pub fn new(program: &'p Program) -> Self {
+        Compiler {
+            program,
+            module: Module::default(),
+            globals: HashMap::new(),
+            function: new_function("main", 0),
+            scopes: Vec::new(),
+            loops: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn compile(mut self) -> Module {
+        //The main program comes first, and initializes the
+        //globals before its statements, like C++ does
+        self.scopes.push(HashMap::new());
+
+        for global in &self.program.globals {
+            self.global(global);
+        }
+
+        self.block(&self.program.body);
+        self.emit(Op::ReturnVoid);
+        self.finish_function();
+
+        for function in &self.program.functions {
+            self.function = new_function(&function.func_name, function.params.len() as u32);
+            self.line = function.line as u32;
+
+            let mut params = HashMap::new();
+            for (_, param_name) in &function.params {
+                params.insert(param_name.clone(), self.allocate_local());
+            }
+            self.scopes.push(params);
+
+            self.block(&function.body);
+            self.emit(Op::ReturnVoid);
+            self.finish_function();
+        }
+
+        self.module
+    }
+
+    fn finish_function(&mut self) {
+        let function = std::mem::replace(&mut self.function, new_function("", 0));
+        self.module.functions.push(function);
+        self.scopes.clear();
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.function.code.push(op);
+        self.function.lines.push(self.line);
+        self.function.code.len() - 1
+    }
+
+    //Points a jump emitted before at the next instruction
+    fn patch(&mut self, jump: usize) {
+        let target = self.function.code.len() as u32;
+        self.function.code[jump] = match self.function.code[jump] {
+            Op::Jump(_) => Op::Jump(target),
+            Op::JumpIfFalse(_) => Op::JumpIfFalse(target),
+            Op::JumpIfTrue(_) => Op::JumpIfTrue(target),
+            op => op,
+        };
+    }
+
+    fn constant(&mut self, value: Value) -> u32 {
+        let constants = &mut self.module.constants;
+        let index = constants
+            .iter()
+            .position(|c| *c == value)
+            .unwrap_or_else(|| {
+                constants.push(value);
+                constants.len() - 1
+            });
+
+        index as u32
+    }
+
+    fn allocate_local(&mut self) -> u32 {
+        self.function.locals += 1;
+        self.function.locals - 1
+    }
+
+    fn global(&mut self, stmt: &Stmt) {
+        self.line = stmt.line as u32;
+
+        if let StmtKind::Decl(_, var_type, declarators) = &stmt.kind {
+            for (var_name, initializer) in declarators {
+                self.initializer(initializer, var_type);
+
+                let index = self.module.globals.len() as u32;
+                self.module.globals.push(var_name.clone());
+                self.globals.insert(var_name.clone(), index);
+                self.emit(Op::StoreGlobal(index));
+            }
+        }
+    }
+
+    fn initializer(&mut self, initializer: &Option<Expr>, var_type: &str) {
+        match initializer {
+            Some(initializer) => self.expr(initializer),
+            None => {
+                let default = self.constant(Value::default_of(var_type));
+                self.emit(Op::Const(default));
+            }
+        }
+    }
+
+    fn load(&mut self, var_name: &str) {
+        match self.local(var_name) {
+            Some(slot) => self.emit(Op::LoadLocal(slot)),
+            None => self.emit(Op::LoadGlobal(self.globals[var_name])),
+        };
+    }
+
+    fn store(&mut self, var_name: &str) {
+        match self.local(var_name) {
+            Some(slot) => self.emit(Op::StoreLocal(slot)),
+            None => self.emit(Op::StoreGlobal(self.globals[var_name])),
+        };
+    }
+
+    fn local(&self, var_name: &str) -> Option<u32> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var_name).copied())
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        self.line = stmt.line as u32;
+
+        match &stmt.kind {
+            StmtKind::Decl(_, var_type, declarators) => {
+                for (var_name, initializer) in declarators {
+                    self.initializer(initializer, var_type);
+
+                    let slot = self.allocate_local();
+                    self.scopes
+                        .last_mut()
+                        .unwrap()
+                        .insert(var_name.clone(), slot);
+                    self.emit(Op::StoreLocal(slot));
+                }
+            }
+            StmtKind::Asgn(var_name, operator, value) => {
+                if operator != "=" {
+                    self.load(var_name);
+                }
+
+                self.expr(value);
+
+                match (operator.as_str(), value.jv_type.as_str()) {
+                    ("+=", "string") => self.emit(Op::Concat),
+                    ("+=", _) => self.emit(Op::Add),
+                    ("-=", _) => self.emit(Op::Sub),
+                    _ => 0,
+                };
+
+                self.store(var_name);
+            }
+            StmtKind::Call(call) => {
+                self.expr(call);
+                self.emit(Op::Pop);
+            }
+            StmtKind::Block(stmts) => self.block(stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                self.expr(condition);
+                let to_else = self.emit(Op::JumpIfFalse(0));
+                self.block(then_block);
+
+                match else_block {
+                    Some(else_block) => {
+                        let to_end = self.emit(Op::Jump(0));
+                        self.patch(to_else);
+                        self.block(else_block);
+                        self.patch(to_end);
+                    }
+                    None => self.patch(to_else),
+                }
+            }
+            StmtKind::While(condition, body) => self.loop_stmt(None, condition, body),
+            StmtKind::For(init, condition, body) => {
+                self.loop_stmt(Some(init.as_ref()), condition, body)
+            }
+            StmtKind::DoWhile(body, condition) => {
+                let start = self.function.code.len() as u32;
+                self.loops.push(Loop::default());
+                self.block(body);
+
+                //`continue` goes to the condition
+                let current = self.loops.pop().unwrap();
+                for jump in current.continues {
+                    self.patch(jump);
+                }
+
+                self.line = condition.line as u32;
+                self.expr(condition);
+                self.emit(Op::JumpIfTrue(start));
+
+                for jump in current.breaks {
+                    self.patch(jump);
+                }
+            }
+            StmtKind::Break => {
+                let jump = self.emit(Op::Jump(0));
+                self.loops.last_mut().unwrap().breaks.push(jump);
+            }
+            StmtKind::Continue => {
+                let jump = self.emit(Op::Jump(0));
+                self.loops.last_mut().unwrap().continues.push(jump);
+            }
+            StmtKind::Return(value) => match value {
+                Some(value) => {
+                    self.expr(value);
+                    self.emit(Op::Return);
+                }
+                None => {
+                    self.emit(Op::ReturnVoid);
+                }
+            },
+        }
+    }
+
+    //`while` and `for` loops, which check their condition first.
+    //`continue` goes back to the condition
+    fn loop_stmt(&mut self, init: Option<&Stmt>, condition: &Expr, body: &[Stmt]) {
+        if let Some(init) = init {
+            self.stmt(init);
+        }
+
+        let start = self.function.code.len() as u32;
+        self.expr(condition);
+        let to_end = self.emit(Op::JumpIfFalse(0));
+
+        self.loops.push(Loop::default());
+        self.block(body);
+        let current = self.loops.pop().unwrap();
+
+        for jump in current.continues {
+            self.function.code[jump] = Op::Jump(start);
+        }
+
+        self.emit(Op::Jump(start));
+        self.patch(to_end);
+
+        for jump in current.breaks {
+            self.patch(jump);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        self.line = expr.line as u32;
+
+        match &expr.kind {
+            ExprKind::Int(value) => {
+                let index = self.constant(Value::Int(*value));
+                self.emit(Op::Const(index));
+            }
+            ExprKind::Bool(value) => {
+                let index = self.constant(Value::Bool(*value));
+                self.emit(Op::Const(index));
+            }
+            ExprKind::Str(value) => {
+                let index = self.constant(Value::Str(runtime::unescape(value).into_bytes()));
+                self.emit(Op::Const(index));
+            }
+            ExprKind::Var(var_name) => self.load(var_name),
+            ExprKind::Call(func_name, args) => {
+                for arg in args {
+                    self.expr(arg);
+                }
+                self.line = expr.line as u32;
+
+                match BUILTINS.iter().position(|builtin| builtin == func_name) {
+                    Some(builtin) => self.emit(Op::CallBuiltin(builtin as u32, args.len() as u32)),
+                    None => {
+                        let index = self.program.function_index(func_name, args).unwrap();
+                        self.emit(Op::Call(index as u32 + 1))
+                    }
+                };
+            }
+            ExprKind::Unary(operator, operand) => {
+                self.expr(operand);
+
+                match operator.as_str() {
+                    "!" => self.emit(Op::Not),
+                    "-" => self.emit(Op::Neg),
+                    _ => {
+                        let one = self.constant(Value::Int(1));
+                        self.emit(Op::Const(one));
+                        self.emit(if operator == "++" { Op::Add } else { Op::Sub })
+                    }
+                };
+
+                //`++` and `--` change the variable they're applied to
+                if let ("++" | "--", ExprKind::Var(var_name)) = (operator.as_str(), &operand.kind) {
+                    self.emit(Op::Dup);
+                    self.store(var_name);
+                }
+            }
+            ExprKind::Binary(operator, left, right) if operator == "&&" || operator == "||" => {
+                //The right side is only evaluated when needed
+                self.expr(left);
+                let short_circuit = match operator.as_str() {
+                    "&&" => self.emit(Op::JumpIfFalse(0)),
+                    _ => self.emit(Op::JumpIfTrue(0)),
+                };
+
+                self.expr(right);
+                let to_end = self.emit(Op::Jump(0));
+
+                self.patch(short_circuit);
+                let result = self.constant(Value::Bool(operator == "||"));
+                self.emit(Op::Const(result));
+                self.patch(to_end);
+            }
+            ExprKind::Binary(operator, left, right) => {
+                self.expr(left);
+                self.expr(right);
+                self.line = expr.line as u32;
+
+                let op = match operator.as_str() {
+                    "+" if left.jv_type == "string" => Op::Concat,
+                    "+" => Op::Add,
+                    "-" => Op::Sub,
+                    "*" => Op::Mul,
+                    "/" => Op::Div,
+                    "%" => Op::Rem,
+                    "&" => Op::BitAnd,
+                    "|" => Op::BitOr,
+                    "==" => Op::Eq,
+                    "!=" => Op::Ne,
+                    "<" => Op::Lt,
+                    "<=" => Op::Le,
+                    ">" => Op::Gt,
+                    _ => Op::Ge,
+                };
+                self.emit(op);
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                self.expr(condition);
+                let to_else = self.emit(Op::JumpIfFalse(0));
+                self.expr(then_expr);
+                let to_end = self.emit(Op::Jump(0));
+                self.patch(to_else);
+                self.expr(else_expr);
+                self.patch(to_end);
+            }
+            ExprKind::Interpolation(parts) => {
+                for part in parts {
+                    self.expr(part);
+                }
+
+                self.emit(Op::Interpolate(parts.len() as u32));
+            }
+        }
+    }
+}
+
+fn new_function(name: &str, arity: u32) -> Function {
+    Function {
+        name: name.into(),
+        arity,
+        locals: 0,
+        code: Vec::new(),
+        lines: Vec::new(),
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt::Write;
+
+use crate::syntax_analysis::builtins::BUILTINS;
+
+use super::{Module, Op};
+
+//Lists the constants, globals and the instructions of every function
+//of a module. Operands that are indexes are followed by what they point to
+pub fn disassemble(module: &Module) -> String {
+    let mut listing = String::new();
+
+    listing.push_str("constants:\n");
+    for (index, constant) in module.constants.iter().enumerate() {
+        writeln!(listing, "    {:<4} {:?}", index, constant).unwrap();
+    }
+
+    listing.push_str("globals:\n");
+    for (index, global) in module.globals.iter().enumerate() {
+        writeln!(listing, "    {:<4} {}", index, global).unwrap();
+    }
+
+    for function in &module.functions {
+        writeln!(
+            listing,
+            "\nfn {} (params: {}, locals: {}):",
+            function.name, function.arity, function.locals
+        )
+        .unwrap();
+
+        for (offset, (op, line)) in function.code.iter().zip(&function.lines).enumerate() {
+            let instruction = format!("{:?}", op);
+            let comment = match op {
+                Op::Const(index) => format!("{:?}", module.constants[*index as usize]),
+                Op::LoadGlobal(index) | Op::StoreGlobal(index) => {
+                    module.globals[*index as usize].clone()
+                }
+                Op::Call(index) => module.functions[*index as usize].name.clone(),
+                Op::CallBuiltin(index, _) => BUILTINS[*index as usize].to_string(),
+                _ => String::new(),
+            };
+
+            let line = format!("{:04}  line {:<4} {:<20}", offset, line, instruction);
+            match comment.is_empty() {
+                true => writeln!(listing, "    {}", line.trim_end()).unwrap(),
+                false => writeln!(listing, "    {} ; {}", line, comment).unwrap(),
+            }
+        }
+    }
+
+    listing
+}
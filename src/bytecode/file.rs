@@ -0,0 +1,420 @@
+use crate::{
+    error::{JuvinilError, JuvinilResult},
+    interpreter::value::Value,
+    syntax_analysis::builtins::{self, BUILTINS},
+};
+
+use super::{Function, Module, Op};
+
+//Layout of a bytecode file, with every number in little endian:
+//
+//    magic       "JVBC"
+//    version     u16, changed whenever the layout or the instructions change
+//    checksum    u32, FNV-1a of everything after it
+//    constants   u32 count, then a tag (0 int, 1 boolean, 2 string) and a value each
+//    globals     u32 count, then a string each
+//    functions   u32 count, then the name, arity, locals and code of each
+//    lines       the line of each instruction, for each function
+//
+//Strings are a u32 length followed by UTF-8 bytes, and
+//instructions an opcode followed by their operands as u32
+pub const MAGIC: &[u8; 4] = b"JVBC";
+pub const VERSION: u16 = 1;
+pub const EXTENSION: &str = "jvc";
+
+//Size of the magic, version and checksum
+const HEADER_SIZE: usize = 10;
+
+pub fn encode(module: &Module) -> Vec<u8> {
+    let mut body = Writer::default();
+
+    body.u32(module.constants.len() as u32);
+    for constant in &module.constants {
+        match constant {
+            Value::Int(value) => {
+                body.u8(0);
+                body.u32(*value as u32);
+            }
+            Value::Bool(value) => {
+                body.u8(1);
+                body.u8(*value as u8);
+            }
+            Value::Str(value) => {
+                body.u8(2);
+                body.bytes(value);
+            }
+            Value::Void => panic!("Void can't be a constant"),
+        }
+    }
+
+    body.u32(module.globals.len() as u32);
+    for global in &module.globals {
+        body.string(global);
+    }
+
+    body.u32(module.functions.len() as u32);
+    for function in &module.functions {
+        body.string(&function.name);
+        body.u32(function.arity);
+        body.u32(function.locals);
+        body.u32(function.code.len() as u32);
+
+        for op in &function.code {
+            let (opcode, operands) = encode_op(*op);
+            body.u8(opcode);
+            for operand in operands {
+                body.u32(operand);
+            }
+        }
+    }
+
+    for function in &module.functions {
+        for line in &function.lines {
+            body.u32(*line);
+        }
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.extend(checksum(&body.bytes).to_le_bytes());
+    bytes.extend(body.bytes);
+
+    bytes
+}
+
+//Reads a bytecode file, checking that it can run
+//without crashing the virtual machine
+pub fn decode(bytes: &[u8]) -> JuvinilResult<Module> {
+    if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+        return Err(invalid("missing the `JVBC` header"));
+    }
+
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != VERSION {
+        return Err(JuvinilError::IncompatibleBytecode(version, VERSION));
+    }
+
+    let expected = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]);
+    if checksum(&bytes[HEADER_SIZE..]) != expected {
+        return Err(invalid("the checksum doesn't match its contents"));
+    }
+
+    let mut reader = Reader {
+        bytes: &bytes[HEADER_SIZE..],
+        position: 0,
+    };
+    let mut module = Module::default();
+
+    for _ in 0..reader.count()? {
+        let constant = match reader.u8()? {
+            0 => Value::Int(reader.u32()? as i32),
+            1 => Value::Bool(reader.u8()? != 0),
+            2 => Value::Str(reader.bytes()?),
+            tag => return Err(invalid(&format!("unknown constant tag {}", tag))),
+        };
+        module.constants.push(constant);
+    }
+
+    for _ in 0..reader.count()? {
+        module.globals.push(reader.string()?);
+    }
+
+    for _ in 0..reader.count()? {
+        let name = reader.string()?;
+        let arity = reader.u32()?;
+        let locals = reader.u32()?;
+
+        let mut code = Vec::new();
+        for _ in 0..reader.count()? {
+            code.push(reader.op()?);
+        }
+
+        module.functions.push(Function {
+            name,
+            arity,
+            locals,
+            code,
+            lines: Vec::new(),
+        });
+    }
+
+    for function in &mut module.functions {
+        for _ in 0..function.code.len() {
+            function.lines.push(reader.u32()?);
+        }
+    }
+
+    if reader.position != reader.bytes.len() {
+        return Err(invalid("unexpected bytes after the line table"));
+    }
+
+    verify(&module)?;
+
+    Ok(module)
+}
+
+//Checks that every operand points at something that exists, and that
+//each instruction always finds the values it pops on the stack
+pub fn verify(module: &Module) -> JuvinilResult<()> {
+    match module.functions.first() {
+        Some(main) if main.arity == 0 => {}
+        _ => return Err(invalid("the main program is missing")),
+    }
+
+    for function in &module.functions {
+        let error = |message: String| invalid(&format!("in `{}`, {}", function.name, message));
+
+        if function.arity > function.locals {
+            return Err(error("the parameters don't fit in the locals".into()));
+        }
+
+        //Every local besides the parameters is stored before it's read, so
+        //more locals than that only make the machine allocate memory
+        let used = function.code.iter().filter_map(|op| match op {
+            Op::LoadLocal(slot) | Op::StoreLocal(slot) => Some(slot + 1),
+            _ => None,
+        });
+        if function.locals > used.max().unwrap_or(0).max(function.arity) {
+            return Err(error("it has more locals than it uses".into()));
+        }
+
+        //Stack size before each instruction, not counting the locals
+        let mut heights: Vec<Option<u32>> = vec![None; function.code.len()];
+        let mut pending: Vec<(usize, u32)> = vec![(0, 0)];
+
+        while let Some((ip, height)) = pending.pop() {
+            let Some(op) = function.code.get(ip) else {
+                return Err(error("the code runs past its end".into()));
+            };
+
+            match heights[ip] {
+                Some(known) if known == height => continue,
+                Some(_) => {
+                    return Err(error(format!(
+                        "the stack has different sizes at instruction {}",
+                        ip
+                    )))
+                }
+                None => heights[ip] = Some(height),
+            }
+
+            let in_range = |index: u32, len: usize| (index as usize) < len;
+            let valid = match *op {
+                Op::Const(index) => in_range(index, module.constants.len()),
+                Op::LoadLocal(slot) | Op::StoreLocal(slot) => slot < function.locals,
+                Op::LoadGlobal(index) | Op::StoreGlobal(index) => {
+                    in_range(index, module.globals.len())
+                }
+                Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target) => {
+                    in_range(target, function.code.len())
+                }
+                Op::Call(index) => index > 0 && in_range(index, module.functions.len()),
+                Op::CallBuiltin(index, argc) => {
+                    in_range(index, BUILTINS.len())
+                        && match builtins::param_count(BUILTINS[index as usize]) {
+                            Some(count) => count == argc as usize,
+                            None => BUILTINS[index as usize] != "printf" || argc > 0,
+                        }
+                }
+                _ => true,
+            };
+            if !valid {
+                return Err(error(format!("invalid operand of instruction {}", ip)));
+            }
+
+            let (pops, pushes) = stack_effect(module, *op);
+            if height < pops {
+                return Err(error(format!("instruction {} pops an empty stack", ip)));
+            }
+            let height = height - pops + pushes;
+
+            match *op {
+                Op::Jump(target) => pending.push((target as usize, height)),
+                Op::JumpIfFalse(target) | Op::JumpIfTrue(target) => {
+                    pending.push((target as usize, height));
+                    pending.push((ip + 1, height));
+                }
+                Op::Return | Op::ReturnVoid => {}
+                _ => pending.push((ip + 1, height)),
+            }
+        }
+
+        if function.lines.len() != function.code.len() {
+            return Err(error("the line table doesn't match the code".into()));
+        }
+    }
+
+    Ok(())
+}
+
+//How many values an instruction pops, and how many it pushes
+fn stack_effect(module: &Module, op: Op) -> (u32, u32) {
+    match op {
+        Op::Const(_) | Op::LoadLocal(_) | Op::LoadGlobal(_) => (0, 1),
+        Op::Pop | Op::StoreLocal(_) | Op::StoreGlobal(_) => (1, 0),
+        Op::Dup => (1, 2),
+        Op::Neg | Op::Not => (1, 1),
+        Op::Interpolate(count) => (count, 1),
+        Op::Jump(_) | Op::ReturnVoid => (0, 0),
+        Op::JumpIfFalse(_) | Op::JumpIfTrue(_) | Op::Return => (1, 0),
+        Op::Call(index) => (module.functions[index as usize].arity, 1),
+        Op::CallBuiltin(_, argc) => (argc, 1),
+        _ => (2, 1),
+    }
+}
+
+fn encode_op(op: Op) -> (u8, Vec<u32>) {
+    match op {
+        Op::Const(index) => (0, vec![index]),
+        Op::Pop => (1, vec![]),
+        Op::Dup => (2, vec![]),
+        Op::LoadLocal(slot) => (3, vec![slot]),
+        Op::StoreLocal(slot) => (4, vec![slot]),
+        Op::LoadGlobal(index) => (5, vec![index]),
+        Op::StoreGlobal(index) => (6, vec![index]),
+        Op::Add => (7, vec![]),
+        Op::Sub => (8, vec![]),
+        Op::Mul => (9, vec![]),
+        Op::Div => (10, vec![]),
+        Op::Rem => (11, vec![]),
+        Op::BitAnd => (12, vec![]),
+        Op::BitOr => (13, vec![]),
+        Op::Neg => (14, vec![]),
+        Op::Not => (15, vec![]),
+        Op::Concat => (16, vec![]),
+        Op::Eq => (17, vec![]),
+        Op::Ne => (18, vec![]),
+        Op::Lt => (19, vec![]),
+        Op::Le => (20, vec![]),
+        Op::Gt => (21, vec![]),
+        Op::Ge => (22, vec![]),
+        Op::Interpolate(count) => (23, vec![count]),
+        Op::Jump(target) => (24, vec![target]),
+        Op::JumpIfFalse(target) => (25, vec![target]),
+        Op::JumpIfTrue(target) => (26, vec![target]),
+        Op::Call(index) => (27, vec![index]),
+        Op::CallBuiltin(index, argc) => (28, vec![index, argc]),
+        Op::Return => (29, vec![]),
+        Op::ReturnVoid => (30, vec![]),
+    }
+}
+
+//FNV-1a, which is enough to notice a damaged file
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+fn invalid(reason: &str) -> JuvinilError {
+    JuvinilError::InvalidBytecode(reason.into())
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.bytes.extend(value);
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> JuvinilResult<&'b [u8]> {
+        let end = self.position.saturating_add(len);
+        let Some(taken) = self.bytes.get(self.position..end) else {
+            return Err(invalid("the file ends too early"));
+        };
+
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> JuvinilResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> JuvinilResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    //Number of items of a section, which can't be more
+    //than the bytes left, so a damaged count fails early
+    fn count(&mut self) -> JuvinilResult<u32> {
+        let count = self.u32()?;
+        match count as usize <= self.bytes.len() - self.position {
+            true => Ok(count),
+            false => Err(invalid("the file ends too early")),
+        }
+    }
+
+    //Names are UTF-8, while string constants can hold any bytes
+    fn string(&mut self) -> JuvinilResult<String> {
+        String::from_utf8(self.bytes()?).map_err(|_| invalid("a name isn't UTF-8"))
+    }
+
+    fn bytes(&mut self) -> JuvinilResult<Vec<u8>> {
+        let len = self.u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn op(&mut self) -> JuvinilResult<Op> {
+        let op = match self.u8()? {
+            0 => Op::Const(self.u32()?),
+            1 => Op::Pop,
+            2 => Op::Dup,
+            3 => Op::LoadLocal(self.u32()?),
+            4 => Op::StoreLocal(self.u32()?),
+            5 => Op::LoadGlobal(self.u32()?),
+            6 => Op::StoreGlobal(self.u32()?),
+            7 => Op::Add,
+            8 => Op::Sub,
+            9 => Op::Mul,
+            10 => Op::Div,
+            11 => Op::Rem,
+            12 => Op::BitAnd,
+            13 => Op::BitOr,
+            14 => Op::Neg,
+            15 => Op::Not,
+            16 => Op::Concat,
+            17 => Op::Eq,
+            18 => Op::Ne,
+            19 => Op::Lt,
+            20 => Op::Le,
+            21 => Op::Gt,
+            22 => Op::Ge,
+            23 => Op::Interpolate(self.u32()?),
+            24 => Op::Jump(self.u32()?),
+            25 => Op::JumpIfFalse(self.u32()?),
+            26 => Op::JumpIfTrue(self.u32()?),
+            27 => Op::Call(self.u32()?),
+            28 => Op::CallBuiltin(self.u32()?, self.u32()?),
+            29 => Op::Return,
+            30 => Op::ReturnVoid,
+            opcode => return Err(invalid(&format!("unknown opcode {}", opcode))),
+        };
+
+        Ok(op)
+    }
+}
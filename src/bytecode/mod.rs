@@ -0,0 +1,64 @@
+pub mod compiler;
+pub mod disassembler;
+pub mod file;
+pub mod vm;
+
+use crate::interpreter::value::Value;
+
+//Instructions of the stack machine. Operands are indexes: of the
+//constant pool, of a local slot, of a global, of an instruction of
+//the same function (jumps) or of a function of the module
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Const(u32), //Pushes a constant
+    Pop,
+    Dup, //Pushes a copy of the top of the stack
+    LoadLocal(u32),
+    StoreLocal(u32), //Pops the value into a local slot
+    LoadGlobal(u32),
+    StoreGlobal(u32),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    Neg,
+    Not,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Interpolate(u32), //Pops values and joins them into a string, like `print` writes them
+    Jump(u32),
+    JumpIfFalse(u32),      //Pops a boolean, jumping if it's false
+    JumpIfTrue(u32),       //Pops a boolean, jumping if it's true
+    Call(u32),             //Calls a function, whose arguments are on the stack
+    CallBuiltin(u32, u32), //Index in `builtins::BUILTINS` and number of arguments
+    Return,                //Returns the value on the top of the stack
+    ReturnVoid,            //Returns from a `void` function
+}
+
+//A function compiled to bytecode. Parameters are the first
+//local slots, followed by every variable declared in its body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub arity: u32,  //Number of parameters
+    pub locals: u32, //Number of local slots, including the parameters
+    pub code: Vec<Op>,
+    pub lines: Vec<u32>, //Source line of each instruction, for errors
+}
+
+//A compiled program. The first function is the main program,
+//which initializes the globals before running its statements
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Module {
+    pub constants: Vec<Value>,
+    pub globals: Vec<String>, //Names of the globals, for the disassembler
+    pub functions: Vec<Function>,
+}
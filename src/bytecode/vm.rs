@@ -0,0 +1,241 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    error::{JuvinilError, JuvinilResult},
+    interpreter::{
+        runtime::{self, Io},
+        value::Value,
+        MAX_CALL_DEPTH,
+    },
+    syntax_analysis::builtins::BUILTINS,
+};
+
+use super::{Module, Op};
+
+//Runs a module with the given input, returning its
+//exit status along with everything it wrote
+pub fn run_captured(module: &Module, input: &str) -> JuvinilResult<(i32, String)> {
+    let mut input = input.as_bytes();
+    let mut output: Vec<u8> = Vec::new();
+
+    let status = Vm::new(module, &mut input, &mut output).run()?;
+
+    Ok((status, String::from_utf8_lossy(&output).into_owned()))
+}
+
+//A running function. Its locals are the slots of the
+//value stack starting at `base`, followed by its temporaries
+struct CallFrame {
+    function: usize,
+    ip: usize,
+    base: usize,
+}
+
+//Runs bytecode on a value stack. Unlike the interpreter, calls of the
+//program don't nest calls of the machine, so it needs no larger stack
+pub struct Vm<'a> {
+    module: &'a Module,
+    io: Io<'a>,
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    globals: Vec<Value>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(module: &'a Module, input: &'a mut dyn BufRead, output: &'a mut dyn Write) -> Self {
+        Vm {
+            module,
+            io: Io::new(input, output),
+            stack: Vec::new(),
+            frames: Vec::new(),
+            globals: vec![Value::Void; module.globals.len()],
+        }
+    }
+
+    //Runs the main program, returning its exit status
+    pub fn run(&mut self) -> JuvinilResult<i32> {
+        let result = self.execute();
+        self.io.flush()?;
+
+        result
+    }
+
+    fn execute(&mut self) -> JuvinilResult<i32> {
+        let module = self.module;
+        self.stack
+            .resize(module.functions[0].locals as usize, Value::Void);
+        self.frames.push(CallFrame {
+            function: 0,
+            ip: 0,
+            base: 0,
+        });
+
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let function = &module.functions[frame.function];
+            let op = function.code[frame.ip];
+            let line = function.lines[frame.ip] as usize;
+            let base = frame.base;
+            frame.ip += 1;
+
+            match op {
+                Op::Const(index) => self.stack.push(module.constants[index as usize].clone()),
+                Op::Pop => {
+                    self.pop()?;
+                }
+                Op::Dup => self.stack.push(self.stack.last().unwrap().clone()),
+                Op::LoadLocal(slot) => self.stack.push(self.stack[base + slot as usize].clone()),
+                Op::StoreLocal(slot) => self.stack[base + slot as usize] = self.pop()?,
+                Op::LoadGlobal(index) => self.stack.push(self.globals[index as usize].clone()),
+                Op::StoreGlobal(index) => self.globals[index as usize] = self.pop()?,
+                Op::Add => self.arithmetic(i32::wrapping_add)?,
+                Op::Sub => self.arithmetic(i32::wrapping_sub)?,
+                Op::Mul => self.arithmetic(i32::wrapping_mul)?,
+                Op::Div | Op::Rem => {
+                    let right = self.pop()?.checked_int()?;
+                    let left = self.pop()?.checked_int()?;
+
+                    if right == 0 {
+                        return Err(JuvinilError::DivisionByZero(line));
+                    }
+
+                    self.stack.push(Value::Int(match op {
+                        Op::Div => left.wrapping_div(right),
+                        _ => left.wrapping_rem(right),
+                    }));
+                }
+                Op::BitAnd => self.arithmetic(|left, right| left & right)?,
+                Op::BitOr => self.arithmetic(|left, right| left | right)?,
+                Op::Neg => {
+                    let value = self.pop()?.checked_int()?;
+                    self.stack.push(Value::Int(value.wrapping_neg()));
+                }
+                Op::Not => {
+                    let value = self.pop()?.checked_bool()?;
+                    self.stack.push(Value::Bool(!value));
+                }
+                Op::Concat => {
+                    let right = self.pop()?;
+                    let left = self.pop()?;
+                    let text = [left.checked_str()?, right.checked_str()?].concat();
+                    self.stack.push(Value::Str(text));
+                }
+                Op::Eq => {
+                    let right = self.pop()?;
+                    let left = self.pop()?;
+                    self.stack.push(Value::Bool(left == right));
+                }
+                Op::Ne => {
+                    let right = self.pop()?;
+                    let left = self.pop()?;
+                    self.stack.push(Value::Bool(left != right));
+                }
+                Op::Lt | Op::Le | Op::Gt | Op::Ge => self.compare(op)?,
+                Op::Interpolate(count) => {
+                    let parts = self.stack.split_off(self.stack.len() - count as usize);
+                    let text: Vec<u8> = parts.iter().flat_map(Value::to_bytes).collect();
+                    self.stack.push(Value::Str(text));
+                }
+                Op::Jump(target) => self.jump(target),
+                Op::JumpIfFalse(target) => {
+                    if !self.pop()?.checked_bool()? {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfTrue(target) => {
+                    if self.pop()?.checked_bool()? {
+                        self.jump(target);
+                    }
+                }
+                Op::Call(index) => {
+                    let callee = &module.functions[index as usize];
+
+                    if self.frames.len() > MAX_CALL_DEPTH {
+                        return Err(JuvinilError::StackOverflow(callee.name.clone(), line));
+                    }
+
+                    //The arguments already on the stack are the first locals
+                    let base = self.stack.len() - callee.arity as usize;
+                    self.stack
+                        .resize(base + callee.locals as usize, Value::Void);
+                    self.frames.push(CallFrame {
+                        function: index as usize,
+                        ip: 0,
+                        base,
+                    });
+                }
+                Op::CallBuiltin(index, argc) => {
+                    let args = self.stack.split_off(self.stack.len() - argc as usize);
+                    let func_name = BUILTINS[index as usize];
+
+                    if func_name == "exit" {
+                        return args[0].checked_int();
+                    }
+
+                    let value = runtime::call_builtin(&mut self.io, func_name, &args)?;
+                    self.stack.push(value);
+                }
+                Op::Return | Op::ReturnVoid => {
+                    let value = match op {
+                        Op::Return => self.pop()?,
+                        _ => Value::Void,
+                    };
+
+                    self.frames.pop();
+                    self.stack.truncate(base);
+
+                    //Returning from the main program ends it
+                    if self.frames.is_empty() {
+                        return match value {
+                            Value::Void => Ok(0),
+                            value => value.checked_int(),
+                        };
+                    }
+
+                    self.stack.push(value);
+                }
+            }
+        }
+    }
+
+    //Values are checked instead of trusted, since a bytecode
+    //file can be damaged in ways its verification doesn't notice
+    fn pop(&mut self) -> JuvinilResult<Value> {
+        self.stack
+            .pop()
+            .ok_or_else(|| JuvinilError::InvalidBytecode("popped an empty stack".into()))
+    }
+
+    fn jump(&mut self, target: u32) {
+        self.frames.last_mut().unwrap().ip = target as usize;
+    }
+
+    fn arithmetic(&mut self, operation: impl Fn(i32, i32) -> i32) -> JuvinilResult<()> {
+        let right = self.pop()?.checked_int()?;
+        let left = self.pop()?.checked_int()?;
+        self.stack.push(Value::Int(operation(left, right)));
+
+        Ok(())
+    }
+
+    //Strings are compared by their bytes, and anything else as an int
+    fn compare(&mut self, op: Op) -> JuvinilResult<()> {
+        let right = self.pop()?;
+        let left = self.pop()?;
+
+        let ordering = match (&left, &right) {
+            (Value::Str(left), Value::Str(right)) => left.cmp(right),
+            (left, right) => left.checked_int()?.cmp(&right.checked_int()?),
+        };
+
+        let result = match op {
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            _ => ordering.is_ge(),
+        };
+        self.stack.push(Value::Bool(result));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use crate::error::{JuvinilError, JuvinilResult};
+
+pub const HELP: &str = "\
+Usage: juvinil <command> [options] <file>
+
+Commands:
+    build <file> [-o <output>]       Compile the file into an executable
+    run <file> [-- <args>...]        Compile and run the file, passing it the arguments
+    check <file>                     Check the file for errors and warnings, without compiling it
+    exec <file.jvc>                  Run a bytecode file written by `build --target bytecode`
+    emit <file> --stage <stage>      Print the tokens, the program tree, the C++, C, assembly or WebAssembly code or the bytecode of the file
+    help                             Print this message
+
+A file named `-` is read from the standard input.
+
+Options:
+    -o <path>                        Where `build` writes the executable or bytecode, or `emit` writes its output
+    --stage <tokens|ast|cpp|c|asm|wat|bytecode>
+                                     What `emit` prints (`cpp` by default)
+    -A <lint>, -W <lint>, -D <lint>  Allow, warn or deny a warning, like `-D unused-variable`
+    -W error                         Treat every warning as an error
+    --target <cpp|interpreter|vm>    How `run` runs the file: compiled to C++ (the default),
+                                     interpreted, or compiled to bytecode for the virtual machine
+    --target c                       Make `build` and `run` compile the file to C instead of C++
+    --target asm                     Make `build` and `run` compile the file to x86-64 assembly, built with `as` and `ld`
+    --target bytecode                Make `build` write a bytecode file instead of an executable
+    --cxx <compiler>                 C++ compiler to use, instead of the `CXX` variable or the first one installed
+    --cc <compiler>                  C compiler to use, instead of the `CC` variable or the first one installed
+    --opt <0|1|2|3|s>                Optimization level of the C or C++ compiler (`2` by default)
+    --cxx-flag <flag>                Extra flag for the C or C++ compiler, like `--cxx-flag -Wall`
+    --pause                          Make the program wait for Enter before ending
+    --timeout <seconds>              Stop the program if `run` takes longer than this
+    -v, --verbose                    Log every step of the compiler
+
+Exit codes:
+    0    success, or the exit status of the program that `run` ran
+    1    the file has errors, or couldn't be compiled
+    2    the command line is invalid
+    124  the program timed out
+    128+ the program was terminated by a signal
+";
+
+//Steps of the compiler whose results `emit` can print
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Tokens,
+    Ast,
+    Cpp,
+    C,
+    Asm,
+    Wat,      //WebAssembly text format
+    Bytecode, //Disassembled
+}
+
+//How a program is executed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Cpp,         //Compiled to C++ by the native toolchain
+    C,           //Compiled to C99 by the native toolchain
+    Asm,         //Compiled to x86-64 assembly, built by `as` and `ld`
+    Interpreter, //Interpreted, without compiling it
+    Vm,          //Compiled to bytecode and run by the virtual machine
+    Bytecode,    //Compiled to a bytecode file, run later by `exec`
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    File(String),
+    Stdin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Build(Option<String>),       //Path of the executable
+    Run(Vec<String>),            //Arguments of the program
+    Check,                       //Only the front-end runs
+    Exec,                        //The input is a bytecode file
+    Emit(Stage, Option<String>), //What to print, and where
+    Help,
+}
+
+//Everything given in the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub command: CliCommand,
+    pub input: Input,
+    pub target: Target,
+    pub warning_args: Vec<String>, //Parsed by `WarningConfig::from_args`
+    pub cxx: Option<String>,
+    pub cc: Option<String>,
+    pub opt_level: Option<String>,
+    pub cxx_flags: Vec<String>,
+    pub pause_on_exit: bool,
+    pub timeout: Option<u64>, //Seconds the program can run for
+    pub verbose: bool,
+}
+
+impl Cli {
+    //Parses the arguments, without the name of the program
+    pub fn parse(args: &[String]) -> JuvinilResult<Self> {
+        let mut cli = Cli {
+            command: CliCommand::Help,
+            input: Input::Stdin,
+            target: Target::Cpp,
+            warning_args: Vec::new(),
+            cxx: None,
+            cc: None,
+            opt_level: None,
+            cxx_flags: Vec::new(),
+            pause_on_exit: false,
+            timeout: None,
+            verbose: false,
+        };
+
+        let Some(command) = args.first() else {
+            return Ok(cli);
+        };
+
+        let mut output: Option<String> = None;
+        let mut stage: Option<Stage> = None;
+        let mut program_args: Vec<String> = Vec::new();
+        let mut input: Option<Input> = None;
+        let mut args = args[1..].iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or(JuvinilError::InvalidArgument(arg.clone()))
+            };
+
+            match arg.as_str() {
+                "-A" | "-W" | "-D" => {
+                    let lint = value()?;
+                    cli.warning_args.extend([arg.clone(), lint]);
+                }
+                "-o" => output = Some(value()?),
+                "--stage" => stage = Some(parse_stage(&value()?)?),
+                "--target" => cli.target = parse_target(&value()?)?,
+                "--cxx" => cli.cxx = Some(value()?),
+                "--cc" => cli.cc = Some(value()?),
+                "--opt" => cli.opt_level = Some(value()?),
+                "--cxx-flag" => cli.cxx_flags.push(value()?),
+                "--pause" => cli.pause_on_exit = true,
+                "--timeout" => {
+                    let seconds = value()?;
+                    let seconds = seconds.parse::<u64>().map_err(|_| {
+                        JuvinilError::InvalidArgument(format!("--timeout {}", seconds))
+                    })?;
+                    cli.timeout = Some(seconds);
+                }
+                "-v" | "--verbose" => cli.verbose = true,
+                "--" if command == "run" => {
+                    program_args = args.by_ref().cloned().collect();
+                }
+                "-" if input.is_none() => input = Some(Input::Stdin),
+                path if input.is_none() && !path.starts_with('-') => {
+                    input = Some(Input::File(path.into()))
+                }
+                _ => return Err(JuvinilError::InvalidArgument(arg.clone())),
+            }
+        }
+
+        //Options that don't apply to the command are mistakes
+        let target_commands = match cli.target {
+            Target::C | Target::Asm => ["build", "run"].as_slice(),
+            Target::Bytecode => ["build"].as_slice(),
+            _ => ["run"].as_slice(),
+        };
+        let options = [
+            ("-o", output.is_some(), ["build", "emit"].as_slice()),
+            ("--stage", stage.is_some(), ["emit"].as_slice()),
+            ("--timeout", cli.timeout.is_some(), ["run"].as_slice()),
+            ("--target", cli.target != Target::Cpp, target_commands),
+        ];
+        let misplaced = options
+            .iter()
+            .find(|(_, given, commands)| *given && !commands.contains(&command.as_str()));
+        if let Some((option, ..)) = misplaced {
+            return Err(JuvinilError::InvalidArgument(option.to_string()));
+        }
+
+        //Only native programs can be timed out or given arguments,
+        //since programs can't read their arguments yet
+        let native = matches!(cli.target, Target::Cpp | Target::C | Target::Asm);
+        if !native && cli.timeout.is_some() {
+            return Err(JuvinilError::InvalidArgument("--timeout".into()));
+        }
+        if !native && !program_args.is_empty() {
+            return Err(JuvinilError::InvalidArgument("--".into()));
+        }
+
+        cli.command = match command.as_str() {
+            "build" => CliCommand::Build(output),
+            "run" => CliCommand::Run(program_args),
+            "check" => CliCommand::Check,
+            "exec" => CliCommand::Exec,
+            "emit" => CliCommand::Emit(stage.unwrap_or(Stage::Cpp), output),
+            "help" | "-h" | "--help" => return Ok(cli),
+            _ => return Err(JuvinilError::InvalidArgument(command.clone())),
+        };
+
+        cli.input = input.ok_or(JuvinilError::MissingInput)?;
+
+        Ok(cli)
+    }
+}
+
+fn parse_target(target: &str) -> JuvinilResult<Target> {
+    match target {
+        "cpp" => Ok(Target::Cpp),
+        "c" => Ok(Target::C),
+        "asm" => Ok(Target::Asm),
+        "interpreter" => Ok(Target::Interpreter),
+        "vm" => Ok(Target::Vm),
+        "bytecode" => Ok(Target::Bytecode),
+        _ => Err(JuvinilError::InvalidArgument(format!(
+            "--target {}",
+            target
+        ))),
+    }
+}
+
+fn parse_stage(stage: &str) -> JuvinilResult<Stage> {
+    match stage {
+        "tokens" => Ok(Stage::Tokens),
+        "ast" => Ok(Stage::Ast),
+        "cpp" => Ok(Stage::Cpp),
+        "c" => Ok(Stage::C),
+        "asm" => Ok(Stage::Asm),
+        "wat" => Ok(Stage::Wat),
+        "bytecode" => Ok(Stage::Bytecode),
+        _ => Err(JuvinilError::InvalidArgument(format!("--stage {}", stage))),
+    }
+}
@@ -0,0 +1,1448 @@
+use std::collections::HashMap;
+
+use crate::{
+    interpreter::runtime::unescape,
+    syntax_analysis::{
+        ast::{Expr, ExprKind, Program, Stmt, StmtKind},
+        builtins::RUNTIME_BUILTINS,
+    },
+};
+
+use super::function_name;
+
+//Runtime of the generated programs, which talk to Linux through system
+//calls instead of linking the C library. A string is a pointer to its
+//length, a quadword, followed by its bytes. Strings are allocated from
+//memory mapped in big chunks and, like in the C backend, never freed.
+//Output is buffered and flushed before reading and when the program ends.
+//Helpers follow the System V calling convention, except `jv_builder_push`,
+//which grows the string kept in r12 (bytes), r13 (length) and r14 (capacity)
+const RUNTIME: &str = r#"    .text
+    .globl _start
+_start:
+    call jv_main
+    movl %eax, %edi
+    jmp jv_exit
+
+jv_exit:
+    pushq %rdi
+    cmpq $0, jv_pause_on_exit(%rip)
+    je 1f
+    call jv_pause
+1:
+    call jv_flush
+    popq %rdi
+    movl $231, %eax
+    syscall
+
+jv_write_all:
+    testq %rdx, %rdx
+    jz 2f
+1:
+    movl $1, %eax
+    syscall
+    testq %rax, %rax
+    js 2f
+    addq %rax, %rsi
+    subq %rax, %rdx
+    jnz 1b
+2:
+    ret
+
+jv_flush:
+    movq jv_out_len(%rip), %rdx
+    movq $0, jv_out_len(%rip)
+    movl $1, %edi
+    leaq jv_out_buf(%rip), %rsi
+    jmp jv_write_all
+
+jv_write:
+    movq jv_out_len(%rip), %rax
+    leaq (%rax,%rsi), %rdx
+    cmpq $4096, %rdx
+    jbe 2f
+    pushq %rdi
+    pushq %rsi
+    call jv_flush
+    popq %rsi
+    popq %rdi
+    xorl %eax, %eax
+    cmpq $4096, %rsi
+    jbe 2f
+    movq %rsi, %rdx
+    movq %rdi, %rsi
+    movl $1, %edi
+    jmp jv_write_all
+2:
+    movq %rsi, %rcx
+    movq %rdi, %rsi
+    leaq jv_out_buf(%rip), %rdi
+    addq %rax, %rdi
+    addq %rcx, %rax
+    movq %rax, jv_out_len(%rip)
+    rep movsb
+    ret
+
+jv_print_str:
+    movq (%rdi), %rsi
+    addq $8, %rdi
+    jmp jv_write
+
+jv_print_newline:
+    leaq jv_newline(%rip), %rdi
+    movl $1, %esi
+    jmp jv_write
+
+jv_print_int:
+    call jv_to_string
+    movq %rax, %rdi
+    jmp jv_print_str
+
+jv_print_bool:
+    call jv_bool_string
+    movq %rax, %rdi
+    jmp jv_print_str
+
+jv_bool_string:
+    leaq jv_false_str(%rip), %rax
+    leaq jv_true_str(%rip), %rdx
+    testl %edi, %edi
+    cmovnz %rdx, %rax
+    ret
+
+jv_alloc:
+    addq $15, %rdi
+    andq $-16, %rdi
+    movq jv_heap_ptr(%rip), %rax
+    leaq (%rax,%rdi), %rdx
+    cmpq jv_heap_end(%rip), %rdx
+    ja 1f
+    movq %rdx, jv_heap_ptr(%rip)
+    ret
+1:
+    pushq %rdi
+    movl $16777216, %esi
+    cmpq %rsi, %rdi
+    cmova %rdi, %rsi
+    pushq %rsi
+    movl $9, %eax
+    xorl %edi, %edi
+    movl $3, %edx
+    movl $34, %r10d
+    movq $-1, %r8
+    xorl %r9d, %r9d
+    syscall
+    popq %rsi
+    popq %rdi
+    cmpq $-4096, %rax
+    ja jv_out_of_memory
+    leaq (%rax,%rsi), %rdx
+    movq %rdx, jv_heap_end(%rip)
+    leaq (%rax,%rdi), %rdx
+    movq %rdx, jv_heap_ptr(%rip)
+    ret
+
+jv_out_of_memory:
+    movl $2, %edi
+    leaq jv_out_of_memory_msg(%rip), %rsi
+    movl $14, %edx
+    call jv_write_all
+    movl $1, %edi
+    jmp jv_exit
+
+jv_new_string:
+    pushq %rdi
+    pushq %rsi
+    leaq 8(%rsi), %rdi
+    call jv_alloc
+    popq %rcx
+    popq %rsi
+    movq %rcx, (%rax)
+    leaq 8(%rax), %rdi
+    rep movsb
+    ret
+
+jv_concat:
+    pushq %rdi
+    pushq %rsi
+    movq (%rdi), %rdi
+    addq (%rsi), %rdi
+    addq $8, %rdi
+    call jv_alloc
+    popq %rdx
+    popq %r8
+    movq (%r8), %rcx
+    movq %rcx, %r9
+    addq (%rdx), %r9
+    movq %r9, (%rax)
+    leaq 8(%rax), %rdi
+    leaq 8(%r8), %rsi
+    rep movsb
+    movq (%rdx), %rcx
+    leaq 8(%rdx), %rsi
+    rep movsb
+    ret
+
+jv_str_cmp:
+    movq (%rdi), %r8
+    movq (%rsi), %r9
+    movq %r8, %rcx
+    cmpq %r9, %rcx
+    cmova %r9, %rcx
+    addq $8, %rdi
+    addq $8, %rsi
+    xorl %edx, %edx
+1:
+    cmpq %rcx, %rdx
+    je 2f
+    movzbl (%rdi,%rdx), %eax
+    movzbl (%rsi,%rdx), %r10d
+    incq %rdx
+    cmpl %r10d, %eax
+    je 1b
+    jmp 3f
+2:
+    cmpq %r9, %r8
+3:
+    seta %al
+    setb %dl
+    subb %dl, %al
+    movsbl %al, %eax
+    ret
+
+jv_contains:
+    movq (%rdi), %r8
+    movq (%rsi), %r9
+    addq $8, %rdi
+    addq $8, %rsi
+    xorl %eax, %eax
+    cmpq %r9, %r8
+    jb 4f
+    subq %r9, %r8
+    xorl %ecx, %ecx
+1:
+    xorl %edx, %edx
+2:
+    cmpq %r9, %rdx
+    je 3f
+    leaq (%rcx,%rdx), %r10
+    movb (%rdi,%r10), %r11b
+    cmpb (%rsi,%rdx), %r11b
+    jne 5f
+    incq %rdx
+    jmp 2b
+5:
+    incq %rcx
+    cmpq %r8, %rcx
+    jbe 1b
+    jmp 4f
+3:
+    movl $1, %eax
+4:
+    ret
+
+jv_substr:
+    movq (%rdi), %r8
+    movslq %esi, %rsi
+    movslq %edx, %rdx
+    testq %rsi, %rsi
+    jns 1f
+    xorl %esi, %esi
+1:
+    cmpq %r8, %rsi
+    jbe 2f
+    movq %r8, %rsi
+2:
+    testq %rdx, %rdx
+    jns 3f
+    xorl %edx, %edx
+3:
+    movq %r8, %rcx
+    subq %rsi, %rcx
+    cmpq %rcx, %rdx
+    jbe 4f
+    movq %rcx, %rdx
+4:
+    leaq 8(%rdi,%rsi), %rdi
+    movq %rdx, %rsi
+    jmp jv_new_string
+
+jv_u64_string:
+    subq $32, %rsp
+    leaq 32(%rsp), %r8
+    movq %rdi, %rax
+    movl $10, %ecx
+1:
+    xorl %edx, %edx
+    divq %rcx
+    addb $48, %dl
+    decq %r8
+    movb %dl, (%r8)
+    testq %rax, %rax
+    jnz 1b
+    leaq 32(%rsp), %rsi
+    subq %r8, %rsi
+    movq %r8, %rdi
+    call jv_new_string
+    addq $32, %rsp
+    ret
+
+jv_to_string:
+    movslq %edi, %rdi
+    testq %rdi, %rdi
+    jns jv_u64_string
+    negq %rdi
+    call jv_u64_string
+    leaq jv_minus_str(%rip), %rdi
+    movq %rax, %rsi
+    jmp jv_concat
+
+jv_format_int:
+    pushq %rbx
+    pushq %r12
+    pushq %r13
+    movslq %edi, %rbx
+    movl %esi, %r12d
+    movq %rdx, %r13
+    movq %rbx, %rdi
+    testq %rdi, %rdi
+    jns 1f
+    negq %rdi
+    leaq jv_minus_str(%rip), %r13
+1:
+    call jv_u64_string
+    testl %r12d, %r12d
+    js 3f
+    jnz 2f
+    testq %rbx, %rbx
+    jnz 2f
+    leaq jv_empty(%rip), %rax
+    jmp 3f
+2:
+    movq %rax, %rdi
+    movl %r12d, %esi
+    movl $2, %edx
+    call jv_pad
+3:
+    movq %r13, %rdi
+    movq %rax, %rsi
+    call jv_concat
+    popq %r13
+    popq %r12
+    popq %rbx
+    ret
+
+jv_pad:
+    movslq %esi, %rsi
+    movq (%rdi), %rcx
+    cmpq %rcx, %rsi
+    jg 1f
+    movq %rdi, %rax
+    ret
+1:
+    pushq %rbx
+    pushq %r12
+    pushq %r13
+    pushq %r14
+    movq %rdi, %rbx
+    movq %rsi, %r12
+    movl %edx, %r13d
+    leaq 8(%rsi), %rdi
+    call jv_alloc
+    movq %r12, (%rax)
+    movq %rax, %r14
+    leaq 8(%rax), %rdi
+    leaq 8(%rbx), %rsi
+    movq (%rbx), %rdx
+    movq %r12, %r8
+    subq %rdx, %r8
+    cmpl $1, %r13d
+    je 4f
+    movb $32, %al
+    cmpl $2, %r13d
+    jne 3f
+    movb $48, %al
+    testq %rdx, %rdx
+    jz 3f
+    movb (%rsi), %cl
+    cmpb $45, %cl
+    je 2f
+    cmpb $43, %cl
+    je 2f
+    cmpb $32, %cl
+    jne 3f
+2:
+    movsb
+    decq %rdx
+3:
+    movq %r8, %rcx
+    rep stosb
+    movq %rdx, %rcx
+    rep movsb
+    jmp 5f
+4:
+    movq %rdx, %rcx
+    rep movsb
+    movq %r8, %rcx
+    movb $32, %al
+    rep stosb
+5:
+    movq %r14, %rax
+    popq %r14
+    popq %r13
+    popq %r12
+    popq %rbx
+    ret
+
+jv_scan_int:
+    movq (%rdi), %rcx
+    leaq 8(%rdi), %rsi
+    xorl %eax, %eax
+    xorl %r8d, %r8d
+    xorl %edx, %edx
+    movl $2147483648, %r10d
+    testq %rcx, %rcx
+    jz 9f
+    movzbl (%rsi), %r9d
+    cmpl $45, %r9d
+    jne 1f
+    movl $1, %r8d
+    jmp 2f
+1:
+    cmpl $43, %r9d
+    jne 3f
+2:
+    incq %rsi
+    decq %rcx
+    jz 9f
+3:
+    movzbl (%rsi), %r9d
+    subl $48, %r9d
+    cmpl $9, %r9d
+    ja 8f
+    imulq $10, %rax
+    addq %r9, %rax
+    cmpq %r10, %rax
+    ja 8f
+    incq %rsi
+    decq %rcx
+    jnz 3b
+    testl %r8d, %r8d
+    jz 4f
+    negq %rax
+    movl $1, %edx
+    ret
+4:
+    cmpq %r10, %rax
+    je 8f
+    movl $1, %edx
+    ret
+8:
+    xorl %eax, %eax
+    xorl %edx, %edx
+9:
+    ret
+
+jv_is_int:
+    call jv_scan_int
+    movl %edx, %eax
+    ret
+
+jv_parse_int:
+    jmp jv_scan_int
+
+jv_peek:
+    movq jv_in_pos(%rip), %rax
+    cmpq jv_in_len(%rip), %rax
+    jb 1f
+    xorl %edi, %edi
+    leaq jv_in_buf(%rip), %rsi
+    movl $4096, %edx
+    xorl %eax, %eax
+    syscall
+    testq %rax, %rax
+    jle 2f
+    movq %rax, jv_in_len(%rip)
+    movq $0, jv_in_pos(%rip)
+    xorl %eax, %eax
+1:
+    leaq jv_in_buf(%rip), %rdx
+    movzbl (%rdx,%rax), %eax
+    ret
+2:
+    movl $-1, %eax
+    ret
+
+jv_getc:
+    call jv_peek
+    testl %eax, %eax
+    js 1f
+    incq jv_in_pos(%rip)
+1:
+    ret
+
+jv_is_space:
+    cmpl $32, %edi
+    je 1f
+    leal -9(%rdi), %eax
+    cmpl $4, %eax
+    jbe 1f
+    xorl %eax, %eax
+    ret
+1:
+    movl $1, %eax
+    ret
+
+jv_builder_push:
+    cmpq %r14, %r13
+    jb 1f
+    pushq %rdi
+    leaq 16(%r14,%r14), %r14
+    leaq 8(%r14), %rdi
+    call jv_alloc
+    leaq 8(%rax), %rdi
+    movq %r12, %rsi
+    movq %r13, %rcx
+    rep movsb
+    leaq 8(%rax), %r12
+    popq %rdi
+1:
+    movb %dil, (%r12,%r13)
+    incq %r13
+    ret
+
+jv_read_word:
+    pushq %r12
+    pushq %r13
+    pushq %r14
+    call jv_flush
+    xorl %r12d, %r12d
+    xorl %r13d, %r13d
+    xorl %r14d, %r14d
+1:
+    call jv_peek
+    testl %eax, %eax
+    js 3f
+    movl %eax, %edi
+    call jv_is_space
+    testl %eax, %eax
+    jz 2f
+    incq jv_in_pos(%rip)
+    jmp 1b
+2:
+    call jv_peek
+    testl %eax, %eax
+    js 3f
+    movl %eax, %edi
+    call jv_is_space
+    testl %eax, %eax
+    jnz 3f
+    incq jv_in_pos(%rip)
+    call jv_builder_push
+    jmp 2b
+3:
+    xorl %eax, %eax
+    testq %r13, %r13
+    jz 4f
+    movq %r13, -8(%r12)
+    leaq -8(%r12), %rax
+4:
+    popq %r14
+    popq %r13
+    popq %r12
+    ret
+
+jv_read_failed:
+    movl $0, jv_last_read_ok(%rip)
+    movl $1, jv_reached_eof(%rip)
+    xorl %eax, %eax
+    ret
+
+jv_read_int:
+    call jv_read_word
+    testq %rax, %rax
+    jz jv_read_failed
+    movq %rax, %rdi
+    call jv_scan_int
+    movl %edx, jv_last_read_ok(%rip)
+    ret
+
+jv_read_bool:
+    call jv_read_word
+    testq %rax, %rax
+    jz jv_read_failed
+    pushq %rax
+    movq %rax, %rdi
+    leaq jv_false_str(%rip), %rsi
+    call jv_str_cmp
+    popq %rdi
+    pushq %rax
+    leaq jv_true_str(%rip), %rsi
+    call jv_str_cmp
+    popq %rdx
+    testl %eax, %eax
+    sete %al
+    testl %edx, %edx
+    sete %dl
+    orb %al, %dl
+    movzbl %dl, %edx
+    movl %edx, jv_last_read_ok(%rip)
+    movzbl %al, %eax
+    ret
+
+jv_read_line:
+    pushq %r12
+    pushq %r13
+    pushq %r14
+    pushq %r15
+    call jv_flush
+    xorl %r12d, %r12d
+    xorl %r13d, %r13d
+    xorl %r14d, %r14d
+    xorl %r15d, %r15d
+1:
+    call jv_getc
+    testl %eax, %eax
+    js 2f
+    cmpl $10, %eax
+    je 3f
+    movl %eax, %edi
+    call jv_builder_push
+    jmp 1b
+2:
+    movl $1, %r15d
+3:
+    movl $1, %eax
+    testq %r13, %r13
+    jnz 4f
+    testl %r15d, %r15d
+    jz 4f
+    xorl %eax, %eax
+    movl $1, jv_reached_eof(%rip)
+4:
+    movl %eax, jv_last_read_ok(%rip)
+    leaq jv_empty(%rip), %rax
+    testq %r13, %r13
+    jz 5f
+    movq %r13, -8(%r12)
+    leaq -8(%r12), %rax
+5:
+    popq %r15
+    popq %r14
+    popq %r13
+    popq %r12
+    ret
+
+jv_input_ok:
+    movl jv_last_read_ok(%rip), %eax
+    ret
+
+jv_input_eof:
+    movl jv_reached_eof(%rip), %eax
+    ret
+
+jv_pause:
+    leaq jv_pause_str(%rip), %rdi
+    call jv_print_str
+    call jv_flush
+    jmp jv_getc
+
+    .section .rodata
+    .p2align 3
+jv_empty:
+    .quad 0
+jv_true_str:
+    .quad 4
+    .ascii "true"
+    .p2align 3
+jv_false_str:
+    .quad 5
+    .ascii "false"
+    .p2align 3
+jv_minus_str:
+    .quad 1
+    .ascii "-"
+    .p2align 3
+jv_plus_str:
+    .quad 1
+    .ascii "+"
+    .p2align 3
+jv_space_str:
+    .quad 1
+    .ascii " "
+    .p2align 3
+jv_pause_str:
+    .quad 22
+    .ascii "Press Enter to exit..."
+jv_newline:
+    .ascii "\n"
+jv_out_of_memory_msg:
+    .ascii "Out of memory\n"
+
+    .data
+jv_last_read_ok:
+    .long 1
+jv_reached_eof:
+    .long 0
+
+    .bss
+    .p2align 3
+jv_pause_on_exit:
+    .skip 8
+jv_heap_ptr:
+    .skip 8
+jv_heap_end:
+    .skip 8
+jv_out_len:
+    .skip 8
+jv_in_pos:
+    .skip 8
+jv_in_len:
+    .skip 8
+jv_out_buf:
+    .skip 4096
+jv_in_buf:
+    .skip 4096
+
+    .section .note.GNU-stack,"",@progbits
+"#;
+
+//Registers of the first arguments of a call, in order
+const ARG_REGISTERS: [&str; 6] = ["%rdi", "%rsi", "%rdx", "%rcx", "%r8", "%r9"];
+
+//Generates GNU assembly for x86-64 Linux out of a parsed program,
+//which `as` and `ld` turn into an executable without the C library.
+//Every value fits in a register: ints are the low 32 bits, booleans
+//are 0 or 1 and strings are pointers. Expressions leave their value in
+//rax and keep the left side of operations on the stack, while variables
+//live in slots of the frame of their function
+pub struct AsmGenerator {
+    code: String,                        //Instructions generated so far
+    literals: Vec<String>,               //String literals, labeled by their index
+    label_count: usize,                  //Labels of jumps generated so far
+    pause_on_exit: bool,                 //Wait for Enter before the program ends
+    function_names: Vec<String>,         //Symbol of each function, like in the C backend
+    scopes: Vec<HashMap<String, usize>>, //Frame offset of each variable in scope
+    frame_size: usize,                   //Bytes of the slots of the current function
+    depth: usize,                        //Values pushed by the expressions being generated
+    loops: Vec<(String, String)>,        //Labels of `continue` and `break` of each loop
+    return_label: String,                //Epilogue of the current function
+}
+
+impl AsmGenerator {
+    pub fn new() -> Self {
+        AsmGenerator {
+            code: String::new(),
+            literals: Vec::new(),
+            label_count: 0,
+            pause_on_exit: false,
+            function_names: Vec::new(),
+            scopes: Vec::new(),
+            frame_size: 0,
+            depth: 0,
+            loops: Vec::new(),
+            return_label: String::new(),
+        }
+    }
+
+    //Makes the program wait for Enter before ending, so its
+    //output stays visible when it runs in its own console window
+    pub fn with_pause_on_exit(mut self, pause_on_exit: bool) -> Self {
+        self.pause_on_exit = pause_on_exit;
+        self
+    }
+
+    //Generates the code of the whole program. Like in the C backend,
+    //globals start with their default value and are initialized at
+    //the start of `jv_main`, which `_start` calls
+    pub fn generate(mut self, program: &Program) -> String {
+        self.function_names = program
+            .functions
+            .iter()
+            .map(|function| function_name(program, function))
+            .collect();
+
+        for index in 0..program.functions.len() {
+            let function = &program.functions[index];
+            let params: Vec<&String> = function.params.iter().map(|(_, name)| name).collect();
+            let name = self.function_names[index].clone();
+            self.function(&name, &params, |generator| {
+                generator.stmts(program, &function.body);
+            });
+        }
+
+        self.function("jv_main", &[], |generator| {
+            //Returning from `main` and calling `exit` both end in
+            //`jv_exit`, which pauses when the flag is set
+            if generator.pause_on_exit {
+                generator.emit("movq $1, jv_pause_on_exit(%rip)");
+            }
+
+            for global in &program.globals {
+                if let StmtKind::Decl(_, _, declarators) = &global.kind {
+                    for (var_name, initializer) in declarators {
+                        if let Some(value) = initializer {
+                            generator.expr(program, value);
+                            generator.emit(&format!("movq %rax, {}", global_symbol(var_name)));
+                        }
+                    }
+                }
+            }
+
+            generator.stmts(program, &program.body);
+            generator.emit("xorl %eax, %eax");
+        });
+
+        let mut data = String::from("\n    .data\n    .p2align 3\n");
+        for global in &program.globals {
+            if let StmtKind::Decl(_, var_type, declarators) = &global.kind {
+                for (var_name, _) in declarators {
+                    let default = match var_type.as_str() {
+                        "string" => "jv_empty",
+                        _ => "0",
+                    };
+                    data.push_str(&format!("jv_global_{}:\n    .quad {}\n", var_name, default));
+                }
+            }
+        }
+
+        data.push_str("\n    .section .rodata\n");
+        for (index, literal) in self.literals.iter().enumerate() {
+            data.push_str(&format!(
+                "    .p2align 3\n.Lstr{}:\n    .quad {}\n",
+                index,
+                literal.len()
+            ));
+
+            if !literal.is_empty() {
+                let bytes: Vec<String> = literal.bytes().map(|b| b.to_string()).collect();
+                data.push_str(&format!("    .byte {}\n", bytes.join(", ")));
+            }
+        }
+
+        format!("{}\n    .text\n{}{}", RUNTIME, self.code, data)
+    }
+
+    //Pushes an instruction
+    fn emit(&mut self, instruction: &str) {
+        self.code.push_str("    ");
+        self.code.push_str(instruction);
+        self.code.push('\n');
+    }
+
+    fn label(&mut self, label: &str) {
+        self.code.push_str(label);
+        self.code.push_str(":\n");
+    }
+
+    fn new_label(&mut self) -> String {
+        self.label_count += 1;
+        format!(".L{}", self.label_count)
+    }
+
+    fn push(&mut self) {
+        self.emit("pushq %rax");
+        self.depth += 1;
+    }
+
+    fn pop(&mut self, register: &str) {
+        self.emit(&format!("popq {}", register));
+        self.depth -= 1;
+    }
+
+    //Generates a function whose body is generated by `body`. Its
+    //frame is only known after the body, so the body is generated
+    //apart and the prologue is inserted before it
+    fn function(&mut self, name: &str, params: &[&String], body: impl FnOnce(&mut Self)) {
+        let outer_code = std::mem::take(&mut self.code);
+        self.scopes.push(HashMap::new());
+        self.frame_size = 0;
+        self.return_label = self.new_label();
+
+        //The first parameters come in registers and
+        //the rest on the stack, above the return address
+        for (index, param) in params.iter().enumerate() {
+            let offset = self.declare(param);
+            match ARG_REGISTERS.get(index) {
+                Some(register) => self.emit(&format!("movq {}, -{}(%rbp)", register, offset)),
+                None => {
+                    self.emit(&format!("movq {}(%rbp), %rax", 16 + 8 * (index - 6)));
+                    self.emit(&format!("movq %rax, -{}(%rbp)", offset));
+                }
+            }
+        }
+
+        body(self);
+
+        let return_label = self.return_label.clone();
+        self.label(&return_label);
+        self.emit("leave");
+        self.emit("ret");
+        self.scopes.pop();
+
+        //The frame keeps the stack aligned to 16 bytes, as calls need
+        let body_code = std::mem::replace(&mut self.code, outer_code);
+        self.code.push_str(&format!("\n{}:\n", name));
+        self.emit("pushq %rbp");
+        self.emit("movq %rsp, %rbp");
+        if self.frame_size > 0 {
+            self.emit(&format!(
+                "subq ${}, %rsp",
+                self.frame_size.next_multiple_of(16)
+            ));
+        }
+        self.code.push_str(&body_code);
+    }
+
+    //Gives a variable a new slot in the frame, returning its offset
+    fn declare(&mut self, var_name: &str) -> usize {
+        self.frame_size += 8;
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(var_name.to_string(), self.frame_size);
+        self.frame_size
+    }
+
+    //Where a variable is, either a slot of
+    //the frame or the symbol of a global
+    fn location(&self, var_name: &str) -> String {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var_name))
+            .map(|offset| format!("-{}(%rbp)", offset))
+            .unwrap_or_else(|| global_symbol(var_name))
+    }
+
+    fn load(&mut self, var_name: &str) {
+        let location = self.location(var_name);
+        self.emit(&format!("movq {}, %rax", location));
+    }
+
+    fn store(&mut self, var_name: &str) {
+        let location = self.location(var_name);
+        self.emit(&format!("movq %rax, {}", location));
+    }
+
+    //Label of a string literal, given without its escape sequences
+    fn literal(&mut self, text: String) -> String {
+        let index = match self.literals.iter().position(|literal| *literal == text) {
+            Some(index) => index,
+            None => {
+                self.literals.push(text);
+                self.literals.len() - 1
+            }
+        };
+
+        format!(".Lstr{}", index)
+    }
+
+    //Calls a function of the runtime whose arguments are already in
+    //their registers, realigning the stack if an odd number of values
+    //were pushed
+    fn call_runtime(&mut self, name: &str) {
+        let misaligned = self.depth % 2 == 1;
+        if misaligned {
+            self.emit("subq $8, %rsp");
+        }
+        self.emit(&format!("call {}", name));
+        if misaligned {
+            self.emit("addq $8, %rsp");
+        }
+    }
+
+    //Calls a function with arguments evaluated from left to right. The
+    //ones past the sixth are stored in stack space reserved beforehand,
+    //below the padding that keeps the stack aligned
+    fn call(&mut self, program: &Program, name: &str, args: &[Expr]) {
+        let stack_args = args.len().saturating_sub(ARG_REGISTERS.len());
+        let reserved = stack_args + (self.depth + stack_args) % 2;
+        if reserved > 0 {
+            self.emit(&format!("subq ${}, %rsp", 8 * reserved));
+            self.depth += reserved;
+        }
+
+        for (index, arg) in args.iter().enumerate() {
+            self.expr(program, arg);
+            match index < ARG_REGISTERS.len() {
+                true => self.push(),
+                false => self.emit(&format!("movq %rax, {}(%rsp)", 8 * index)),
+            }
+        }
+
+        for register in ARG_REGISTERS[..args.len().min(ARG_REGISTERS.len())]
+            .iter()
+            .rev()
+        {
+            self.pop(register);
+        }
+
+        self.emit(&format!("call {}", name));
+        if reserved > 0 {
+            self.emit(&format!("addq ${}, %rsp", 8 * reserved));
+            self.depth -= reserved;
+        }
+    }
+
+    //Generates the statements of a block in a new scope
+    fn stmts(&mut self, program: &Program, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+
+        for stmt in stmts {
+            self.stmt(program, stmt);
+        }
+
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, program: &Program, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(_, var_type, declarators) => {
+                for (var_name, initializer) in declarators {
+                    match initializer {
+                        Some(value) => self.expr(program, value),
+                        None if var_type == "string" => self.emit("leaq jv_empty(%rip), %rax"),
+                        None => self.emit("xorl %eax, %eax"),
+                    }
+
+                    let offset = self.declare(var_name);
+                    self.emit(&format!("movq %rax, -{}(%rbp)", offset));
+                }
+            }
+            StmtKind::Asgn(var_name, operator, value) => {
+                self.expr(program, value);
+
+                match (operator.as_str(), value.jv_type.as_str()) {
+                    ("+=", "string") => {
+                        self.emit("movq %rax, %rsi");
+                        self.load(var_name);
+                        self.emit("movq %rax, %rdi");
+                        self.call_runtime("jv_concat");
+                    }
+                    ("+=", _) | ("-=", _) => {
+                        let instruction = match operator.as_str() {
+                            "+=" => "addl",
+                            _ => "subl",
+                        };
+                        self.emit("movl %eax, %ecx");
+                        self.load(var_name);
+                        self.emit(&format!("{} %ecx, %eax", instruction));
+                    }
+                    _ => {}
+                }
+
+                self.store(var_name);
+            }
+            StmtKind::Call(call) => self.expr(program, call),
+            StmtKind::Block(stmts) => self.stmts(program, stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                let else_label = self.new_label();
+                let end_label = self.new_label();
+
+                self.expr(program, condition);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("je {}", else_label));
+                self.stmts(program, then_block);
+                self.emit(&format!("jmp {}", end_label));
+                self.label(&else_label);
+                if let Some(else_block) = else_block {
+                    self.stmts(program, else_block);
+                }
+                self.label(&end_label);
+            }
+            StmtKind::While(condition, body) => {
+                let condition_label = self.new_label();
+                let end_label = self.new_label();
+
+                self.label(&condition_label);
+                self.expr(program, condition);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("je {}", end_label));
+                self.loop_body(program, body, &condition_label, &end_label);
+                self.emit(&format!("jmp {}", condition_label));
+                self.label(&end_label);
+            }
+            StmtKind::DoWhile(body, condition) => {
+                let body_label = self.new_label();
+                let condition_label = self.new_label();
+                let end_label = self.new_label();
+
+                self.label(&body_label);
+                self.loop_body(program, body, &condition_label, &end_label);
+                self.label(&condition_label);
+                self.expr(program, condition);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("jne {}", body_label));
+                self.label(&end_label);
+            }
+            StmtKind::For(init, condition, body) => {
+                let condition_label = self.new_label();
+                let end_label = self.new_label();
+
+                self.stmt(program, init);
+                self.label(&condition_label);
+                self.expr(program, condition);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("je {}", end_label));
+                self.loop_body(program, body, &condition_label, &end_label);
+                self.emit(&format!("jmp {}", condition_label));
+                self.label(&end_label);
+            }
+            StmtKind::Break => {
+                let (_, break_label) = self.loops.last().unwrap().clone();
+                self.emit(&format!("jmp {}", break_label));
+            }
+            StmtKind::Continue => {
+                let (continue_label, _) = self.loops.last().unwrap().clone();
+                self.emit(&format!("jmp {}", continue_label));
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(program, value);
+                }
+
+                let return_label = self.return_label.clone();
+                self.emit(&format!("jmp {}", return_label));
+            }
+        }
+    }
+
+    fn loop_body(&mut self, program: &Program, body: &[Stmt], continue_label: &str, end: &str) {
+        self.loops
+            .push((continue_label.to_string(), end.to_string()));
+        self.stmts(program, body);
+        self.loops.pop();
+    }
+
+    //Generates an expression, leaving its value in rax
+    fn expr(&mut self, program: &Program, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Int(value) => self.emit(&format!("movl ${}, %eax", value)),
+            ExprKind::Bool(value) => self.emit(&format!("movl ${}, %eax", *value as i32)),
+            ExprKind::Str(value) => {
+                let label = self.literal(unescape(value));
+                self.emit(&format!("leaq {}(%rip), %rax", label));
+            }
+            ExprKind::Var(name) => self.load(name),
+            ExprKind::Call(name, args) if name == "print" || name == "println" => {
+                self.print(program, args, name == "println")
+            }
+            ExprKind::Call(name, args) if name == "printf" => self.printf(program, args),
+            ExprKind::Call(name, args) if name == "len" => {
+                self.expr(program, &args[0]);
+                self.emit("movq (%rax), %rax");
+            }
+            ExprKind::Call(name, args)
+                if RUNTIME_BUILTINS.contains(&name.as_str())
+                    || ["contains", "to_string", "exit"].contains(&name.as_str()) =>
+            {
+                self.call(program, &format!("jv_{}", name), args)
+            }
+            ExprKind::Call(name, args) => {
+                let index = program.function_index(name, args).unwrap();
+                let function_name = self.function_names[index].clone();
+                self.call(program, &function_name, args);
+            }
+            ExprKind::Unary(operator, operand) => {
+                self.expr(program, operand);
+                match operator.as_str() {
+                    "!" => self.emit("xorl $1, %eax"),
+                    "-" => self.emit("negl %eax"),
+                    "++" => self.emit("addl $1, %eax"),
+                    _ => self.emit("subl $1, %eax"),
+                }
+
+                if let ("++" | "--", ExprKind::Var(var_name)) = (operator.as_str(), &operand.kind) {
+                    self.store(var_name);
+                }
+            }
+            ExprKind::Binary(operator, left, right) if operator == "&&" || operator == "||" => {
+                //The left side is already the result when it decides it
+                let end_label = self.new_label();
+                let jump = match operator.as_str() {
+                    "&&" => "je",
+                    _ => "jne",
+                };
+
+                self.expr(program, left);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("{} {}", jump, end_label));
+                self.expr(program, right);
+                self.label(&end_label);
+            }
+            ExprKind::Binary(operator, left, right) => {
+                self.expr(program, left);
+                self.push();
+                self.expr(program, right);
+                self.emit("movq %rax, %rcx");
+                self.pop("%rax");
+
+                if left.jv_type == "string" {
+                    self.emit("movq %rax, %rdi");
+                    self.emit("movq %rcx, %rsi");
+
+                    if operator == "+" {
+                        self.call_runtime("jv_concat");
+                        return;
+                    }
+
+                    //Strings are compared by the sign of `jv_str_cmp`
+                    self.call_runtime("jv_str_cmp");
+                    self.emit("movl $0, %ecx");
+                }
+
+                match operator.as_str() {
+                    "+" => self.emit("addl %ecx, %eax"),
+                    "-" => self.emit("subl %ecx, %eax"),
+                    "*" => self.emit("imull %ecx, %eax"),
+                    "/" | "%" => {
+                        //`idivl` faults when the smallest int is divided
+                        //by -1, so that divisor wraps like the interpreter
+                        let mut end_label = None;
+                        if expr.may_overflow_division() {
+                            let divide_label = self.new_label();
+                            let label = self.new_label();
+                            self.emit("cmpl $-1, %ecx");
+                            self.emit(&format!("jne {}", divide_label));
+                            match operator.as_str() {
+                                "/" => self.emit("negl %eax"),
+                                _ => self.emit("xorl %eax, %eax"),
+                            }
+                            self.emit(&format!("jmp {}", label));
+                            self.label(&divide_label);
+                            end_label = Some(label);
+                        }
+
+                        self.emit("cltd");
+                        self.emit("idivl %ecx");
+                        if operator == "%" {
+                            self.emit("movl %edx, %eax");
+                        }
+
+                        if let Some(end_label) = end_label {
+                            self.label(&end_label);
+                        }
+                    }
+                    "&" => self.emit("andl %ecx, %eax"),
+                    "|" => self.emit("orl %ecx, %eax"),
+                    comparison => {
+                        let condition = match comparison {
+                            "==" => "e",
+                            "!=" => "ne",
+                            "<" => "l",
+                            "<=" => "le",
+                            ">" => "g",
+                            _ => "ge",
+                        };
+                        self.emit("cmpl %ecx, %eax");
+                        self.emit(&format!("set{} %al", condition));
+                        self.emit("movzbl %al, %eax");
+                    }
+                }
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                let else_label = self.new_label();
+                let end_label = self.new_label();
+
+                self.expr(program, condition);
+                self.emit("testl %eax, %eax");
+                self.emit(&format!("je {}", else_label));
+                self.expr(program, then_expr);
+                self.emit(&format!("jmp {}", end_label));
+                self.label(&else_label);
+                self.expr(program, else_expr);
+                self.label(&end_label);
+            }
+            ExprKind::Interpolation(parts) => {
+                if parts.is_empty() {
+                    self.emit("leaq jv_empty(%rip), %rax");
+                }
+
+                for (index, part) in parts.iter().enumerate() {
+                    if index > 0 {
+                        self.push();
+                    }
+
+                    self.expr(program, part);
+                    self.stringify(&part.jv_type);
+
+                    if index > 0 {
+                        self.emit("movq %rax, %rsi");
+                        self.pop("%rdi");
+                        self.call_runtime("jv_concat");
+                    }
+                }
+            }
+        }
+    }
+
+    //Converts the value in rax to a string,
+    //writing booleans as `true` or `false`
+    fn stringify(&mut self, jv_type: &str) {
+        let function = match jv_type {
+            "int" => "jv_to_string",
+            "boolean" => "jv_bool_string",
+            _ => return,
+        };
+
+        self.emit("movq %rax, %rdi");
+        self.call_runtime(function);
+    }
+
+    //Evaluates values and pushes them, like a call does before printing
+    //anything. Returns the offset from rsp to each of them
+    fn push_values(&mut self, program: &Program, values: &[Expr]) -> Vec<usize> {
+        for value in values {
+            self.expr(program, value);
+            self.push();
+        }
+
+        (0..values.len())
+            .map(|index| 8 * (values.len() - 1 - index))
+            .collect()
+    }
+
+    fn drop_values(&mut self, count: usize) {
+        if count > 0 {
+            self.emit(&format!("addq ${}, %rsp", 8 * count));
+            self.depth -= count;
+        }
+    }
+
+    //Prints each value according to its type, one after the other
+    fn print(&mut self, program: &Program, args: &[Expr], newline: bool) {
+        let offsets = self.push_values(program, args);
+
+        for (arg, offset) in args.iter().zip(offsets) {
+            let function = match arg.jv_type.as_str() {
+                "int" => "jv_print_int",
+                "boolean" => "jv_print_bool",
+                _ => "jv_print_str",
+            };
+
+            self.emit(&format!("movq {}(%rsp), %rdi", offset));
+            self.call_runtime(function);
+        }
+
+        if newline {
+            self.call_runtime("jv_print_newline");
+        }
+
+        self.drop_values(args.len());
+    }
+
+    //The format of `printf` is a literal, so it is parsed here, like
+    //`runtime::format` does, and each specifier becomes calls that
+    //format its value. The text between specifiers is printed as it is
+    fn printf(&mut self, program: &Program, args: &[Expr]) {
+        let ExprKind::Str(format) = &args[0].kind else {
+            panic!("The format of printf must be a literal");
+        };
+
+        let offsets = self.push_values(program, &args[1..]);
+        let mut offsets = offsets.into_iter();
+        let mut text = String::new();
+        let format = unescape(format);
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+
+            let mut flags = String::new();
+            while let Some(&flag) = chars.peek().filter(|f| "-+ #0".contains(**f)) {
+                flags.push(flag);
+                chars.next();
+            }
+
+            let mut width = 0;
+            while let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                width = width * 10 + digit;
+                chars.next();
+            }
+
+            let mut precision: Option<u32> = None;
+            if chars.next_if_eq(&'.').is_some() {
+                let mut digits = 0;
+                while let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                    digits = digits * 10 + digit;
+                    chars.next();
+                }
+                precision = Some(digits);
+            }
+
+            let conversion = chars.next();
+            if conversion == Some('%') {
+                text.push('%');
+                continue;
+            }
+
+            self.print_text(&mut text);
+            let offset = offsets.next().unwrap();
+            self.emit(&format!("movq {}(%rsp), %rdi", offset));
+
+            let numeric = conversion != Some('s');
+            if numeric {
+                let sign = match flags.as_str() {
+                    flags if flags.contains('+') => "jv_plus_str",
+                    flags if flags.contains(' ') => "jv_space_str",
+                    _ => "jv_empty",
+                };
+                let precision = precision.map_or(-1, |precision| precision as i64);
+
+                self.emit(&format!("movl ${}, %esi", precision));
+                self.emit(&format!("leaq {}(%rip), %rdx", sign));
+                self.call_runtime("jv_format_int");
+                self.emit("movq %rax, %rdi");
+            } else if let Some(precision) = precision {
+                self.emit("xorl %esi, %esi");
+                self.emit(&format!("movl ${}, %edx", precision));
+                self.call_runtime("jv_substr");
+                self.emit("movq %rax, %rdi");
+            }
+
+            //Modes of `jv_pad`: spaces before, spaces after or zeros after the sign
+            if width > 0 {
+                let mode = match flags.as_str() {
+                    flags if flags.contains('-') => 1,
+                    flags if flags.contains('0') && numeric && precision.is_none() => 2,
+                    _ => 0,
+                };
+
+                self.emit(&format!("movl ${}, %esi", width));
+                self.emit(&format!("movl ${}, %edx", mode));
+                self.call_runtime("jv_pad");
+                self.emit("movq %rax, %rdi");
+            }
+
+            self.call_runtime("jv_print_str");
+        }
+
+        self.print_text(&mut text);
+        self.drop_values(args.len() - 1);
+    }
+
+    //Prints the text of a format gathered so far, if any
+    fn print_text(&mut self, text: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+
+        let label = self.literal(std::mem::take(text));
+        self.emit(&format!("leaq {}(%rip), %rdi", label));
+        self.call_runtime("jv_print_str");
+    }
+}
+
+impl Default for AsmGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn global_symbol(var_name: &str) -> String {
+    format!("jv_global_{}(%rip)", var_name)
+}
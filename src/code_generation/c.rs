@@ -0,0 +1,617 @@
+use crate::syntax_analysis::{
+    ast::{Expr, ExprKind, Program, Stmt, StmtKind},
+    builtins::RUNTIME_BUILTINS,
+};
+
+use super::function_name;
+
+//Strings are immutable C strings. Every string built while the program
+//runs is allocated on the heap and lives until the program ends, since
+//C has no destructors to free them when they're no longer used
+const STRING_RUNTIME: &str = r#"#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <stdbool.h>
+#include <stdarg.h>
+
+typedef const char *jv_string;
+
+void *jv_alloc(size_t size) {
+    void *memory = malloc(size);
+    if (memory == NULL) {
+        fputs("Out of memory\n", stderr);
+        exit(1);
+    }
+    return memory;
+}
+
+jv_string jv_concat(jv_string left, jv_string right) {
+    size_t left_length = strlen(left);
+    size_t right_length = strlen(right);
+    char *result = jv_alloc(left_length + right_length + 1);
+    memcpy(result, left, left_length);
+    memcpy(result + left_length, right, right_length + 1);
+    return result;
+}
+
+jv_string jv_join(int count, ...) {
+    va_list parts;
+    size_t length = 0;
+    va_start(parts, count);
+    for (int i = 0; i < count; i++) {
+        length += strlen(va_arg(parts, jv_string));
+    }
+    va_end(parts);
+
+    char *result = jv_alloc(length + 1);
+    char *end = result;
+    va_start(parts, count);
+    for (int i = 0; i < count; i++) {
+        jv_string part = va_arg(parts, jv_string);
+        size_t part_length = strlen(part);
+        memcpy(end, part, part_length);
+        end += part_length;
+    }
+    va_end(parts);
+    *end = '\0';
+    return result;
+}
+
+jv_string jv_to_string(int value) {
+    char *result = jv_alloc(12);
+    snprintf(result, 12, "%d", value);
+    return result;
+}
+
+"#;
+
+//Functions behind the builtins that need them, only emitted when
+//the program calls one of them. They read the input like the C++
+//runtime does with `cin` and `getline`
+const INPUT_RUNTIME: &str = r#"#include <ctype.h>
+#include <errno.h>
+#include <limits.h>
+
+bool jv_last_read_ok = true;
+bool jv_reached_eof = false;
+
+bool jv_input_ok(void) { return jv_last_read_ok; }
+bool jv_input_eof(void) { return jv_reached_eof; }
+
+bool jv_is_int(jv_string text) {
+    size_t start = text[0] == '-' || text[0] == '+' ? 1 : 0;
+    size_t length = strlen(text);
+    if (length <= start || strspn(text + start, "0123456789") != length - start) {
+        return false;
+    }
+    errno = 0;
+    long value = strtol(text, NULL, 10);
+    return errno == 0 && value >= INT_MIN && value <= INT_MAX;
+}
+
+int jv_parse_int(jv_string text) {
+    return jv_is_int(text) ? (int) strtol(text, NULL, 10) : 0;
+}
+
+jv_string jv_substr(jv_string text, int start, int length) {
+    int size = (int) strlen(text);
+    start = start < 0 ? 0 : (start > size ? size : start);
+    length = length < 0 ? 0 : (length > size - start ? size - start : length);
+    char *result = jv_alloc((size_t) length + 1);
+    memcpy(result, text + start, (size_t) length);
+    result[length] = '\0';
+    return result;
+}
+
+typedef struct {
+    char *data;
+    size_t length;
+    size_t capacity;
+} jv_buffer;
+
+void jv_buffer_push(jv_buffer *buffer, char c) {
+    if (buffer->length + 1 >= buffer->capacity) {
+        buffer->capacity = buffer->capacity == 0 ? 16 : buffer->capacity * 2;
+        buffer->data = realloc(buffer->data, buffer->capacity);
+        if (buffer->data == NULL) {
+            fputs("Out of memory\n", stderr);
+            exit(1);
+        }
+    }
+    buffer->data[buffer->length++] = c;
+    buffer->data[buffer->length] = '\0';
+}
+
+jv_string jv_buffer_string(jv_buffer *buffer) {
+    return buffer->data == NULL ? "" : buffer->data;
+}
+
+bool jv_read_word(jv_string *word) {
+    jv_buffer buffer = {NULL, 0, 0};
+    int c;
+    fflush(stdout);
+    do {
+        c = getchar();
+    } while (c != EOF && isspace(c));
+    while (c != EOF && !isspace(c)) {
+        jv_buffer_push(&buffer, (char) c);
+        c = getchar();
+    }
+    if (c != EOF) {
+        ungetc(c, stdin);
+    }
+    if (buffer.length == 0) {
+        jv_last_read_ok = false;
+        jv_reached_eof = true;
+        return false;
+    }
+    *word = jv_buffer_string(&buffer);
+    return true;
+}
+
+int jv_read_int(void) {
+    jv_string word;
+    if (!jv_read_word(&word)) {
+        return 0;
+    }
+    jv_last_read_ok = jv_is_int(word);
+    return jv_parse_int(word);
+}
+
+bool jv_read_bool(void) {
+    jv_string word;
+    if (!jv_read_word(&word)) {
+        return false;
+    }
+    jv_last_read_ok = strcmp(word, "true") == 0 || strcmp(word, "false") == 0;
+    return strcmp(word, "true") == 0;
+}
+
+jv_string jv_read_line(void) {
+    jv_buffer buffer = {NULL, 0, 0};
+    int c;
+    fflush(stdout);
+    while ((c = getchar()) != EOF && c != '\n') {
+        jv_buffer_push(&buffer, (char) c);
+    }
+    jv_last_read_ok = c != EOF || buffer.length > 0;
+    jv_reached_eof = jv_reached_eof || !jv_last_read_ok;
+    return jv_buffer_string(&buffer);
+}
+
+"#;
+
+//Division that wraps like the interpreter, since dividing the
+//smallest int by -1 overflows, which is undefined in C
+const DIVISION: &str = r#"int jv_div(int left, int right) {
+    return right == -1 ? (int) (0u - (unsigned) left) : left / right;
+}
+
+int jv_rem(int left, int right) {
+    return right == -1 ? 0 : left % right;
+}
+
+"#;
+
+//Pause registered with `atexit` at the start of `main`, so the program
+//waits for Enter whether it reaches its end, returns early or calls `exit`
+const PAUSE: &str = r#"static void jv_pause(void) {
+    printf("Press Enter to exit...");
+    fflush(stdout);
+    getchar();
+}
+
+"#;
+
+//Generates C99 code out of a parsed program, which behaves
+//like the code of `CppGenerator` without needing a C++ compiler
+pub struct CGenerator {
+    intermediary_code: String,   //Code generated so far
+    scope_level: usize,          //Current scope level to determine tabs in intermediary code
+    pause_on_exit: bool,         //Wait for Enter before the program ends
+    function_names: Vec<String>, //Name of each function in C, which has no overloads
+}
+
+impl CGenerator {
+    pub fn new() -> Self {
+        CGenerator {
+            intermediary_code: String::from(STRING_RUNTIME),
+            scope_level: 0,
+            pause_on_exit: false,
+            function_names: Vec::new(),
+        }
+    }
+
+    //Makes the program wait for Enter before ending, so its
+    //output stays visible when it runs in its own console window
+    pub fn with_pause_on_exit(mut self, pause_on_exit: bool) -> Self {
+        self.pause_on_exit = pause_on_exit;
+        self
+    }
+
+    //Generates the code of the whole program. C only allows constants
+    //as initializers at file scope, so globals start with their default
+    //value and are initialized at the start of `main`, in order
+    pub fn generate(mut self, program: &Program) -> String {
+        if RUNTIME_BUILTINS.iter().any(|name| program.calls(name)) {
+            self.intermediary_code.push_str(INPUT_RUNTIME);
+        }
+
+        if program.may_overflow_division() {
+            self.intermediary_code.push_str(DIVISION);
+        }
+
+        self.function_names = program
+            .functions
+            .iter()
+            .map(|function| function_name(program, function))
+            .collect();
+
+        if !program.functions.is_empty() {
+            for index in 0..program.functions.len() {
+                let signature = self.signature_code(program, index);
+                self.push_line(format!("{};", signature).as_str());
+            }
+
+            self.push_line("");
+        }
+
+        if !program.globals.is_empty() {
+            for global in &program.globals {
+                if let StmtKind::Decl(_, var_type, declarators) = &global.kind {
+                    let declarators: Vec<String> = declarators
+                        .iter()
+                        .map(|(var_name, _)| format!("{} = {}", var_name, default_value(var_type)))
+                        .collect();
+
+                    self.push_line(
+                        format!("{} {};", map_type(var_type), declarators.join(", ")).as_str(),
+                    );
+                }
+            }
+
+            self.push_line("");
+        }
+
+        for index in 0..program.functions.len() {
+            self.funcdecl(program, index);
+        }
+
+        if self.pause_on_exit {
+            self.intermediary_code.push_str(PAUSE);
+        }
+
+        self.push_line("int main(void) {");
+        self.scope_level += 1;
+
+        if self.pause_on_exit {
+            self.push_line("atexit(jv_pause);");
+        }
+
+        for global in &program.globals {
+            if let StmtKind::Decl(_, _, declarators) = &global.kind {
+                for (var_name, initializer) in declarators {
+                    if let Some(value) = initializer {
+                        let line = format!("{} = {};", var_name, self.expr_code(program, value));
+                        self.push_line(&line);
+                    }
+                }
+            }
+        }
+
+        self.scope_level -= 1;
+        self.stmts_in_scope(program, &program.body);
+        self.push_line("");
+        self.scope_level += 1;
+        self.push_line("return 0;");
+        self.scope_level -= 1;
+        self.push_line("}");
+
+        self.intermediary_code
+    }
+
+    //Pushes a line of code, indented by the current scope level
+    fn push_line(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.intermediary_code
+                .push_str("    ".repeat(self.scope_level).as_str());
+        }
+
+        self.intermediary_code.push_str(text);
+        self.intermediary_code.push('\n');
+    }
+
+    fn funcdecl(&mut self, program: &Program, index: usize) {
+        let signature = self.signature_code(program, index);
+        self.push_line(format!("{} {{", signature).as_str());
+        self.stmts_in_scope(program, &program.functions[index].body);
+        self.push_line("}");
+        self.push_line("");
+    }
+
+    //Generates the signature of a function, shared
+    //by its prototype and its definition
+    fn signature_code(&self, program: &Program, index: usize) -> String {
+        let function = &program.functions[index];
+        let params: Vec<String> = function
+            .params
+            .iter()
+            .map(|(param_type, param_name)| format!("{} {}", map_type(param_type), param_name))
+            .collect();
+
+        let params = match params.is_empty() {
+            true => "void".into(),
+            false => params.join(", "),
+        };
+
+        format!(
+            "{} {}({})",
+            map_type(&function.return_type),
+            self.function_names[index],
+            params
+        )
+    }
+
+    //Generates the statements of a block, one level deeper
+    fn stmts_in_scope(&mut self, program: &Program, stmts: &[Stmt]) {
+        self.scope_level += 1;
+
+        for stmt in stmts {
+            self.stmt(program, stmt);
+        }
+
+        self.scope_level -= 1;
+    }
+
+    fn stmt(&mut self, program: &Program, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(constant, var_type, declarators) => {
+                let declarators: Vec<String> = declarators
+                    .iter()
+                    .map(|(var_name, initializer)| match initializer {
+                        Some(value) => format!("{} = {}", var_name, self.expr_code(program, value)),
+                        None => format!("{} = {}", var_name, default_value(var_type)),
+                    })
+                    .collect();
+
+                let const_prefix = if *constant { "const " } else { "" };
+                self.push_line(
+                    format!(
+                        "{}{} {};",
+                        const_prefix,
+                        map_type(var_type),
+                        declarators.join(", ")
+                    )
+                    .as_str(),
+                );
+            }
+            StmtKind::Asgn(..) => {
+                let line = format!("{};", self.asgn_code(program, stmt));
+                self.push_line(&line);
+            }
+            StmtKind::Call(call) => {
+                let line = format!("{};", self.expr_code(program, call));
+                self.push_line(&line);
+            }
+            StmtKind::Block(stmts) => {
+                self.push_line("{");
+                self.stmts_in_scope(program, stmts);
+                self.push_line("}");
+            }
+            StmtKind::If(condition, then_block, else_block) => {
+                let line = format!("if ({}) {{", self.expr_code(program, condition));
+                self.push_line(&line);
+                self.stmts_in_scope(program, then_block);
+
+                if let Some(else_block) = else_block {
+                    self.push_line("} else {");
+                    self.stmts_in_scope(program, else_block);
+                }
+
+                self.push_line("}");
+            }
+            StmtKind::While(condition, body) => {
+                let line = format!("while ({}) {{", self.expr_code(program, condition));
+                self.push_line(&line);
+                self.stmts_in_scope(program, body);
+                self.push_line("}");
+            }
+            StmtKind::DoWhile(body, condition) => {
+                self.push_line("do {");
+                self.stmts_in_scope(program, body);
+                let line = format!("}} while ({});", self.expr_code(program, condition));
+                self.push_line(&line);
+            }
+            StmtKind::For(init, condition, body) => {
+                let line = format!(
+                    "for ({}; {};) {{",
+                    self.asgn_code(program, init),
+                    self.expr_code(program, condition)
+                );
+                self.push_line(&line);
+                self.stmts_in_scope(program, body);
+                self.push_line("}");
+            }
+            StmtKind::Break => self.push_line("break;"),
+            StmtKind::Continue => self.push_line("continue;"),
+            StmtKind::Return(value) => match value {
+                Some(value) => {
+                    let line = format!("return {};", self.expr_code(program, value));
+                    self.push_line(&line);
+                }
+                None => self.push_line("return;"),
+            },
+        }
+    }
+
+    //Generates an assignment without the ending semicolon, so it can also
+    //be used in the header of a `for`. Strings are concatenated into a new one
+    fn asgn_code(&self, program: &Program, stmt: &Stmt) -> String {
+        match &stmt.kind {
+            StmtKind::Asgn(var_name, operator, value) if value.jv_type == "string" => {
+                match operator.as_str() {
+                    "+=" => format!(
+                        "{} = jv_concat({}, {})",
+                        var_name,
+                        var_name,
+                        self.expr_code(program, value)
+                    ),
+                    _ => format!("{} = {}", var_name, self.expr_code(program, value)),
+                }
+            }
+            StmtKind::Asgn(var_name, operator, value) => {
+                format!(
+                    "{} {} {}",
+                    var_name,
+                    operator,
+                    self.expr_code(program, value)
+                )
+            }
+            _ => String::new(),
+        }
+    }
+
+    //Generates the C code of an expression.
+    //Nested operations are always parenthesized, since
+    //Juvinil's precedence levels differ from C's
+    fn expr_code(&self, program: &Program, expr: &Expr) -> String {
+        let code = |expr: &Expr| self.expr_code(program, expr);
+        let nested = |expr: &Expr| self.nested_expr_code(program, expr);
+
+        match &expr.kind {
+            ExprKind::Int(value) => value.to_string(),
+            ExprKind::Bool(value) => value.to_string(),
+            ExprKind::Str(value) => format!("\"{}\"", value),
+            ExprKind::Var(name) => name.clone(),
+            ExprKind::Call(name, args) if name == "printf" => {
+                let args: Vec<String> = args.iter().map(code).collect();
+                format!("printf({})", args.join(", "))
+            }
+            ExprKind::Call(name, args) if name == "print" || name == "println" => {
+                self.print_code(program, args, name == "println")
+            }
+            ExprKind::Call(name, args) if RUNTIME_BUILTINS.contains(&name.as_str()) => {
+                let args: Vec<String> = args.iter().map(code).collect();
+                format!("jv_{}({})", name, args.join(", "))
+            }
+            ExprKind::Call(name, args) if name == "len" => {
+                format!("(int) strlen({})", code(&args[0]))
+            }
+            ExprKind::Call(name, args) if name == "contains" => {
+                format!("(strstr({}, {}) != NULL)", code(&args[0]), code(&args[1]))
+            }
+            ExprKind::Call(name, args) if name == "to_string" => {
+                format!("jv_to_string({})", code(&args[0]))
+            }
+            ExprKind::Call(name, args) if name == "exit" => format!("exit({})", code(&args[0])),
+            ExprKind::Call(name, args) => {
+                let index = program.function_index(name, args).unwrap();
+                let args: Vec<String> = args.iter().map(code).collect();
+                format!("{}({})", self.function_names[index], args.join(", "))
+            }
+            ExprKind::Unary(operator, operand) => format!("{}{}", operator, nested(operand)),
+            ExprKind::Binary(operator, left, right) if left.jv_type == "string" => {
+                match operator.as_str() {
+                    "+" => format!("jv_concat({}, {})", code(left), code(right)),
+                    _ => format!("strcmp({}, {}) {} 0", code(left), code(right), operator),
+                }
+            }
+            ExprKind::Binary(operator, left, right) if expr.may_overflow_division() => {
+                let helper = if operator == "/" { "jv_div" } else { "jv_rem" };
+                format!("{}({}, {})", helper, code(left), code(right))
+            }
+            ExprKind::Binary(operator, left, right) => {
+                format!("{} {} {}", nested(left), operator, nested(right))
+            }
+            ExprKind::Interpolation(parts) => {
+                let parts: Vec<String> = parts
+                    .iter()
+                    .map(|part| self.to_string_code(program, part))
+                    .collect();
+                format!("jv_join({}, {})", parts.len(), parts.join(", "))
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => format!(
+                "{} ? {} : {}",
+                nested(condition),
+                nested(then_expr),
+                nested(else_expr)
+            ),
+        }
+    }
+
+    //Generates a `printf` call that prints each value according to its
+    //type. Values are printed one after the other, with no separator
+    fn print_code(&self, program: &Program, args: &[Expr], newline: bool) -> String {
+        let mut format = String::new();
+        let mut values: Vec<String> = Vec::new();
+
+        for arg in args {
+            match arg.jv_type.as_str() {
+                "int" => format.push_str("%d"),
+                _ => format.push_str("%s"),
+            }
+
+            values.push(match arg.jv_type.as_str() {
+                "int" => self.expr_code(program, arg),
+                _ => self.to_string_code(program, arg),
+            });
+        }
+
+        if newline {
+            format.push_str("\\n");
+        }
+
+        values.insert(0, format!("\"{}\"", format));
+        format!("printf({})", values.join(", "))
+    }
+
+    //Converts a value of any type to a string,
+    //writing booleans as `true` or `false`
+    fn to_string_code(&self, program: &Program, expr: &Expr) -> String {
+        match expr.jv_type.as_str() {
+            "int" => format!("jv_to_string({})", self.expr_code(program, expr)),
+            "boolean" => format!(
+                "{} ? \"true\" : \"false\"",
+                self.nested_expr_code(program, expr)
+            ),
+            _ => self.expr_code(program, expr),
+        }
+    }
+
+    //Generates the C code of an operand, wrapping it in
+    //parenthesis if it is an operation itself
+    fn nested_expr_code(&self, program: &Program, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Unary(..) | ExprKind::Binary(..) | ExprKind::Ternary(..) => {
+                format!("({})", self.expr_code(program, expr))
+            }
+            _ => self.expr_code(program, expr),
+        }
+    }
+}
+
+impl Default for CGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//Maps a JvType to a C type
+fn map_type(value: &str) -> String {
+    match value {
+        "void" => "void".into(),
+        "int" => "int".into(),
+        "boolean" => "bool".into(),
+        "string" => "jv_string".into(),
+        _ => panic!("Unknown type `{}`", value),
+    }
+}
+
+//Value of a variable declared without an initializer
+fn default_value(var_type: &str) -> &'static str {
+    match var_type {
+        "string" => "\"\"",
+        "boolean" => "false",
+        _ => "0",
+    }
+}
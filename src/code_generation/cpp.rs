@@ -0,0 +1,475 @@
+use crate::syntax_analysis::{
+    ast::{Expr, ExprKind, FuncDecl, Program, Stmt, StmtKind},
+    builtins::RUNTIME_BUILTINS,
+};
+
+//Functions behind the builtins that need them, only emitted when the
+//program calls one of them. Words are read with `cin` and lines with
+//`getline`. When the input ends or a word isn't a valid value, the read
+//returns a default value and `input_ok` becomes false
+const RUNTIME: &str = r#"#include <iostream>
+#include <climits>
+#include <cerrno>
+
+namespace jv {
+bool last_read_ok = true;
+bool reached_eof = false;
+
+bool input_ok() { return last_read_ok; }
+bool input_eof() { return reached_eof; }
+
+bool is_int(const string &text) {
+    size_t start = text[0] == '-' || text[0] == '+' ? 1 : 0;
+    if (text.length() <= start || text.find_first_not_of("0123456789", start) != string::npos) {
+        return false;
+    }
+    errno = 0;
+    long value = strtol(text.c_str(), nullptr, 10);
+    return errno == 0 && value >= INT_MIN && value <= INT_MAX;
+}
+
+int parse_int(const string &text) {
+    return is_int(text) ? (int) strtol(text.c_str(), nullptr, 10) : 0;
+}
+
+string substr(const string &text, int start, int length) {
+    int size = (int) text.length();
+    start = start < 0 ? 0 : (start > size ? size : start);
+    length = length < 0 ? 0 : length;
+    return text.substr(start, length);
+}
+
+bool read_word(string &word) {
+    fflush(stdout);
+    if (cin >> word) {
+        return true;
+    }
+    last_read_ok = false;
+    reached_eof = true;
+    return false;
+}
+
+int read_int() {
+    string word;
+    if (!read_word(word)) {
+        return 0;
+    }
+    last_read_ok = is_int(word);
+    return parse_int(word);
+}
+
+bool read_bool() {
+    string word;
+    if (!read_word(word)) {
+        return false;
+    }
+    last_read_ok = word == "true" || word == "false";
+    return word == "true";
+}
+
+string read_line() {
+    string line;
+    fflush(stdout);
+    last_read_ok = (bool) getline(cin, line);
+    reached_eof = reached_eof || !last_read_ok;
+    return line;
+}
+}
+
+"#;
+
+//Division that wraps like the interpreter, since dividing the
+//smallest int by -1 overflows, which is undefined in C++
+const DIVISION: &str = r#"namespace jv {
+int div(int left, int right) {
+    return right == -1 ? (int) (0u - (unsigned) left) : left / right;
+}
+
+int rem(int left, int right) {
+    return right == -1 ? 0 : left % right;
+}
+}
+
+"#;
+
+//Pause registered with `atexit`, so the program waits for Enter
+//whether it reaches its end, returns early or calls `exit`. It's
+//registered at file scope, before any global is initialized
+const PAUSE: &str = r#"namespace jv {
+void pause() {
+    printf("Press Enter to exit...");
+    fflush(stdout);
+    getchar();
+}
+
+const int pause_registered = atexit(pause);
+}
+
+"#;
+
+//Generates C++ code out of a parsed program
+pub struct CppGenerator {
+    intermediary_code: String, //Code generated so far
+    scope_level: usize,        //Current scope level to determine tabs in intermediary code
+    pause_on_exit: bool,       //Wait for Enter before the program ends
+}
+
+impl CppGenerator {
+    pub fn new() -> Self {
+        CppGenerator {
+            intermediary_code: String::from(
+                "#include <stdio.h>\n#include <string>\n#include <cstdlib>\nusing namespace std;\n\n",
+            ),
+            scope_level: 0,
+            pause_on_exit: false,
+        }
+    }
+
+    //Makes the program wait for Enter before ending, so its
+    //output stays visible when it runs in its own console window
+    pub fn with_pause_on_exit(mut self, pause_on_exit: bool) -> Self {
+        self.pause_on_exit = pause_on_exit;
+        self
+    }
+
+    //Generates the code of the whole program. Function prototypes come
+    //first, so global initializers can call them, followed by the globals
+    //at file scope, the function definitions and a `main` function with
+    //the program statements
+    pub fn generate(mut self, program: &Program) -> String {
+        if RUNTIME_BUILTINS.iter().any(|name| program.calls(name)) {
+            self.intermediary_code.push_str(RUNTIME);
+        }
+
+        if program.may_overflow_division() {
+            self.intermediary_code.push_str(DIVISION);
+        }
+
+        if self.pause_on_exit {
+            self.intermediary_code.push_str(PAUSE);
+        }
+
+        if !program.functions.is_empty() {
+            for function in &program.functions {
+                self.push_line(format!("{};", signature_code(function)).as_str());
+            }
+
+            self.push_line("");
+        }
+
+        if !program.globals.is_empty() {
+            for global in &program.globals {
+                self.stmt(global);
+            }
+
+            self.push_line("");
+        }
+
+        for function in &program.functions {
+            self.funcdecl(function);
+        }
+
+        self.push_line("int main() {");
+        self.stmts_in_scope(&program.body);
+        self.push_line("");
+        self.scope_level += 1;
+        self.push_line("return 0;");
+        self.scope_level -= 1;
+        self.push_line("}");
+
+        self.intermediary_code
+    }
+
+    //Pushes a line of code, indented by the current scope level
+    fn push_line(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.intermediary_code
+                .push_str("    ".repeat(self.scope_level).as_str());
+        }
+
+        self.intermediary_code.push_str(text);
+        self.intermediary_code.push('\n');
+    }
+
+    fn funcdecl(&mut self, function: &FuncDecl) {
+        self.push_line(format!("{} {{", signature_code(function)).as_str());
+        self.stmts_in_scope(&function.body);
+        self.push_line("}");
+        self.push_line("");
+    }
+
+    //Generates the statements of a block, one level deeper
+    fn stmts_in_scope(&mut self, stmts: &[Stmt]) {
+        self.scope_level += 1;
+
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+
+        self.scope_level -= 1;
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(constant, var_type, declarators) => {
+                let declarators: Vec<String> = declarators
+                    .iter()
+                    .map(|(var_name, initializer)| match initializer {
+                        Some(value) => format!("{} = {}", var_name, expr_code(value)),
+                        None => var_name.clone(),
+                    })
+                    .collect();
+
+                let const_prefix = if *constant { "const " } else { "" };
+                self.push_line(
+                    format!(
+                        "{}{} {};",
+                        const_prefix,
+                        map_type(var_type),
+                        declarators.join(", ")
+                    )
+                    .as_str(),
+                );
+            }
+            StmtKind::Asgn(..) => self.push_line(format!("{};", asgn_code(stmt)).as_str()),
+            StmtKind::Call(call) => self.push_line(format!("{};", expr_code(call)).as_str()),
+            StmtKind::Block(stmts) => {
+                self.push_line("{");
+                self.stmts_in_scope(stmts);
+                self.push_line("}");
+            }
+            StmtKind::If(condition, then_block, else_block) => {
+                self.push_line(format!("if ({}) {{", expr_code(condition)).as_str());
+                self.stmts_in_scope(then_block);
+
+                if let Some(else_block) = else_block {
+                    self.push_line("} else {");
+                    self.stmts_in_scope(else_block);
+                }
+
+                self.push_line("}");
+            }
+            StmtKind::While(condition, body) => {
+                self.push_line(format!("while ({}) {{", expr_code(condition)).as_str());
+                self.stmts_in_scope(body);
+                self.push_line("}");
+            }
+            StmtKind::DoWhile(body, condition) => {
+                self.push_line("do {");
+                self.stmts_in_scope(body);
+                self.push_line(format!("}} while ({});", expr_code(condition)).as_str());
+            }
+            StmtKind::For(init, condition, body) => {
+                self.push_line(
+                    format!("for ({}; {};) {{", asgn_code(init), expr_code(condition)).as_str(),
+                );
+                self.stmts_in_scope(body);
+                self.push_line("}");
+            }
+            StmtKind::Break => self.push_line("break;"),
+            StmtKind::Continue => self.push_line("continue;"),
+            StmtKind::Return(value) => match value {
+                Some(value) => self.push_line(format!("return {};", expr_code(value)).as_str()),
+                None => self.push_line("return;"),
+            },
+        }
+    }
+}
+
+impl Default for CppGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//Maps a JvType to a C++ type
+fn map_type(value: &str) -> String {
+    match value {
+        "void" => "void".into(),
+        "int" => "int".into(),
+        "boolean" => "bool".into(),
+        "string" => "string".into(),
+        _ => panic!("Wtf"),
+    }
+}
+
+//Generates the signature of a function, shared
+//by its prototype and its definition
+fn signature_code(function: &FuncDecl) -> String {
+    let params: Vec<String> = function
+        .params
+        .iter()
+        .map(|(param_type, param_name)| format!("{} {}", map_type(param_type), param_name))
+        .collect();
+
+    format!(
+        "{} {}({})",
+        map_type(&function.return_type),
+        function.func_name,
+        params.join(", ")
+    )
+}
+
+//Generates an assignment without the ending semicolon,
+//so it can also be used in the header of a `for`
+fn asgn_code(stmt: &Stmt) -> String {
+    match &stmt.kind {
+        StmtKind::Asgn(var_name, operator, value) => {
+            format!("{} {} {}", var_name, operator, expr_code(value))
+        }
+        _ => String::new(),
+    }
+}
+
+//Generates the C++ code of an expression.
+//Nested operations are always parenthesized, since
+//Juvinil's precedence levels differ from C++'s
+fn expr_code(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Int(value) => value.to_string(),
+        ExprKind::Bool(value) => value.to_string(),
+        ExprKind::Str(value) => format!("\"{}\"", value),
+        ExprKind::Var(name) => name.clone(),
+        ExprKind::Call(name, args) if name == "printf" => {
+            let args: Vec<String> = args
+                .iter()
+                .enumerate()
+                .map(|(index, arg)| match index {
+                    0 => expr_code(arg),
+                    _ => printf_arg_code(arg),
+                })
+                .collect();
+            format!("printf({})", args.join(", "))
+        }
+        ExprKind::Call(name, args) if name == "print" || name == "println" => {
+            print_code(args, name == "println")
+        }
+        ExprKind::Call(name, args) if RUNTIME_BUILTINS.contains(&name.as_str()) => {
+            let args: Vec<String> = args.iter().map(arg_code).collect();
+            format!("jv::{}({})", name, args.join(", "))
+        }
+        ExprKind::Call(name, args) if name == "len" => {
+            format!("(int) {}.length()", string_code(&args[0]))
+        }
+        ExprKind::Call(name, args) if name == "contains" => format!(
+            "({}.find({}) != string::npos)",
+            string_code(&args[0]),
+            string_code(&args[1])
+        ),
+        ExprKind::Call(name, args) => {
+            let args: Vec<String> = args.iter().map(arg_code).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+        ExprKind::Unary(operator, operand) => {
+            format!("{}{}", operator, nested_expr_code(operand))
+        }
+        ExprKind::Binary(operator, left, right) if left.jv_type == "string" => {
+            format!("{} {} {}", string_code(left), operator, string_code(right))
+        }
+        ExprKind::Binary(operator, left, right) if expr.may_overflow_division() => {
+            let helper = if operator == "/" { "div" } else { "rem" };
+            format!("jv::{}({}, {})", helper, expr_code(left), expr_code(right))
+        }
+        ExprKind::Binary(operator, left, right) => format!(
+            "{} {} {}",
+            nested_expr_code(left),
+            operator,
+            nested_expr_code(right)
+        ),
+        ExprKind::Interpolation(parts) => {
+            let parts: Vec<String> = parts
+                .iter()
+                .enumerate()
+                .filter(|(index, part)| {
+                    *index == 0 || !matches!(&part.kind, ExprKind::Str(value) if value.is_empty())
+                })
+                .map(|(_, part)| to_string_code(part))
+                .collect();
+            format!("({})", parts.join(" + "))
+        }
+        ExprKind::Ternary(condition, then_expr, else_expr) => format!(
+            "{} ? {} : {}",
+            nested_expr_code(condition),
+            nested_expr_code(then_expr),
+            nested_expr_code(else_expr)
+        ),
+    }
+}
+
+//Generates a `printf` call that prints each value according to its
+//type. Values are printed one after the other, with no separator
+fn print_code(args: &[Expr], newline: bool) -> String {
+    let mut format = String::new();
+    let mut values: Vec<String> = Vec::new();
+
+    for arg in args {
+        match arg.jv_type.as_str() {
+            "int" => format.push_str("%d"),
+            _ => format.push_str("%s"),
+        }
+
+        //Booleans are printed as `true` or `false`
+        match arg.jv_type.as_str() {
+            "boolean" => values.push(format!("{} ? \"true\" : \"false\"", nested_expr_code(arg))),
+            _ => values.push(printf_arg_code(arg)),
+        }
+    }
+
+    if newline {
+        format.push_str("\\n");
+    }
+
+    values.insert(0, format!("\"{}\"", format));
+    format!("printf({})", values.join(", "))
+}
+
+//Generates a value passed to `printf`, where strings are C strings
+fn printf_arg_code(arg: &Expr) -> String {
+    match (&arg.kind, arg.jv_type.as_str()) {
+        (ExprKind::Str(_), _) => expr_code(arg),
+        (ExprKind::Var(_) | ExprKind::Call(..), "string") => format!("{}.c_str()", expr_code(arg)),
+        (_, "string") => format!("string({}).c_str()", expr_code(arg)),
+        _ => expr_code(arg),
+    }
+}
+
+//Generates the C++ code of an argument of a Juvinil function.
+//Overloads are left to C++, but a string literal would rather
+//convert to `bool` than to `string`, so strings are made explicit
+fn arg_code(arg: &Expr) -> String {
+    match arg.jv_type.as_str() {
+        "string" => string_code(arg),
+        _ => expr_code(arg),
+    }
+}
+
+//Converts a value of any type to a C++ `string`,
+//writing booleans as `true` or `false`
+fn to_string_code(expr: &Expr) -> String {
+    match expr.jv_type.as_str() {
+        "int" => format!("to_string({})", expr_code(expr)),
+        "boolean" => format!("string({} ? \"true\" : \"false\")", nested_expr_code(expr)),
+        _ => string_code(expr),
+    }
+}
+
+//Generates a string value as a C++ `string`. Literals are C strings,
+//which can't be concatenated nor compared by their contents, so they
+//are converted, along with conditionals that may result in a literal
+fn string_code(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Str(_) | ExprKind::Ternary(..) => format!("string({})", expr_code(expr)),
+        _ => nested_expr_code(expr),
+    }
+}
+
+//Generates the C++ code of an operand, wrapping it in
+//parenthesis if it is an operation itself
+fn nested_expr_code(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Unary(..) | ExprKind::Binary(..) | ExprKind::Ternary(..) => {
+            format!("({})", expr_code(expr))
+        }
+        _ => expr_code(expr),
+    }
+}
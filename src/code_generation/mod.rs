@@ -0,0 +1,34 @@
+pub mod asm;
+pub mod c;
+pub mod cpp;
+pub mod wat;
+
+use crate::syntax_analysis::ast::{FuncDecl, Program};
+
+//Name of a function in the C, assembly and WebAssembly backends.
+//Functions are prefixed, so they can't clash with the C library or
+//the runtime, and overloads get the types of their parameters
+//appended to their name, like `jv_fn_describe__int_int`
+pub fn function_name(program: &Program, function: &FuncDecl) -> String {
+    let overloaded = program
+        .functions
+        .iter()
+        .filter(|other| other.func_name == function.func_name)
+        .count()
+        > 1;
+
+    if !overloaded {
+        return format!("jv_fn_{}", function.func_name);
+    }
+
+    let types: Vec<&str> = function
+        .params
+        .iter()
+        .map(|(param_type, _)| param_type.as_str())
+        .collect();
+
+    match types.is_empty() {
+        true => format!("jv_fn_{}__void", function.func_name),
+        false => format!("jv_fn_{}__{}", function.func_name, types.join("_")),
+    }
+}
@@ -0,0 +1,1659 @@
+use std::collections::HashMap;
+
+use crate::{
+    interpreter::runtime::unescape,
+    syntax_analysis::{
+        ast::{Expr, ExprKind, Program, Stmt, StmtKind},
+        builtins::RUNTIME_BUILTINS,
+    },
+};
+
+use super::function_name;
+
+//Functions the host must provide, in the `env` module. `write` prints
+//bytes of the memory, `read_byte` returns the next byte of the input
+//or -1 at its end, and `exit` ends the program without returning
+const IMPORTS: &str = r#"  (import "env" "write" (func $write (param i32 i32)))
+  (import "env" "read_byte" (func $read_byte (result i32)))
+  (import "env" "exit" (func $exit (param i32)))
+"#;
+
+//State of the runtime. Reads keep the next byte in `jv_peeked` (-2 when
+//there's none) and build strings in `jv_buf`, like `jv_builder_push` does
+const RUNTIME_GLOBALS: &str = r#"  (global $jv_peeked (mut i32) (i32.const -2))
+  (global $jv_last_read_ok (mut i32) (i32.const 1))
+  (global $jv_reached_eof (mut i32) (i32.const 0))
+  (global $jv_scan_ok (mut i32) (i32.const 0))
+  (global $jv_buf (mut i32) (i32.const 0))
+  (global $jv_buf_len (mut i32) (i32.const 0))
+  (global $jv_buf_cap (mut i32) (i32.const 0))
+"#;
+
+//Strings of the runtime, which are the first literals of every module
+const RUNTIME_STRINGS: [(&str, &str); 8] = [
+    ("jv_empty", ""),
+    ("jv_true_str", "true"),
+    ("jv_false_str", "false"),
+    ("jv_minus_str", "-"),
+    ("jv_plus_str", "+"),
+    ("jv_space_str", " "),
+    ("jv_newline_str", "\n"),
+    ("jv_pause_str", "Press Enter to exit..."),
+];
+
+//Functions of the runtime, like the ones of the assembly backend. A string
+//is the address of its length, an i32, followed by its bytes. Strings are
+//allocated after the data of the module, and the memory grows when needed
+const RUNTIME: &str = r#"  (func $jv_alloc (param $size i32) (result i32)
+    (local $ptr i32)
+    global.get $jv_heap
+    local.set $ptr
+    global.get $jv_heap
+    local.get $size
+    i32.add
+    i32.const 7
+    i32.add
+    i32.const -8
+    i32.and
+    global.set $jv_heap
+    block $done
+      loop $grow
+        global.get $jv_heap
+        memory.size
+        i32.const 16
+        i32.shl
+        i32.le_u
+        br_if $done
+        i32.const 1
+        memory.grow
+        i32.const -1
+        i32.eq
+        if
+          unreachable
+        end
+        br $grow
+      end
+    end
+    local.get $ptr
+  )
+
+  (func $jv_new_string (param $src i32) (param $len i32) (result i32)
+    (local $str i32)
+    local.get $len
+    i32.const 4
+    i32.add
+    call $jv_alloc
+    local.tee $str
+    local.get $len
+    i32.store
+    local.get $str
+    i32.const 4
+    i32.add
+    local.get $src
+    local.get $len
+    memory.copy
+    local.get $str
+  )
+
+  (func $jv_concat (param $a i32) (param $b i32) (result i32)
+    (local $str i32)
+    (local $a_len i32)
+    (local $b_len i32)
+    local.get $a
+    i32.load
+    local.set $a_len
+    local.get $b
+    i32.load
+    local.set $b_len
+    local.get $a_len
+    local.get $b_len
+    i32.add
+    i32.const 4
+    i32.add
+    call $jv_alloc
+    local.tee $str
+    local.get $a_len
+    local.get $b_len
+    i32.add
+    i32.store
+    local.get $str
+    i32.const 4
+    i32.add
+    local.get $a
+    i32.const 4
+    i32.add
+    local.get $a_len
+    memory.copy
+    local.get $str
+    i32.const 4
+    i32.add
+    local.get $a_len
+    i32.add
+    local.get $b
+    i32.const 4
+    i32.add
+    local.get $b_len
+    memory.copy
+    local.get $str
+  )
+
+  (func $jv_str_cmp (param $a i32) (param $b i32) (result i32)
+    (local $a_len i32)
+    (local $b_len i32)
+    (local $i i32)
+    (local $x i32)
+    (local $y i32)
+    local.get $a
+    i32.load
+    local.set $a_len
+    local.get $b
+    i32.load
+    local.set $b_len
+    block $done
+      loop $next
+        local.get $i
+        local.get $a_len
+        i32.ge_u
+        br_if $done
+        local.get $i
+        local.get $b_len
+        i32.ge_u
+        br_if $done
+        local.get $a
+        local.get $i
+        i32.add
+        i32.load8_u offset=4
+        local.set $x
+        local.get $b
+        local.get $i
+        i32.add
+        i32.load8_u offset=4
+        local.set $y
+        local.get $x
+        local.get $y
+        i32.ne
+        if
+          local.get $x
+          local.get $y
+          i32.gt_u
+          local.get $x
+          local.get $y
+          i32.lt_u
+          i32.sub
+          return
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $next
+      end
+    end
+    local.get $a_len
+    local.get $b_len
+    i32.gt_u
+    local.get $a_len
+    local.get $b_len
+    i32.lt_u
+    i32.sub
+  )
+
+  (func $jv_contains (param $s i32) (param $sub i32) (result i32)
+    (local $last i32)
+    (local $i i32)
+    (local $j i32)
+    (local $sub_len i32)
+    local.get $sub
+    i32.load
+    local.set $sub_len
+    local.get $s
+    i32.load
+    local.get $sub_len
+    i32.lt_u
+    if
+      i32.const 0
+      return
+    end
+    local.get $s
+    i32.load
+    local.get $sub_len
+    i32.sub
+    local.set $last
+    block $not_found
+      loop $start
+        i32.const 0
+        local.set $j
+        block $mismatch
+          loop $compare
+            local.get $j
+            local.get $sub_len
+            i32.ge_u
+            if
+              i32.const 1
+              return
+            end
+            local.get $s
+            local.get $i
+            i32.add
+            local.get $j
+            i32.add
+            i32.load8_u offset=4
+            local.get $sub
+            local.get $j
+            i32.add
+            i32.load8_u offset=4
+            i32.ne
+            br_if $mismatch
+            local.get $j
+            i32.const 1
+            i32.add
+            local.set $j
+            br $compare
+          end
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.tee $i
+        local.get $last
+        i32.gt_u
+        br_if $not_found
+        br $start
+      end
+    end
+    i32.const 0
+  )
+
+  (func $jv_substr (param $s i32) (param $start i32) (param $length i32) (result i32)
+    (local $size i32)
+    local.get $s
+    i32.load
+    local.set $size
+    local.get $start
+    i32.const 0
+    i32.lt_s
+    if
+      i32.const 0
+      local.set $start
+    end
+    local.get $start
+    local.get $size
+    i32.gt_s
+    if
+      local.get $size
+      local.set $start
+    end
+    local.get $length
+    i32.const 0
+    i32.lt_s
+    if
+      i32.const 0
+      local.set $length
+    end
+    local.get $length
+    local.get $size
+    local.get $start
+    i32.sub
+    i32.gt_s
+    if
+      local.get $size
+      local.get $start
+      i32.sub
+      local.set $length
+    end
+    local.get $s
+    i32.const 4
+    i32.add
+    local.get $start
+    i32.add
+    local.get $length
+    call $jv_new_string
+  )
+
+  (func $jv_div (param $left i32) (param $right i32) (result i32)
+    local.get $right
+    i32.const -1
+    i32.eq
+    if (result i32)
+      i32.const 0
+      local.get $left
+      i32.sub
+    else
+      local.get $left
+      local.get $right
+      i32.div_s
+    end
+  )
+
+  (func $jv_digits (param $value i64) (result i32)
+    (local $count i32)
+    (local $rest i64)
+    (local $str i32)
+    local.get $value
+    local.set $rest
+    loop $count_digits
+      local.get $count
+      i32.const 1
+      i32.add
+      local.set $count
+      local.get $rest
+      i64.const 10
+      i64.div_u
+      local.tee $rest
+      i64.const 0
+      i64.ne
+      br_if $count_digits
+    end
+    local.get $count
+    i32.const 4
+    i32.add
+    call $jv_alloc
+    local.tee $str
+    local.get $count
+    i32.store
+    loop $write_digits
+      local.get $str
+      local.get $count
+      i32.add
+      local.get $value
+      i64.const 10
+      i64.rem_u
+      i32.wrap_i64
+      i32.const 48
+      i32.add
+      i32.store8 offset=3
+      local.get $value
+      i64.const 10
+      i64.div_u
+      local.set $value
+      local.get $count
+      i32.const 1
+      i32.sub
+      local.tee $count
+      br_if $write_digits
+    end
+    local.get $str
+  )
+
+  (func $jv_to_string (param $value i32) (result i32)
+    local.get $value
+    i32.const 0
+    i32.lt_s
+    if (result i32)
+      global.get $jv_minus_str
+      i64.const 0
+      local.get $value
+      i64.extend_i32_s
+      i64.sub
+      call $jv_digits
+      call $jv_concat
+    else
+      local.get $value
+      i64.extend_i32_s
+      call $jv_digits
+    end
+  )
+
+  (func $jv_bool_string (param $value i32) (result i32)
+    global.get $jv_true_str
+    global.get $jv_false_str
+    local.get $value
+    select
+  )
+
+  (func $jv_format_int (param $value i32) (param $precision i32) (param $sign i32) (result i32)
+    (local $magnitude i64)
+    (local $digits i32)
+    local.get $value
+    i64.extend_i32_s
+    local.set $magnitude
+    local.get $value
+    i32.const 0
+    i32.lt_s
+    if
+      global.get $jv_minus_str
+      local.set $sign
+      i64.const 0
+      local.get $magnitude
+      i64.sub
+      local.set $magnitude
+    end
+    local.get $magnitude
+    call $jv_digits
+    local.set $digits
+    local.get $precision
+    i32.const 0
+    i32.ge_s
+    if
+      local.get $precision
+      i32.eqz
+      local.get $value
+      i32.eqz
+      i32.and
+      if (result i32)
+        global.get $jv_empty
+      else
+        local.get $digits
+        local.get $precision
+        i32.const 2
+        call $jv_pad
+      end
+      local.set $digits
+    end
+    local.get $sign
+    local.get $digits
+    call $jv_concat
+  )
+
+  (func $jv_pad (param $text i32) (param $width i32) (param $mode i32) (result i32)
+    (local $len i32)
+    (local $missing i32)
+    (local $str i32)
+    (local $dest i32)
+    (local $src i32)
+    (local $first i32)
+    local.get $text
+    i32.load
+    local.set $len
+    local.get $width
+    local.get $len
+    i32.le_s
+    if
+      local.get $text
+      return
+    end
+    local.get $width
+    local.get $len
+    i32.sub
+    local.set $missing
+    local.get $width
+    i32.const 4
+    i32.add
+    call $jv_alloc
+    local.tee $str
+    local.get $width
+    i32.store
+    local.get $str
+    i32.const 4
+    i32.add
+    local.set $dest
+    local.get $text
+    i32.const 4
+    i32.add
+    local.set $src
+    local.get $mode
+    i32.const 1
+    i32.eq
+    if
+      local.get $dest
+      local.get $src
+      local.get $len
+      memory.copy
+      local.get $dest
+      local.get $len
+      i32.add
+      i32.const 32
+      local.get $missing
+      memory.fill
+      local.get $str
+      return
+    end
+    local.get $mode
+    i32.const 2
+    i32.eq
+    local.get $len
+    i32.const 0
+    i32.gt_s
+    i32.and
+    if
+      local.get $src
+      i32.load8_u
+      local.set $first
+      local.get $first
+      i32.const 45
+      i32.eq
+      local.get $first
+      i32.const 43
+      i32.eq
+      i32.or
+      local.get $first
+      i32.const 32
+      i32.eq
+      i32.or
+      if
+        local.get $dest
+        local.get $first
+        i32.store8
+        local.get $dest
+        i32.const 1
+        i32.add
+        local.set $dest
+        local.get $src
+        i32.const 1
+        i32.add
+        local.set $src
+        local.get $len
+        i32.const 1
+        i32.sub
+        local.set $len
+      end
+    end
+    local.get $dest
+    i32.const 48
+    i32.const 32
+    local.get $mode
+    i32.const 2
+    i32.eq
+    select
+    local.get $missing
+    memory.fill
+    local.get $dest
+    local.get $missing
+    i32.add
+    local.get $src
+    local.get $len
+    memory.copy
+    local.get $str
+  )
+
+  (func $jv_scan_int (param $s i32) (result i32)
+    (local $len i32)
+    (local $i i32)
+    (local $negative i32)
+    (local $value i64)
+    (local $digit i32)
+    i32.const 0
+    global.set $jv_scan_ok
+    local.get $s
+    i32.load
+    local.tee $len
+    i32.eqz
+    if
+      i32.const 0
+      return
+    end
+    local.get $s
+    i32.load8_u offset=4
+    local.set $digit
+    local.get $digit
+    i32.const 45
+    i32.eq
+    local.set $negative
+    local.get $negative
+    local.get $digit
+    i32.const 43
+    i32.eq
+    i32.or
+    if
+      i32.const 1
+      local.set $i
+      local.get $len
+      i32.const 1
+      i32.eq
+      if
+        i32.const 0
+        return
+      end
+    end
+    loop $next
+      local.get $s
+      local.get $i
+      i32.add
+      i32.load8_u offset=4
+      i32.const 48
+      i32.sub
+      local.tee $digit
+      i32.const 9
+      i32.gt_u
+      if
+        i32.const 0
+        return
+      end
+      local.get $value
+      i64.const 10
+      i64.mul
+      local.get $digit
+      i64.extend_i32_u
+      i64.add
+      local.tee $value
+      i64.const 2147483648
+      i64.gt_u
+      if
+        i32.const 0
+        return
+      end
+      local.get $i
+      i32.const 1
+      i32.add
+      local.tee $i
+      local.get $len
+      i32.lt_u
+      br_if $next
+    end
+    local.get $negative
+    if
+      i64.const 0
+      local.get $value
+      i64.sub
+      local.set $value
+    else
+      local.get $value
+      i64.const 2147483648
+      i64.eq
+      if
+        i32.const 0
+        return
+      end
+    end
+    i32.const 1
+    global.set $jv_scan_ok
+    local.get $value
+    i32.wrap_i64
+  )
+
+  (func $jv_is_int (param $s i32) (result i32)
+    local.get $s
+    call $jv_scan_int
+    drop
+    global.get $jv_scan_ok
+  )
+
+  (func $jv_parse_int (param $s i32) (result i32)
+    local.get $s
+    call $jv_scan_int
+  )
+
+  (func $jv_print_str (param $s i32)
+    local.get $s
+    i32.const 4
+    i32.add
+    local.get $s
+    i32.load
+    call $write
+  )
+
+  (func $jv_print_int (param $value i32)
+    local.get $value
+    call $jv_to_string
+    call $jv_print_str
+  )
+
+  (func $jv_print_bool (param $value i32)
+    local.get $value
+    call $jv_bool_string
+    call $jv_print_str
+  )
+
+  (func $jv_print_newline
+    global.get $jv_newline_str
+    call $jv_print_str
+  )
+
+  (func $jv_exit (param $status i32)
+    local.get $status
+    call $exit
+    unreachable
+  )
+
+  (func $jv_peek (result i32)
+    global.get $jv_peeked
+    i32.const -2
+    i32.eq
+    if
+      call $read_byte
+      global.set $jv_peeked
+    end
+    global.get $jv_peeked
+  )
+
+  (func $jv_getc (result i32)
+    (local $c i32)
+    call $jv_peek
+    local.tee $c
+    i32.const 0
+    i32.ge_s
+    if
+      i32.const -2
+      global.set $jv_peeked
+    end
+    local.get $c
+  )
+
+  (func $jv_is_space (param $c i32) (result i32)
+    local.get $c
+    i32.const 32
+    i32.eq
+    local.get $c
+    i32.const 9
+    i32.sub
+    i32.const 4
+    i32.le_u
+    i32.or
+  )
+
+  (func $jv_builder_start
+    i32.const 0
+    global.set $jv_buf
+    i32.const 0
+    global.set $jv_buf_len
+    i32.const 0
+    global.set $jv_buf_cap
+  )
+
+  (func $jv_builder_push (param $c i32)
+    (local $grown i32)
+    global.get $jv_buf_len
+    global.get $jv_buf_cap
+    i32.ge_u
+    if
+      global.get $jv_buf_cap
+      i32.const 1
+      i32.shl
+      i32.const 16
+      i32.add
+      global.set $jv_buf_cap
+      global.get $jv_buf_cap
+      i32.const 4
+      i32.add
+      call $jv_alloc
+      local.tee $grown
+      i32.const 4
+      i32.add
+      global.get $jv_buf
+      i32.const 4
+      i32.add
+      global.get $jv_buf_len
+      memory.copy
+      local.get $grown
+      global.set $jv_buf
+    end
+    global.get $jv_buf
+    global.get $jv_buf_len
+    i32.add
+    local.get $c
+    i32.store8 offset=4
+    global.get $jv_buf_len
+    i32.const 1
+    i32.add
+    global.set $jv_buf_len
+  )
+
+  (func $jv_builder_finish (result i32)
+    global.get $jv_buf_len
+    i32.eqz
+    if (result i32)
+      global.get $jv_empty
+    else
+      global.get $jv_buf
+      global.get $jv_buf_len
+      i32.store
+      global.get $jv_buf
+    end
+  )
+
+  (func $jv_read_word (result i32)
+    (local $c i32)
+    call $jv_builder_start
+    block $skipped
+      loop $skip
+        call $jv_peek
+        call $jv_is_space
+        i32.eqz
+        br_if $skipped
+        call $jv_getc
+        drop
+        br $skip
+      end
+    end
+    block $done
+      loop $take
+        call $jv_peek
+        local.tee $c
+        i32.const 0
+        i32.lt_s
+        br_if $done
+        local.get $c
+        call $jv_is_space
+        br_if $done
+        call $jv_getc
+        call $jv_builder_push
+        br $take
+      end
+    end
+    global.get $jv_buf_len
+    i32.eqz
+    if
+      i32.const 0
+      global.set $jv_last_read_ok
+      i32.const 1
+      global.set $jv_reached_eof
+      i32.const 0
+      return
+    end
+    call $jv_builder_finish
+  )
+
+  (func $jv_read_int (result i32)
+    (local $word i32)
+    call $jv_read_word
+    local.tee $word
+    i32.eqz
+    if
+      i32.const 0
+      return
+    end
+    local.get $word
+    call $jv_scan_int
+    global.get $jv_scan_ok
+    global.set $jv_last_read_ok
+  )
+
+  (func $jv_read_bool (result i32)
+    (local $word i32)
+    (local $is_true i32)
+    call $jv_read_word
+    local.tee $word
+    i32.eqz
+    if
+      i32.const 0
+      return
+    end
+    local.get $word
+    global.get $jv_true_str
+    call $jv_str_cmp
+    i32.eqz
+    local.set $is_true
+    local.get $is_true
+    local.get $word
+    global.get $jv_false_str
+    call $jv_str_cmp
+    i32.eqz
+    i32.or
+    global.set $jv_last_read_ok
+    local.get $is_true
+  )
+
+  (func $jv_read_line (result i32)
+    (local $c i32)
+    call $jv_builder_start
+    block $done
+      loop $next
+        call $jv_getc
+        local.tee $c
+        i32.const 0
+        i32.lt_s
+        br_if $done
+        local.get $c
+        i32.const 10
+        i32.eq
+        br_if $done
+        local.get $c
+        call $jv_builder_push
+        br $next
+      end
+    end
+    local.get $c
+    i32.const 0
+    i32.ge_s
+    global.get $jv_buf_len
+    i32.const 0
+    i32.gt_u
+    i32.or
+    global.set $jv_last_read_ok
+    global.get $jv_last_read_ok
+    i32.eqz
+    if
+      i32.const 1
+      global.set $jv_reached_eof
+    end
+    call $jv_builder_finish
+  )
+
+  (func $jv_input_ok (result i32)
+    global.get $jv_last_read_ok
+  )
+
+  (func $jv_input_eof (result i32)
+    global.get $jv_reached_eof
+  )
+
+  (func $jv_pause
+    global.get $jv_pause_str
+    call $jv_print_str
+    call $jv_getc
+    drop
+  )
+"#;
+
+//Strings of the program start after the first 8 bytes,
+//so no string is at the address 0
+const DATA_START: u32 = 8;
+const PAGE_SIZE: u32 = 65536;
+
+//Generates a WebAssembly module in the text format out of a parsed
+//program, to run it wherever there's a host for its imports, like a
+//browser. Every value is an i32: ints, booleans that are 0 or 1, and
+//strings, which are addresses in the memory. Each function of the
+//program becomes a function of the module, and the main program
+//becomes `main`, which is exported and returns the exit status
+pub struct WatGenerator {
+    code: String,                         //Instructions of the current function
+    indent: usize,                        //Depth of the blocks being generated
+    literals: Vec<(String, u32)>,         //Strings in the memory, with their address
+    data_end: u32,                        //Address after the last string
+    pause_on_exit: bool,                  //Wait for Enter before the program ends
+    in_main: bool,                        //Whether `main` is being generated
+    function_names: Vec<String>,          //Name of each function, like in the C backend
+    scopes: Vec<HashMap<String, String>>, //Name of the local of each variable in scope
+    locals: Vec<String>,                  //Locals of the current function, parameters first
+    params: usize,                        //How many of the locals are parameters
+    temps: usize,                         //Temporary locals in use
+    label_count: usize,                   //Labels of blocks generated so far
+    loops: Vec<(String, String)>,         //Labels of `continue` and `break` of each loop
+}
+
+impl WatGenerator {
+    pub fn new() -> Self {
+        let mut generator = WatGenerator {
+            code: String::new(),
+            indent: 0,
+            literals: Vec::new(),
+            data_end: DATA_START,
+            pause_on_exit: false,
+            in_main: false,
+            function_names: Vec::new(),
+            scopes: Vec::new(),
+            locals: Vec::new(),
+            params: 0,
+            temps: 0,
+            label_count: 0,
+            loops: Vec::new(),
+        };
+
+        for (_, text) in RUNTIME_STRINGS {
+            generator.literal(text.to_string());
+        }
+
+        generator
+    }
+
+    //Makes the program wait for Enter before ending, so its
+    //output stays visible when it runs in its own console window
+    pub fn with_pause_on_exit(mut self, pause_on_exit: bool) -> Self {
+        self.pause_on_exit = pause_on_exit;
+        self
+    }
+
+    //Generates the whole module. Like in the C backend, globals
+    //start with their default value and are initialized by `main`
+    pub fn generate(mut self, program: &Program) -> String {
+        self.function_names = program
+            .functions
+            .iter()
+            .map(|function| function_name(program, function))
+            .collect();
+
+        let mut functions = String::new();
+        for (index, function) in program.functions.iter().enumerate() {
+            let name = self.function_names[index].clone();
+            let result = match function.return_type.as_str() {
+                "void" => None,
+                _ => Some("i32"),
+            };
+
+            functions.push_str(
+                &self.function(&name, &function.params, result, |generator| {
+                    generator.stmts(program, &function.body);
+
+                    //Functions that end without returning return 0
+                    if result.is_some() {
+                        generator.emit("i32.const 0");
+                    }
+                }),
+            );
+        }
+
+        self.in_main = true;
+        functions.push_str(&self.function("jv_main", &[], Some("i32"), |generator| {
+            for global in &program.globals {
+                if let StmtKind::Decl(_, _, declarators) = &global.kind {
+                    for (var_name, initializer) in declarators {
+                        if let Some(value) = initializer {
+                            generator.expr(program, value);
+                            generator.emit(&format!("global.set $global.{}", var_name));
+                        }
+                    }
+                }
+            }
+
+            generator.stmts(program, &program.body);
+
+            if generator.pause_on_exit {
+                generator.emit("call $jv_pause");
+            }
+            generator.emit("i32.const 0");
+        }));
+
+        let mut module = String::from("(module\n");
+        module.push_str(IMPORTS);
+
+        //The strings are followed by the heap, which has at least a page
+        let heap_start = self.data_end.next_multiple_of(8);
+        module.push_str(&format!(
+            "\n  (memory (export \"memory\") {})\n",
+            heap_start / PAGE_SIZE + 1
+        ));
+        module.push_str(&format!(
+            "  (export \"main\" (func $jv_main))\n\n  (global $jv_heap (mut i32) (i32.const {}))\n",
+            heap_start
+        ));
+        module.push_str(RUNTIME_GLOBALS);
+        for (index, (name, _)) in RUNTIME_STRINGS.iter().enumerate() {
+            module.push_str(&format!(
+                "  (global ${} i32 (i32.const {}))\n",
+                name, self.literals[index].1
+            ));
+        }
+
+        for global in &program.globals {
+            if let StmtKind::Decl(_, var_type, declarators) = &global.kind {
+                for (var_name, _) in declarators {
+                    let default = match var_type.as_str() {
+                        "string" => self.literals[0].1,
+                        _ => 0,
+                    };
+                    module.push_str(&format!(
+                        "  (global $global.{} (mut i32) (i32.const {}))\n",
+                        var_name, default
+                    ));
+                }
+            }
+        }
+
+        module.push('\n');
+        for (text, address) in &self.literals {
+            let mut bytes = (text.len() as u32).to_le_bytes().to_vec();
+            bytes.extend(text.bytes());
+            module.push_str(&format!(
+                "  (data (i32.const {}) \"{}\")\n",
+                address,
+                escape(&bytes)
+            ));
+        }
+
+        module.push('\n');
+        module.push_str(RUNTIME);
+        module.push_str(&functions);
+        module.push_str(")\n");
+        module
+    }
+
+    //Pushes an instruction, indented by the blocks it's in
+    fn emit(&mut self, instruction: &str) {
+        self.code.push_str(&"  ".repeat(self.indent + 2));
+        self.code.push_str(instruction);
+        self.code.push('\n');
+    }
+
+    //Starts a `block`, `loop` or `if`
+    fn open(&mut self, instruction: &str) {
+        self.emit(instruction);
+        self.indent += 1;
+    }
+
+    fn close(&mut self) {
+        self.indent -= 1;
+        self.emit("end");
+    }
+
+    fn otherwise(&mut self) {
+        self.indent -= 1;
+        self.emit("else");
+        self.indent += 1;
+    }
+
+    fn new_label(&mut self) -> usize {
+        self.label_count += 1;
+        self.label_count
+    }
+
+    //Generates a function whose body is generated by `body`. Locals
+    //can only be declared before the body, so they are collected
+    //while the body is generated
+    fn function(
+        &mut self,
+        name: &str,
+        params: &[(String, String)],
+        result: Option<&str>,
+        body: impl FnOnce(&mut Self),
+    ) -> String {
+        self.scopes.push(HashMap::new());
+        self.locals.clear();
+        self.temps = 0;
+        for (_, param_name) in params {
+            self.declare(param_name);
+        }
+        self.params = params.len();
+
+        body(self);
+        self.scopes.pop();
+
+        let mut header = format!("\n  (func ${}", name);
+        for param in &self.locals[..self.params] {
+            header.push_str(&format!(" (param {} i32)", param));
+        }
+        if let Some(result) = result {
+            header.push_str(&format!(" (result {})", result));
+        }
+        header.push('\n');
+
+        for local in &self.locals[self.params..] {
+            header.push_str(&format!("    (local {} i32)\n", local));
+        }
+
+        format!("{}{}  )\n", header, std::mem::take(&mut self.code))
+    }
+
+    //Gives a variable a new local, named after it. Variables declared
+    //again, in another scope, get a number appended to their name
+    fn declare(&mut self, var_name: &str) -> String {
+        let mut local = format!("${}", var_name);
+        let mut count = 0;
+        while self.locals.contains(&local) {
+            count += 1;
+            local = format!("${}.{}", var_name, count);
+        }
+
+        self.locals.push(local.clone());
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(var_name.to_string(), local.clone());
+        local
+    }
+
+    //A local to keep a value for a while. Temporaries
+    //are freed in the opposite order they're taken
+    fn temp(&mut self) -> String {
+        let local = format!("$tmp.{}", self.temps);
+        if !self.locals.contains(&local) {
+            self.locals.push(local.clone());
+        }
+
+        self.temps += 1;
+        local
+    }
+
+    fn free_temps(&mut self, count: usize) {
+        self.temps -= count;
+    }
+
+    fn local(&self, var_name: &str) -> Option<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var_name))
+            .cloned()
+    }
+
+    fn load(&mut self, var_name: &str) {
+        match self.local(var_name) {
+            Some(local) => self.emit(&format!("local.get {}", local)),
+            None => self.emit(&format!("global.get $global.{}", var_name)),
+        }
+    }
+
+    fn store(&mut self, var_name: &str) {
+        match self.local(var_name) {
+            Some(local) => self.emit(&format!("local.set {}", local)),
+            None => self.emit(&format!("global.set $global.{}", var_name)),
+        }
+    }
+
+    //Address of a string literal, given without its escape sequences
+    fn literal(&mut self, text: String) -> u32 {
+        if let Some((_, address)) = self.literals.iter().find(|(literal, _)| *literal == text) {
+            return *address;
+        }
+
+        let address = self.data_end;
+        self.data_end = (address + 4 + text.len() as u32).next_multiple_of(4);
+        self.literals.push((text, address));
+        address
+    }
+
+    //Generates the statements of a block in a new scope
+    fn stmts(&mut self, program: &Program, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+
+        for stmt in stmts {
+            self.stmt(program, stmt);
+        }
+
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, program: &Program, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(_, var_type, declarators) => {
+                for (var_name, initializer) in declarators {
+                    match initializer {
+                        Some(value) => self.expr(program, value),
+                        None if var_type == "string" => self.emit("global.get $jv_empty"),
+                        None => self.emit("i32.const 0"),
+                    }
+
+                    let local = self.declare(var_name);
+                    self.emit(&format!("local.set {}", local));
+                }
+            }
+            StmtKind::Asgn(var_name, operator, value) => {
+                self.expr(program, value);
+
+                //The value is computed before the variable is read
+                if operator != "=" {
+                    let temp = self.temp();
+                    self.emit(&format!("local.set {}", temp));
+                    self.load(var_name);
+                    self.emit(&format!("local.get {}", temp));
+                    self.free_temps(1);
+
+                    match (operator.as_str(), value.jv_type.as_str()) {
+                        ("+=", "string") => self.emit("call $jv_concat"),
+                        ("+=", _) => self.emit("i32.add"),
+                        _ => self.emit("i32.sub"),
+                    }
+                }
+
+                self.store(var_name);
+            }
+            StmtKind::Call(call) => {
+                self.expr(program, call);
+                if call.jv_type != "void" {
+                    self.emit("drop");
+                }
+            }
+            StmtKind::Block(stmts) => self.stmts(program, stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                self.expr(program, condition);
+                self.open("if");
+                self.stmts(program, then_block);
+                if let Some(else_block) = else_block {
+                    self.otherwise();
+                    self.stmts(program, else_block);
+                }
+                self.close();
+            }
+            StmtKind::While(condition, body) => self.loop_stmt(program, None, condition, body),
+            StmtKind::For(init, condition, body) => {
+                self.loop_stmt(program, Some(init), condition, body)
+            }
+            StmtKind::DoWhile(body, condition) => {
+                //`continue` leaves the block of the body, going to the condition
+                let label = self.new_label();
+                self.open(&format!("block $break.{}", label));
+                self.open(&format!("loop $loop.{}", label));
+                self.open(&format!("block $continue.{}", label));
+                self.loops
+                    .push((format!("$continue.{}", label), format!("$break.{}", label)));
+                self.stmts(program, body);
+                self.loops.pop();
+                self.close();
+                self.expr(program, condition);
+                self.emit(&format!("br_if $loop.{}", label));
+                self.close();
+                self.close();
+            }
+            StmtKind::Break => {
+                let (_, break_label) = self.loops.last().unwrap().clone();
+                self.emit(&format!("br {}", break_label));
+            }
+            StmtKind::Continue => {
+                let (continue_label, _) = self.loops.last().unwrap().clone();
+                self.emit(&format!("br {}", continue_label));
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(program, value);
+                }
+
+                //Returning from `main` ends the program
+                if self.in_main && self.pause_on_exit {
+                    self.emit("call $jv_pause");
+                }
+                self.emit("return");
+            }
+        }
+    }
+
+    //Generates a `while` or a `for`, whose condition is at the
+    //start of the loop, which is where `continue` goes
+    fn loop_stmt(
+        &mut self,
+        program: &Program,
+        init: Option<&Stmt>,
+        condition: &Expr,
+        body: &[Stmt],
+    ) {
+        if let Some(init) = init {
+            self.stmt(program, init);
+        }
+
+        let label = self.new_label();
+        self.open(&format!("block $break.{}", label));
+        self.open(&format!("loop $loop.{}", label));
+        self.expr(program, condition);
+        self.emit("i32.eqz");
+        self.emit(&format!("br_if $break.{}", label));
+        self.loops
+            .push((format!("$loop.{}", label), format!("$break.{}", label)));
+        self.stmts(program, body);
+        self.loops.pop();
+        self.emit(&format!("br $loop.{}", label));
+        self.close();
+        self.close();
+    }
+
+    //Generates an expression, which leaves its value on the stack
+    fn expr(&mut self, program: &Program, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Int(value) => self.emit(&format!("i32.const {}", value)),
+            ExprKind::Bool(value) => self.emit(&format!("i32.const {}", *value as i32)),
+            ExprKind::Str(value) => {
+                let address = self.literal(unescape(value));
+                self.emit(&format!("i32.const {}", address));
+            }
+            ExprKind::Var(name) => self.load(name),
+            ExprKind::Call(name, args) if name == "print" || name == "println" => {
+                self.print(program, args, name == "println")
+            }
+            ExprKind::Call(name, args) if name == "printf" => self.printf(program, args),
+            ExprKind::Call(name, args) if name == "len" => {
+                self.expr(program, &args[0]);
+                self.emit("i32.load");
+            }
+            ExprKind::Call(name, args) => {
+                for arg in args {
+                    self.expr(program, arg);
+                }
+
+                //The status stays on the stack while pausing
+                if name == "exit" && self.pause_on_exit {
+                    self.emit("call $jv_pause");
+                }
+
+                let is_runtime = RUNTIME_BUILTINS.contains(&name.as_str())
+                    || ["contains", "to_string", "exit"].contains(&name.as_str());
+                match is_runtime {
+                    true => self.emit(&format!("call $jv_{}", name)),
+                    false => {
+                        let index = program.function_index(name, args).unwrap();
+                        let call = format!("call ${}", self.function_names[index]);
+                        self.emit(&call);
+                    }
+                }
+            }
+            ExprKind::Unary(operator, operand) if operator == "-" => {
+                self.emit("i32.const 0");
+                self.expr(program, operand);
+                self.emit("i32.sub");
+            }
+            ExprKind::Unary(operator, operand) => {
+                self.expr(program, operand);
+                match operator.as_str() {
+                    "!" => self.emit("i32.eqz"),
+                    "++" => {
+                        self.emit("i32.const 1");
+                        self.emit("i32.add");
+                    }
+                    _ => {
+                        self.emit("i32.const 1");
+                        self.emit("i32.sub");
+                    }
+                }
+
+                if let ("++" | "--", ExprKind::Var(var_name)) = (operator.as_str(), &operand.kind) {
+                    self.store(var_name);
+                    self.load(var_name);
+                }
+            }
+            ExprKind::Binary(operator, left, right) if operator == "&&" || operator == "||" => {
+                self.expr(program, left);
+                self.open("if (result i32)");
+                match operator.as_str() {
+                    "&&" => self.expr(program, right),
+                    _ => self.emit("i32.const 1"),
+                }
+                self.otherwise();
+                match operator.as_str() {
+                    "&&" => self.emit("i32.const 0"),
+                    _ => self.expr(program, right),
+                }
+                self.close();
+            }
+            ExprKind::Binary(operator, left, right) => {
+                self.expr(program, left);
+                self.expr(program, right);
+
+                if left.jv_type == "string" {
+                    if operator == "+" {
+                        self.emit("call $jv_concat");
+                        return;
+                    }
+
+                    //Strings are compared by the sign of `jv_str_cmp`
+                    self.emit("call $jv_str_cmp");
+                    self.emit("i32.const 0");
+                }
+
+                let instruction = match operator.as_str() {
+                    "+" => "i32.add",
+                    "-" => "i32.sub",
+                    "*" => "i32.mul",
+                    //`i32.div_s` traps when the smallest int is divided by -1,
+                    //while `i32.rem_s` gives 0 like the interpreter
+                    "/" if expr.may_overflow_division() => "call $jv_div",
+                    "/" => "i32.div_s",
+                    "%" => "i32.rem_s",
+                    "&" => "i32.and",
+                    "|" => "i32.or",
+                    "==" => "i32.eq",
+                    "!=" => "i32.ne",
+                    "<" => "i32.lt_s",
+                    "<=" => "i32.le_s",
+                    ">" => "i32.gt_s",
+                    _ => "i32.ge_s",
+                };
+                self.emit(instruction);
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                self.expr(program, condition);
+                self.open("if (result i32)");
+                self.expr(program, then_expr);
+                self.otherwise();
+                self.expr(program, else_expr);
+                self.close();
+            }
+            ExprKind::Interpolation(parts) => {
+                if parts.is_empty() {
+                    self.emit("global.get $jv_empty");
+                }
+
+                for (index, part) in parts.iter().enumerate() {
+                    self.expr(program, part);
+                    self.stringify(&part.jv_type);
+
+                    if index > 0 {
+                        self.emit("call $jv_concat");
+                    }
+                }
+            }
+        }
+    }
+
+    //Converts the value on the stack to a string,
+    //writing booleans as `true` or `false`
+    fn stringify(&mut self, jv_type: &str) {
+        match jv_type {
+            "int" => self.emit("call $jv_to_string"),
+            "boolean" => self.emit("call $jv_bool_string"),
+            _ => {}
+        }
+    }
+
+    //Evaluates values into temporaries, like a call
+    //does before printing anything
+    fn save_values(&mut self, program: &Program, values: &[Expr]) -> Vec<String> {
+        values
+            .iter()
+            .map(|value| {
+                self.expr(program, value);
+                let temp = self.temp();
+                self.emit(&format!("local.set {}", temp));
+                temp
+            })
+            .collect()
+    }
+
+    //Prints each value according to its type, one after the other
+    fn print(&mut self, program: &Program, args: &[Expr], newline: bool) {
+        let temps = self.save_values(program, args);
+
+        for (arg, temp) in args.iter().zip(&temps) {
+            let function = match arg.jv_type.as_str() {
+                "int" => "$jv_print_int",
+                "boolean" => "$jv_print_bool",
+                _ => "$jv_print_str",
+            };
+
+            self.emit(&format!("local.get {}", temp));
+            self.emit(&format!("call {}", function));
+        }
+
+        if newline {
+            self.emit("call $jv_print_newline");
+        }
+
+        self.free_temps(temps.len());
+    }
+
+    //The format of `printf` is a literal, so it is parsed here, like
+    //the assembly backend does, and each specifier becomes calls that
+    //format its value. The text between specifiers is printed as it is
+    fn printf(&mut self, program: &Program, args: &[Expr]) {
+        let ExprKind::Str(format) = &args[0].kind else {
+            panic!("The format of printf must be a literal");
+        };
+
+        let temps = self.save_values(program, &args[1..]);
+        let mut values = temps.iter();
+        let mut text = String::new();
+        let format = unescape(format);
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+
+            let mut flags = String::new();
+            while let Some(&flag) = chars.peek().filter(|f| "-+ #0".contains(**f)) {
+                flags.push(flag);
+                chars.next();
+            }
+
+            let mut width = 0;
+            while let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                width = width * 10 + digit;
+                chars.next();
+            }
+
+            let mut precision: Option<u32> = None;
+            if chars.next_if_eq(&'.').is_some() {
+                let mut digits = 0;
+                while let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                    digits = digits * 10 + digit;
+                    chars.next();
+                }
+                precision = Some(digits);
+            }
+
+            let conversion = chars.next();
+            if conversion == Some('%') {
+                text.push('%');
+                continue;
+            }
+
+            self.print_text(&mut text);
+            let temp = values.next().unwrap();
+            self.emit(&format!("local.get {}", temp));
+
+            let numeric = conversion != Some('s');
+            if numeric {
+                let sign = match flags.as_str() {
+                    flags if flags.contains('+') => "$jv_plus_str",
+                    flags if flags.contains(' ') => "$jv_space_str",
+                    _ => "$jv_empty",
+                };
+                let precision = precision.map_or(-1, |precision| precision as i64);
+
+                self.emit(&format!("i32.const {}", precision));
+                self.emit(&format!("global.get {}", sign));
+                self.emit("call $jv_format_int");
+            } else if let Some(precision) = precision {
+                self.emit("i32.const 0");
+                self.emit(&format!("i32.const {}", precision));
+                self.emit("call $jv_substr");
+            }
+
+            //Modes of `jv_pad`: spaces before, spaces after or zeros after the sign
+            if width > 0 {
+                let mode = match flags.as_str() {
+                    flags if flags.contains('-') => 1,
+                    flags if flags.contains('0') && numeric && precision.is_none() => 2,
+                    _ => 0,
+                };
+
+                self.emit(&format!("i32.const {}", width));
+                self.emit(&format!("i32.const {}", mode));
+                self.emit("call $jv_pad");
+            }
+
+            self.emit("call $jv_print_str");
+        }
+
+        self.print_text(&mut text);
+        self.free_temps(temps.len());
+    }
+
+    //Prints the text of a format gathered so far, if any
+    fn print_text(&mut self, text: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+
+        let address = self.literal(std::mem::take(text));
+        self.emit(&format!("i32.const {}", address));
+        self.emit("call $jv_print_str");
+    }
+}
+
+impl Default for WatGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//Writes bytes as a string of the text format, where
+//anything but printable ASCII is written as `\hh`
+fn escape(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b'"' | b'\\' => format!("\\{:02x}", b),
+            0x20..=0x7e => (b as char).to_string(),
+            _ => format!("\\{:02x}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use crate::error::{JuvinilError, JuvinilResult, JuvinilWarning};
+
+//Every warning the compiler can report,
+//with the level it has when not configured
+pub const LINTS: [(&str, WarningLevel); 5] = [
+    ("unused-variable", WarningLevel::Warn),
+    ("unread-variable", WarningLevel::Warn),
+    ("unused-parameter", WarningLevel::Warn),
+    ("unused-function", WarningLevel::Warn),
+    ("shadowing", WarningLevel::Allow),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+            Severity::Help => write!(f, "help"),
+        }
+    }
+}
+
+//Something the compiler reports to the user. Errors and warnings
+//can have notes and help messages attached to them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub lint: Option<String>, //Name of the warning that produced it, if any
+    pub children: Vec<Diagnostic>, //Notes and help attached to it
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String) -> Self {
+        Diagnostic {
+            severity,
+            message,
+            lint: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn error(message: String) -> Self {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: String) -> Self {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    pub fn note(message: String) -> Self {
+        Diagnostic::new(Severity::Note, message)
+    }
+
+    pub fn help(message: String) -> Self {
+        Diagnostic::new(Severity::Help, message)
+    }
+
+    //Attach a note or help message to this diagnostic
+    pub fn with_child(mut self, child: Diagnostic) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    //Builds the diagnostic of a warning. Depending on its configured
+    //level, a warning can also be reported as an error
+    pub fn from_warning(warning: &JuvinilWarning, level: WarningLevel) -> Self {
+        let severity = match level {
+            WarningLevel::Deny => Severity::Error,
+            _ => Severity::Warning,
+        };
+
+        let mut diagnostic = Diagnostic::new(severity, warning.to_string());
+        diagnostic.lint = Some(warning.lint().into());
+
+        if let Some(note) = warning.note() {
+            diagnostic = diagnostic.with_child(Diagnostic::note(note));
+        }
+
+        if let Some(help) = warning.help() {
+            diagnostic = diagnostic.with_child(Diagnostic::help(help));
+        }
+
+        diagnostic
+    }
+
+    pub fn from_error(error: &JuvinilError) -> Self {
+        Diagnostic::error(error.to_string())
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+
+        if let Some(lint) = &self.lint {
+            write!(f, " [{}]", lint)?;
+        }
+
+        for child in &self.children {
+            write!(f, "\n  = {}: {}", child.severity, child.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningLevel {
+    Allow, //The warning isn't reported
+    Warn,  //The warning is reported, but doesn't fail the compilation
+    Deny,  //The warning is reported as an error
+}
+
+//Level of each warning, configured by the user
+#[derive(Debug, Clone, Default)]
+pub struct WarningConfig {
+    all: Option<WarningLevel>, //Level applied to every reported warning, like `-W error`
+    levels: HashMap<String, WarningLevel>,
+}
+
+impl WarningConfig {
+    //Builds the configuration from command line flags:
+    //`-W error` turns every warning into an error,
+    //`-A <name>`, `-W <name>` and `-D <name>` allow, warn or deny a warning,
+    //and `warnings` can be used as the name to refer to all of them
+    pub fn from_args(args: &[String]) -> JuvinilResult<Self> {
+        let mut config = WarningConfig::default();
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            let level = match flag.as_str() {
+                "-A" => WarningLevel::Allow,
+                "-W" => WarningLevel::Warn,
+                "-D" => WarningLevel::Deny,
+                _ => return Err(JuvinilError::InvalidArgument(flag.clone())),
+            };
+
+            let lint = args
+                .next()
+                .ok_or(JuvinilError::InvalidArgument(flag.clone()))?;
+
+            match (level, lint.as_str()) {
+                (WarningLevel::Warn, "error") => config.set_all(WarningLevel::Deny),
+                (_, "warnings") => config.set_all(level),
+                _ => config.set(lint, level)?,
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn set_all(&mut self, level: WarningLevel) {
+        self.all = Some(level);
+    }
+
+    pub fn set(&mut self, lint: &str, level: WarningLevel) -> JuvinilResult<()> {
+        if !LINTS.iter().any(|(name, _)| *name == lint) {
+            return Err(JuvinilError::UnknownWarning(lint.into()));
+        }
+
+        self.levels.insert(lint.into(), level);
+        Ok(())
+    }
+
+    //Level of a warning. A warning configured by name takes
+    //precedence over the level of all warnings, which only
+    //applies to warnings that are reported by default
+    pub fn level(&self, lint: &str) -> WarningLevel {
+        if let Some(level) = self.levels.get(lint) {
+            return *level;
+        }
+
+        let default_level = LINTS
+            .iter()
+            .find(|(name, _)| *name == lint)
+            .map_or(WarningLevel::Warn, |(_, level)| *level);
+
+        match (default_level, self.all) {
+            (WarningLevel::Warn, Some(level)) => level,
+            _ => default_level,
+        }
+    }
+}
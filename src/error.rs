@@ -19,23 +19,198 @@ pub enum JuvinilError {
     #[error("Unassigned Variable - Variable `{0}` was not assigned before being used - line {1}")]
     UnassignedVariable(String, usize),
 
+    #[error(
+        "Constant Assignment - Constant `{0}` can't be assigned after its declaration - line {1}"
+    )]
+    ConstantAssignment(String, usize),
+
     #[error("Duplicate Function - Function `{0}` was already declared - line {1}")]
     DuplicateFunction(String, usize),
 
     #[error("Undeclared Function - Function `{0}` was not declared - line {1}")]
     UndeclaredFunction(String, usize),
 
+    #[error("No Matching Overload - No overload of `{0}` takes ({1}), the candidates are: {2} - line {3}")]
+    NoMatchingOverload(String, String, String, usize),
+
+    #[error("Ambiguous Call - The call to `{0}` with ({1}) matches more than one overload: {2} - line {3}")]
+    AmbiguousCall(String, String, String, usize),
+
     #[error("Invalid Parameter Count - Function `{0}` expects {1} parameters, but {2} were given - line {3}")]
     InvalidParamCount(String, usize, usize, usize),
 
+    #[error("Invalid Parameter Count - Function `{0}` expects from {1} to {2} parameters, but {3} were given - line {4}")]
+    InvalidParamRange(String, usize, usize, usize, usize),
+
+    #[error("Unknown Argument - Function `{0}` has no parameter named `{1}` - line {2}")]
+    UnknownArgument(String, String, usize),
+
+    #[error("Duplicate Argument - Parameter `{1}` of function `{0}` was given more than once - line {2}")]
+    DuplicateArgument(String, String, usize),
+
+    #[error("Missing Argument - Parameter `{1}` of function `{0}` was not given and has no default value - line {2}")]
+    MissingArgument(String, String, usize),
+
+    #[error(
+        "Positional Argument - Positional arguments can't come after named arguments - line {0}"
+    )]
+    PositionalAfterNamed(usize),
+
+    #[error("Missing Default Value - Parameter `{0}` comes after a parameter with a default value, so it needs one too - line {1}")]
+    MissingDefaultValue(String, usize),
+
+    #[error("Invalid Default Value - The default value of parameter `{0}` must be a constant expression - line {1}")]
+    InvalidDefaultValue(String, usize),
+
+    #[error("Invalid Format - Format string \"{0}\" {1} - line {2}")]
+    InvalidFormat(String, String, usize),
+
+    #[error("Invalid Format Argument - Specifier `{0}` expects a value of type {1}, but {2} was given - line {3}")]
+    InvalidFormatArgument(String, String, String, usize),
+
     #[error("Invalid Value Type - Variable `{0}` expects a value of type `{1}`, but `{2}` was given - line {3}")]
     InvalidValueType(String, String, String, usize),
 
+    #[error("Invalid Parameter Type - Function `{0}` expects a parameter of type `{1}`, but `{2}` was given - line {3}")]
+    InvalidParamType(String, String, String, usize),
+
+    #[error(
+        "Invalid Return Type - Function `{0}` must return `{1}`, but `{2}` was returned - line {3}"
+    )]
+    InvalidReturnType(String, String, String, usize),
+
+    #[error("Missing Return - Function `{0}` can reach the end of its body without returning `{1}` - line {2}")]
+    MissingReturn(String, String, usize),
+
+    #[error("Invalid Operands - Operator `{0}` can't be applied to `{1}` and `{2}` - line {3}")]
+    InvalidOperands(String, String, String, usize),
+
+    #[error("Invalid Operand - Operator `{0}` can't be applied to `{1}` - line {2}")]
+    InvalidOperand(String, String, usize),
+
+    #[error("Invalid Increment - Operator `{0}` can only be applied to a variable - line {1}")]
+    InvalidIncrement(String, usize),
+
+    #[error(
+        "Invalid Condition - Conditions must be of type `boolean`, but `{0}` was given - line {1}"
+    )]
+    InvalidCondition(String, usize),
+
+    #[error("Invalid Conditional - Both branches must have the same type, but `{0}` and `{1}` were given - line {2}")]
+    InvalidConditionalTypes(String, String, usize),
+
+    #[error(
+        "Invalid Interpolation - A value of type {0} can't be embedded in a string - line {1}"
+    )]
+    InvalidInterpolation(String, usize),
+
     #[error("Unclosed string - line {0}")]
     UnclosedString(usize),
 
+    #[error(
+        "Unclosed interpolation - Expression in string \"{0}\" has no closing bracket - line {1}"
+    )]
+    UnclosedInterpolation(String, usize),
+
+    #[error("Denied Warnings - Compilation failed because of {0} warning(s) treated as errors")]
+    DeniedWarnings(usize),
+
+    #[error("Unknown Warning - There is no warning named `{0}`")]
+    UnknownWarning(String),
+
+    #[error("Invalid Argument - Couldn't understand the argument `{0}`")]
+    InvalidArgument(String),
+
+    #[error("Missing Input - No file was given, use `-` to read the standard input")]
+    MissingInput,
+
+    #[error("Compiler Not Found - Couldn't find a compiler ({0}), install one or set it with `--cxx` or `CXX` for C++, and `--cc` or `CC` for C")]
+    CompilerNotFound(String),
+
+    #[error("Build Failed - `{0}` couldn't build the generated code:\n{1}")]
+    ToolError(String, String),
+
+    #[error("Unsupported Platform - The {0} target only works on x86-64 Linux")]
+    UnsupportedPlatform(String),
+
+    #[error("Division By Zero - The program divided by zero - line {0}")]
+    DivisionByZero(usize),
+
+    #[error("Stack Overflow - Too many nested calls, the last one to `{0}` - line {1}")]
+    StackOverflow(String, usize),
+
+    #[error("Timeout - The program was stopped after running for {0} second(s)")]
+    ProgramTimeout(u64),
+
+    #[error("Program Killed - The program was terminated by signal {0}")]
+    ProgramSignal(i32),
+
+    #[error("Invalid Bytecode - The file is corrupted or isn't a Juvinil bytecode file: {0}")]
+    InvalidBytecode(String),
+
+    #[error("Incompatible Bytecode - The file has version {0} of the bytecode format, but only version {1} can be run, build it again with `--target bytecode`")]
+    IncompatibleBytecode(u16, u16),
+
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
 
+//Implementation of all the different warnings that can
+//occur during all steps of the compiler. Unlike errors,
+//warnings don't stop the compilation
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum JuvinilWarning {
+    #[error("Unused Variable - Variable `{0}` is never used - line {1}")]
+    UnusedVariable(String, usize),
+
+    #[error("Unread Variable - Variable `{0}` is assigned but never read - line {1}")]
+    UnreadVariable(String, usize),
+
+    #[error("Unused Parameter - Parameter `{0}` of function `{1}` is never read - line {2}")]
+    UnusedParameter(String, String, usize),
+
+    #[error("Unused Function - Function `{0}` is never called - line {1}")]
+    UnusedFunction(String, usize),
+
+    #[error("Shadowed Variable - Variable `{0}` shadows a variable of an outer scope - line {1}")]
+    ShadowedVariable(String, usize, usize),
+}
+
+impl JuvinilWarning {
+    //Name used to configure the warning
+    pub fn lint(&self) -> &'static str {
+        match self {
+            JuvinilWarning::UnusedVariable(..) => "unused-variable",
+            JuvinilWarning::UnreadVariable(..) => "unread-variable",
+            JuvinilWarning::UnusedParameter(..) => "unused-parameter",
+            JuvinilWarning::UnusedFunction(..) => "unused-function",
+            JuvinilWarning::ShadowedVariable(..) => "shadowing",
+        }
+    }
+
+    //Note pointing at other code related to the warning
+    pub fn note(&self) -> Option<String> {
+        match self {
+            JuvinilWarning::ShadowedVariable(name, _, shadowed_line) => Some(format!(
+                "the shadowed `{}` is declared here - line {}",
+                name, shadowed_line
+            )),
+            _ => None,
+        }
+    }
+
+    //Help message on how to fix or silence the warning
+    pub fn help(&self) -> Option<String> {
+        match self {
+            JuvinilWarning::UnusedVariable(name, _)
+            | JuvinilWarning::UnreadVariable(name, _)
+            | JuvinilWarning::UnusedParameter(name, _, _)
+            | JuvinilWarning::UnusedFunction(name, _) => {
+                Some(format!("if this is intentional, rename it to `_{}`", name))
+            }
+            JuvinilWarning::ShadowedVariable(..) => None,
+        }
+    }
+}
+
 pub type JuvinilResult<T, E = JuvinilError> = anyhow::Result<T, E>;
@@ -0,0 +1,451 @@
+pub mod runtime;
+pub mod value;
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use crate::{
+    error::{JuvinilError, JuvinilResult},
+    syntax_analysis::{
+        ast::{Expr, ExprKind, Program, Stmt, StmtKind},
+        builtins,
+    },
+};
+
+use runtime::Io;
+use value::Value;
+
+//Calls that can be nested before the program is stopped,
+//so infinite recursion is reported instead of crashing
+pub const MAX_CALL_DEPTH: usize = 15_000;
+
+//Stack of the thread the interpreter runs on. Every call of the
+//program nests several calls of the interpreter, taking up to tens
+//of kilobytes in debug builds, so the default stack isn't enough
+//for `MAX_CALL_DEPTH` calls
+pub const STACK_SIZE: usize = 1024 * 1024 * 1024;
+
+//Runs a function in a thread with a stack of `STACK_SIZE`
+pub fn with_stack<T: Send>(run: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, run)
+            .expect("Couldn't start the interpreter thread");
+
+        match thread.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    })
+}
+
+//Runs a program with the given input, returning its
+//exit status along with everything it wrote
+pub fn run_captured(program: &Program, input: &str) -> JuvinilResult<(i32, String)> {
+    with_stack(|| {
+        let mut input = input.as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+
+        let status = Interpreter::new(program, &mut input, &mut output).run()?;
+
+        Ok((status, String::from_utf8_lossy(&output).into_owned()))
+    })
+}
+
+//How the statements of a block ended
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+//Reasons to stop evaluating an expression early: the
+//program called `exit`, or it failed while running
+enum Halt {
+    Exit(i32),
+    Error(JuvinilError),
+}
+
+impl From<JuvinilError> for Halt {
+    fn from(err: JuvinilError) -> Self {
+        Halt::Error(err)
+    }
+}
+
+type Eval<T> = Result<T, Halt>;
+
+//Variables of a function call. Each block inside the
+//function adds a scope, like `syntax_analysis::scope::Scope`
+struct Frame {
+    scopes: Vec<HashMap<String, Value>>,
+}
+
+//Executes a parsed program by walking its tree. Globals live as
+//long as the program, while each call gets its own frame
+pub struct Interpreter<'a> {
+    program: &'a Program,
+    io: Io<'a>,
+    globals: HashMap<String, Value>,
+    frames: Vec<Frame>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(
+        program: &'a Program,
+        input: &'a mut dyn BufRead,
+        output: &'a mut dyn Write,
+    ) -> Self {
+        Interpreter {
+            program,
+            io: Io::new(input, output),
+            globals: HashMap::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    //Runs the globals and then the main program,
+    //returning the exit status of the program
+    pub fn run(&mut self) -> JuvinilResult<i32> {
+        let result = self.run_main();
+        self.io.flush()?;
+
+        match result {
+            Ok(status) | Err(Halt::Exit(status)) => Ok(status),
+            Err(Halt::Error(err)) => Err(err),
+        }
+    }
+
+    fn run_main(&mut self) -> Eval<i32> {
+        //Global initializers run at file scope, without a frame
+        for global in &self.program.globals {
+            if let StmtKind::Decl(_, var_type, declarators) = &global.kind {
+                for (var_name, initializer) in declarators {
+                    let value = self.eval_or_default(initializer, var_type)?;
+                    self.globals.insert(var_name.clone(), value);
+                }
+            }
+        }
+
+        self.frames.push(Frame {
+            scopes: vec![HashMap::new()],
+        });
+
+        let status = match self.exec_block(&self.program.body)? {
+            Flow::Return(value) => value.as_int(),
+            _ => 0,
+        };
+
+        self.frames.pop();
+
+        Ok(status)
+    }
+
+    //Runs statements in a new scope, which is dropped when they end
+    fn exec_block(&mut self, stmts: &'a [Stmt]) -> Eval<Flow> {
+        self.frame().scopes.push(HashMap::new());
+        let flow = self.exec_stmts(stmts);
+        self.frame().scopes.pop();
+
+        flow
+    }
+
+    fn exec_stmts(&mut self, stmts: &'a [Stmt]) -> Eval<Flow> {
+        for stmt in stmts {
+            let flow = self.exec_stmt(stmt)?;
+            if !matches!(flow, Flow::Normal) {
+                return Ok(flow);
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn exec_stmt(&mut self, stmt: &'a Stmt) -> Eval<Flow> {
+        match &stmt.kind {
+            StmtKind::Decl(_, var_type, declarators) => {
+                for (var_name, initializer) in declarators {
+                    let value = self.eval_or_default(initializer, var_type)?;
+                    self.declare(var_name, value);
+                }
+            }
+            StmtKind::Asgn(..) => self.exec_asgn(stmt)?,
+            StmtKind::Call(call) => {
+                self.eval(call)?;
+            }
+            StmtKind::Block(stmts) => return self.exec_block(stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                if self.eval(condition)?.as_bool() {
+                    return self.exec_block(then_block);
+                }
+
+                if let Some(else_block) = else_block {
+                    return self.exec_block(else_block);
+                }
+            }
+            StmtKind::While(condition, body) => {
+                while self.eval(condition)?.as_bool() {
+                    if let Some(flow) = self.exec_loop_body(body)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            StmtKind::DoWhile(body, condition) => loop {
+                if let Some(flow) = self.exec_loop_body(body)? {
+                    return Ok(flow);
+                }
+
+                if !self.eval(condition)?.as_bool() {
+                    break;
+                }
+            },
+            StmtKind::For(init, condition, body) => {
+                self.exec_asgn(init)?;
+
+                while self.eval(condition)?.as_bool() {
+                    if let Some(flow) = self.exec_loop_body(body)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            StmtKind::Break => return Ok(Flow::Break),
+            StmtKind::Continue => return Ok(Flow::Continue),
+            StmtKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Void,
+                };
+
+                return Ok(Flow::Return(value));
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    //Runs one iteration of a loop. Returns the flow that
+    //ends the loop's statement, if the body returned
+    fn exec_loop_body(&mut self, body: &'a [Stmt]) -> Eval<Option<Flow>> {
+        match self.exec_block(body)? {
+            Flow::Break => Ok(Some(Flow::Normal)),
+            Flow::Return(value) => Ok(Some(Flow::Return(value))),
+            Flow::Normal | Flow::Continue => Ok(None),
+        }
+    }
+
+    fn exec_asgn(&mut self, stmt: &'a Stmt) -> Eval<()> {
+        let StmtKind::Asgn(var_name, operator, value) = &stmt.kind else {
+            return Ok(());
+        };
+
+        let value = self.eval(value)?;
+        let value = match (operator.as_str(), self.load(var_name), value) {
+            ("+=", Value::Int(current), Value::Int(value)) => {
+                Value::Int(current.wrapping_add(value))
+            }
+            ("-=", Value::Int(current), Value::Int(value)) => {
+                Value::Int(current.wrapping_sub(value))
+            }
+            ("+=", Value::Str(current), Value::Str(value)) => Value::Str([current, value].concat()),
+            (_, _, value) => value,
+        };
+
+        self.store(var_name, value);
+        Ok(())
+    }
+
+    fn eval_or_default(&mut self, initializer: &'a Option<Expr>, var_type: &str) -> Eval<Value> {
+        match initializer {
+            Some(initializer) => self.eval(initializer),
+            None => Ok(Value::default_of(var_type)),
+        }
+    }
+
+    fn eval(&mut self, expr: &'a Expr) -> Eval<Value> {
+        let value = match &expr.kind {
+            ExprKind::Int(value) => Value::Int(*value),
+            ExprKind::Bool(value) => Value::Bool(*value),
+            ExprKind::Str(value) => Value::Str(runtime::unescape(value).into_bytes()),
+            ExprKind::Var(var_name) => self.load(var_name),
+            ExprKind::Call(func_name, args) => {
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+
+                self.call(func_name, args, values, expr.line)?
+            }
+            ExprKind::Unary(operator, operand) => self.eval_unary(operator, operand)?,
+            ExprKind::Binary(operator, left, right) => {
+                self.eval_binary(operator, left, right, expr.line)?
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                match self.eval(condition)?.as_bool() {
+                    true => self.eval(then_expr)?,
+                    false => self.eval(else_expr)?,
+                }
+            }
+            ExprKind::Interpolation(parts) => {
+                let mut text = Vec::new();
+                for part in parts {
+                    text.extend(self.eval(part)?.to_bytes());
+                }
+
+                Value::Str(text)
+            }
+        };
+
+        Ok(value)
+    }
+
+    //`++` and `--` change the variable they're applied to
+    fn eval_unary(&mut self, operator: &str, operand: &'a Expr) -> Eval<Value> {
+        let value = self.eval(operand)?;
+
+        let result = match operator {
+            "!" => Value::Bool(!value.as_bool()),
+            "-" => Value::Int(value.as_int().wrapping_neg()),
+            "++" => Value::Int(value.as_int().wrapping_add(1)),
+            _ => Value::Int(value.as_int().wrapping_sub(1)),
+        };
+
+        if let ("++" | "--", ExprKind::Var(var_name)) = (operator, &operand.kind) {
+            self.store(var_name, result.clone());
+        }
+
+        Ok(result)
+    }
+
+    fn eval_binary(
+        &mut self,
+        operator: &str,
+        left: &'a Expr,
+        right: &'a Expr,
+        line: usize,
+    ) -> Eval<Value> {
+        //`&&` and `||` only evaluate the right side when needed
+        let left = self.eval(left)?;
+        match (operator, &left) {
+            ("&&", Value::Bool(false)) => return Ok(Value::Bool(false)),
+            ("||", Value::Bool(true)) => return Ok(Value::Bool(true)),
+            ("&&" | "||", _) => return self.eval(right),
+            _ => {}
+        }
+
+        let right = self.eval(right)?;
+        let value = match (operator, left, right) {
+            ("==", left, right) => Value::Bool(left == right),
+            ("!=", left, right) => Value::Bool(left != right),
+            ("+", Value::Str(left), Value::Str(right)) => Value::Str([left, right].concat()),
+            ("<", Value::Str(left), Value::Str(right)) => Value::Bool(left < right),
+            ("<=", Value::Str(left), Value::Str(right)) => Value::Bool(left <= right),
+            (">", Value::Str(left), Value::Str(right)) => Value::Bool(left > right),
+            (">=", Value::Str(left), Value::Str(right)) => Value::Bool(left >= right),
+            (operator, left, right) => {
+                let (left, right) = (left.as_int(), right.as_int());
+
+                if (operator == "/" || operator == "%") && right == 0 {
+                    return Err(JuvinilError::DivisionByZero(line).into());
+                }
+
+                match operator {
+                    "+" => Value::Int(left.wrapping_add(right)),
+                    "-" => Value::Int(left.wrapping_sub(right)),
+                    "*" => Value::Int(left.wrapping_mul(right)),
+                    "/" => Value::Int(left.wrapping_div(right)),
+                    "%" => Value::Int(left.wrapping_rem(right)),
+                    "&" => Value::Int(left & right),
+                    "|" => Value::Int(left | right),
+                    "<" => Value::Bool(left < right),
+                    "<=" => Value::Bool(left <= right),
+                    ">" => Value::Bool(left > right),
+                    _ => Value::Bool(left >= right),
+                }
+            }
+        };
+
+        Ok(value)
+    }
+
+    //Calls a builtin or a declared function. Overloads are told
+    //apart by the types of the arguments, like the parser does
+    fn call(
+        &mut self,
+        func_name: &str,
+        args: &[Expr],
+        values: Vec<Value>,
+        line: usize,
+    ) -> Eval<Value> {
+        if func_name == "exit" {
+            return Err(Halt::Exit(values[0].as_int()));
+        }
+
+        if builtins::is_builtin(func_name) {
+            return Ok(runtime::call_builtin(&mut self.io, func_name, &values)?);
+        }
+
+        let program = self.program;
+        let function = &program.functions[program.function_index(func_name, args).unwrap()];
+
+        if self.frames.len() > MAX_CALL_DEPTH {
+            return Err(JuvinilError::StackOverflow(func_name.into(), line).into());
+        }
+
+        //Parameters are the first scope of the new frame
+        let params: HashMap<String, Value> = function
+            .params
+            .iter()
+            .map(|(_, param_name)| param_name.clone())
+            .zip(values)
+            .collect();
+
+        self.frames.push(Frame {
+            scopes: vec![params],
+        });
+        let flow = self.exec_block(&function.body);
+        self.frames.pop();
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::Void),
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn declare(&mut self, var_name: &str, value: Value) {
+        let scope = self.frame().scopes.last_mut().unwrap();
+        scope.insert(var_name.into(), value);
+    }
+
+    //Finds a variable in the innermost scope that has it,
+    //falling back to the globals
+    fn lookup(&mut self, var_name: &str) -> &mut Value {
+        let frame = self.frames.last_mut();
+        let local = frame.and_then(|frame| {
+            frame
+                .scopes
+                .iter_mut()
+                .rev()
+                .find_map(|scope| scope.get_mut(var_name))
+        });
+
+        match local {
+            Some(value) => value,
+            None => self.globals.get_mut(var_name).unwrap(),
+        }
+    }
+
+    fn load(&mut self, var_name: &str) -> Value {
+        self.lookup(var_name).clone()
+    }
+
+    fn store(&mut self, var_name: &str, value: Value) {
+        *self.lookup(var_name) = value;
+    }
+}
@@ -0,0 +1,320 @@
+use std::io::{BufRead, Write};
+
+use crate::error::JuvinilResult;
+
+use super::value::Value;
+
+//Standard input and output of a running program, along with the
+//state of the last read. Reads behave like the C++ runtime: words
+//are separated by whitespace, and a read that fails returns a
+//default value and makes `input_ok` false
+pub struct Io<'a> {
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+    last_read_ok: bool,
+    reached_eof: bool,
+}
+
+impl<'a> Io<'a> {
+    pub fn new(input: &'a mut dyn BufRead, output: &'a mut dyn Write) -> Self {
+        Io {
+            input,
+            output,
+            last_read_ok: true,
+            reached_eof: false,
+        }
+    }
+
+    pub fn write(&mut self, text: &[u8]) -> JuvinilResult<()> {
+        self.output.write_all(text)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> JuvinilResult<()> {
+        self.output.flush()?;
+        Ok(())
+    }
+
+    //Reads the next word, skipping the whitespace before it.
+    //The output is flushed first, so prompts show up before reading
+    fn read_word(&mut self) -> JuvinilResult<Option<Vec<u8>>> {
+        self.flush()?;
+
+        let mut word: Vec<u8> = Vec::new();
+        loop {
+            let buffer = self.input.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+
+            let skipped = match word.is_empty() {
+                true => buffer
+                    .iter()
+                    .take_while(|b| b.is_ascii_whitespace())
+                    .count(),
+                false => 0,
+            };
+            let taken = buffer[skipped..]
+                .iter()
+                .take_while(|b| !b.is_ascii_whitespace())
+                .count();
+            word.extend_from_slice(&buffer[skipped..skipped + taken]);
+
+            let ended = skipped + taken < buffer.len();
+            self.input.consume(skipped + taken);
+
+            if ended && !word.is_empty() {
+                break;
+            }
+        }
+
+        if word.is_empty() {
+            self.last_read_ok = false;
+            self.reached_eof = true;
+            return Ok(None);
+        }
+
+        Ok(Some(word))
+    }
+
+    pub fn read_int(&mut self) -> JuvinilResult<i32> {
+        let Some(word) = self.read_word()? else {
+            return Ok(0);
+        };
+
+        self.last_read_ok = is_int(&word);
+        Ok(parse_int(&word))
+    }
+
+    pub fn read_bool(&mut self) -> JuvinilResult<bool> {
+        let Some(word) = self.read_word()? else {
+            return Ok(false);
+        };
+
+        self.last_read_ok = word == b"true" || word == b"false";
+        Ok(word == b"true")
+    }
+
+    //Reads the rest of the current line, without the line break
+    pub fn read_line(&mut self) -> JuvinilResult<Vec<u8>> {
+        self.flush()?;
+
+        let mut line: Vec<u8> = Vec::new();
+        let read = self.input.read_until(b'\n', &mut line)?;
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+
+        self.last_read_ok = read > 0;
+        self.reached_eof = self.reached_eof || !self.last_read_ok;
+        Ok(line)
+    }
+
+    pub fn input_ok(&self) -> bool {
+        self.last_read_ok
+    }
+
+    pub fn input_eof(&self) -> bool {
+        self.reached_eof
+    }
+}
+
+//Calls a builtin function with the values of its arguments.
+//`exit` isn't handled here, since it stops the program. Arguments
+//read from a bytecode file weren't checked by the parser, so their
+//types are checked instead of trusted
+pub fn call_builtin(io: &mut Io, func_name: &str, args: &[Value]) -> JuvinilResult<Value> {
+    let value = match func_name {
+        "print" | "println" => {
+            let mut text: Vec<u8> = args.iter().flat_map(Value::to_bytes).collect();
+            if func_name == "println" {
+                text.push(b'\n');
+            }
+
+            io.write(&text)?;
+            Value::Void
+        }
+        "printf" => {
+            io.write(&format(args[0].checked_str()?, &args[1..])?)?;
+            Value::Void
+        }
+        "read_int" => Value::Int(io.read_int()?),
+        "read_bool" => Value::Bool(io.read_bool()?),
+        "read_line" => Value::Str(io.read_line()?),
+        "input_ok" => Value::Bool(io.input_ok()),
+        "input_eof" => Value::Bool(io.input_eof()),
+        "len" => Value::Int(args[0].checked_str()?.len() as i32),
+        "substr" => Value::Str(substr(
+            args[0].checked_str()?,
+            args[1].checked_int()?,
+            args[2].checked_int()?,
+        )),
+        "contains" => Value::Bool(contains(args[0].checked_str()?, args[1].checked_str()?)),
+        "to_string" => Value::Str(args[0].checked_int()?.to_string().into_bytes()),
+        "parse_int" => Value::Int(parse_int(args[0].checked_str()?)),
+        "is_int" => Value::Bool(is_int(args[0].checked_str()?)),
+        _ => panic!("Unknown builtin `{}`", func_name),
+    };
+
+    Ok(value)
+}
+
+//Replaces the escape sequences of a string literal,
+//like `\n`, by the characters they stand for
+pub fn unescape(literal: &str) -> String {
+    let mut result = String::new();
+    let mut chars = literal.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            Some('a') => result.push('\x07'),
+            Some('b') => result.push('\x08'),
+            Some('f') => result.push('\x0c'),
+            Some('v') => result.push('\x0b'),
+            Some(escaped) => result.push(escaped),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+//A whole `int`, with an optional sign, that doesn't overflow
+pub fn is_int(text: &[u8]) -> bool {
+    let digits = text
+        .strip_prefix(b"-")
+        .or(text.strip_prefix(b"+"))
+        .unwrap_or(text);
+    !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) && parse(text).is_some()
+}
+
+pub fn parse_int(text: &[u8]) -> i32 {
+    match is_int(text) {
+        true => parse(text).unwrap(),
+        false => 0,
+    }
+}
+
+fn parse(text: &[u8]) -> Option<i32> {
+    std::str::from_utf8(text).ok()?.parse().ok()
+}
+
+//Up to `length` bytes starting at `start`, where a start out of the string
+//is moved to its closest end and a negative length takes nothing. A cut
+//inside a multi-byte character keeps its bytes, like the native backends
+pub fn substr(text: &[u8], start: i32, length: i32) -> Vec<u8> {
+    let start = start.clamp(0, text.len() as i32) as usize;
+    let end = start + (length.max(0) as usize).min(text.len() - start);
+
+    text[start..end].to_vec()
+}
+
+pub fn contains(text: &[u8], part: &[u8]) -> bool {
+    part.is_empty() || text.windows(part.len()).any(|window| window == part)
+}
+
+//Formats values like `printf`. The format was already checked by the
+//parser, so each specifier has a value of the right type, unless
+//the values come from a damaged bytecode file. Widths and precisions
+//count bytes, like the `printf` of C
+pub fn format(format: &[u8], values: &[Value]) -> JuvinilResult<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut values = values.iter();
+    let mut bytes = format.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            result.push(byte);
+            continue;
+        }
+
+        let mut flags = String::new();
+        while let Some(flag) = bytes.next_if(|f| b"-+ #0".contains(f)) {
+            flags.push(flag as char);
+        }
+
+        let mut width = 0;
+        while let Some(digit) = bytes.next_if(u8::is_ascii_digit) {
+            width = width * 10 + (digit - b'0') as usize;
+        }
+
+        let mut precision: Option<usize> = None;
+        if bytes.next_if_eq(&b'.').is_some() {
+            let mut digits = 0;
+            while let Some(digit) = bytes.next_if(u8::is_ascii_digit) {
+                digits = digits * 10 + (digit - b'0') as usize;
+            }
+            precision = Some(digits);
+        }
+
+        let (text, numeric) = match bytes.next() {
+            Some(b'%') => (b"%".to_vec(), false),
+            Some(b'd' | b'i') => {
+                let value = values.next().map_or(Ok(0), Value::checked_int)?;
+                (format_int(value, &flags, precision).into_bytes(), true)
+            }
+            Some(b's') => {
+                let mut text = values.next().map(Value::to_bytes).unwrap_or_default();
+                if let Some(precision) = precision {
+                    text.truncate(precision);
+                }
+                (text, false)
+            }
+            _ => continue,
+        };
+
+        result.extend(pad(text, width, &flags, numeric && precision.is_none()));
+    }
+
+    Ok(result)
+}
+
+//Formats an integer with its sign, and at least `precision` digits
+fn format_int(value: i32, flags: &str, precision: Option<usize>) -> String {
+    let mut digits = (value as i64).abs().to_string();
+    if let Some(precision) = precision {
+        digits = match (precision, value) {
+            (0, 0) => String::new(),
+            _ => format!("{:0>width$}", digits, width = precision),
+        };
+    }
+
+    let sign = match value {
+        value if value < 0 => "-",
+        _ if flags.contains('+') => "+",
+        _ if flags.contains(' ') => " ",
+        _ => "",
+    };
+
+    format!("{}{}", sign, digits)
+}
+
+//Pads a formatted value up to `width` bytes. Numbers can
+//be padded with zeros, which go after their sign
+fn pad(text: Vec<u8>, width: usize, flags: &str, zero_allowed: bool) -> Vec<u8> {
+    let missing = width.saturating_sub(text.len());
+    if missing == 0 {
+        return text;
+    }
+
+    if flags.contains('-') {
+        return [text, vec![b' '; missing]].concat();
+    }
+
+    if flags.contains('0') && zero_allowed {
+        let sign_len = text.first().is_some_and(|c| b"-+ ".contains(c)) as usize;
+        let (sign, digits) = text.split_at(sign_len);
+        return [sign, &vec![b'0'; missing], digits].concat();
+    }
+
+    [vec![b' '; missing], text].concat()
+}
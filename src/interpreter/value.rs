@@ -0,0 +1,104 @@
+use std::fmt;
+
+use crate::error::{JuvinilError, JuvinilResult};
+
+//A value computed while running a program
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Bool(bool),
+    Str(Vec<u8>), //Bytes, like the strings of the native backends, which may not be UTF-8
+    Void,         //Result of calling a `void` function
+}
+
+impl Value {
+    //Value of a variable declared without an initializer
+    pub fn default_of(jv_type: &str) -> Self {
+        match jv_type {
+            "int" => Value::Int(0),
+            "boolean" => Value::Bool(false),
+            "string" => Value::Str(Vec::new()),
+            _ => Value::Void,
+        }
+    }
+
+    //The parser checks every type, so a
+    //mismatch here is a bug in the interpreter
+    pub fn as_int(&self) -> i32 {
+        match self {
+            Value::Int(value) => *value,
+            Value::Bool(value) => *value as i32,
+            _ => panic!("Expected an int, found {:?}", self),
+        }
+    }
+
+    pub fn as_bool(&self) -> bool {
+        match self {
+            Value::Bool(value) => *value,
+            _ => panic!("Expected a boolean, found {:?}", self),
+        }
+    }
+
+    pub fn as_str(&self) -> &[u8] {
+        match self {
+            Value::Str(value) => value,
+            _ => panic!("Expected a string, found {:?}", self),
+        }
+    }
+
+    //Like `as_int`, `as_bool` and `as_str`, for values the parser didn't
+    //check. Only a damaged bytecode file can give them the wrong type
+    pub fn checked_int(&self) -> JuvinilResult<i32> {
+        match self {
+            Value::Int(_) | Value::Bool(_) => Ok(self.as_int()),
+            _ => Err(self.mismatch("an int")),
+        }
+    }
+
+    pub fn checked_bool(&self) -> JuvinilResult<bool> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(self.mismatch("a boolean")),
+        }
+    }
+
+    pub fn checked_str(&self) -> JuvinilResult<&[u8]> {
+        match self {
+            Value::Str(value) => Ok(value),
+            _ => Err(self.mismatch("a string")),
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> JuvinilError {
+        let found = match self {
+            Value::Int(value) => format!("the int {}", value),
+            Value::Bool(value) => format!("the boolean {}", value),
+            Value::Str(value) => format!("the string {:?}", String::from_utf8_lossy(value)),
+            Value::Void => "no value".into(),
+        };
+
+        JuvinilError::InvalidBytecode(format!("expected {}, found {}", expected, found))
+    }
+
+    //Bytes of the value, like `print` writes them
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Value::Int(value) => value.to_string().into_bytes(),
+            Value::Bool(value) => value.to_string().into_bytes(),
+            Value::Str(value) => value.clone(),
+            Value::Void => Vec::new(),
+        }
+    }
+}
+
+//Strings are shown as text, like `Str("abc")`
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "Int({})", value),
+            Value::Bool(value) => write!(f, "Bool({})", value),
+            Value::Str(value) => write!(f, "Str({:?})", String::from_utf8_lossy(value)),
+            Value::Void => write!(f, "Void"),
+        }
+    }
+}
@@ -1,12 +1,23 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
 
 use crate::{
     error::{JuvinilError, JuvinilResult},
-    lexical_analysis::token::Token,
+    lexical_analysis::token::{Token, TokenType},
 };
 
 use super::{regex_token, token};
 
+//The regexes of `regex_token::REGEX_TOKEN_MAP`, compiled only once
+//since compiling them for every token is very slow on large files
+static COMPILED_REGEX_TOKENS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    regex_token::REGEX_TOKEN_MAP
+        .iter()
+        .map(|rt| Regex::new(rt.regex_template).unwrap())
+        .collect()
+});
+
 //Static function to tokenize the contents of a file
 pub fn tokenize(content: String) -> JuvinilResult<Vec<Token>> {
     let mut tokens: Vec<Token> = Vec::new();
@@ -27,9 +38,13 @@ pub fn tokenize(content: String) -> JuvinilResult<Vec<Token>> {
         let mut token_line: Vec<Token> = Vec::new();
 
         //Process each individual token
-        //and put them inside the `token_line` vector
+        //and put them inside the `token_line` vector.
+        //Interpolated strings become several tokens
         for str_token in pre_processed_line {
-            token_line.push(process_token(str_token.as_str(), line_number + 1)?);
+            match interpolation_tokens(str_token.as_str(), line_number)? {
+                Some(tokens) => token_line.extend(tokens),
+                None => token_line.push(process_token(str_token.as_str(), line_number + 1)?),
+            }
         }
 
         tracing::info!("{} | {:?}", line_number + 1, token_line);
@@ -48,65 +63,139 @@ pub fn tokenize(content: String) -> JuvinilResult<Vec<Token>> {
 //Verifies if there are any unclosed strings,
 //build strings with spaces in a single String instance,
 //and separates special symbols, putting spaces between them.
-//For example, `if (teste < 2)` becomes `if ( teste < 2 )`
+//For example, `if (teste<2)` becomes `if ( teste < 2 )`
 fn pre_process_line(line_content: &str, line_number: usize) -> JuvinilResult<Vec<String>> {
-    if line_content.chars().filter(|c| c == &'\"').count() % 2 != 0 {
-        return Err(JuvinilError::UnclosedString(line_number + 1));
-    }
+    //Every operator, comparator and symbol splits words,
+    //longest ones first so `+=` isn't read as `+` and `=`
+    let mut special_symbols: Vec<&str> = token::OPERATORS
+        .iter()
+        .chain(token::COMPARATORS.iter())
+        .chain(token::SYMBOLS.iter())
+        .copied()
+        .collect();
+    special_symbols.sort_by_key(|symbol| std::cmp::Reverse(symbol.len()));
 
     let mut processed_content: Vec<String> = Vec::new();
-    let mut inside_string = false;
-    let mut complete_string = String::new();
+    let mut current_word = String::new();
+    let mut rest = line_content;
+
+    while let Some(c) = rest.chars().next() {
+        //Whitespace ends the current word
+        if c.is_whitespace() {
+            push_word(&mut processed_content, &mut current_word);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
 
-    for word in line_content.split_whitespace() {
-        if word.starts_with('\"') {
-            inside_string = true;
+        //A quote starts a string, which goes until the next
+        //quote that isn't escaped by a backslash
+        if c == '\"' {
+            push_word(&mut processed_content, &mut current_word);
+
+            let closing = rest[1..]
+                .char_indices()
+                .scan(false, |escaped, (i, c)| {
+                    let found = (c == '\"' && !*escaped).then_some(i);
+                    *escaped = c == '\\' && !*escaped;
+                    Some(found)
+                })
+                .flatten()
+                .next()
+                .ok_or(JuvinilError::UnclosedString(line_number + 1))?;
+
+            processed_content.push(rest[..closing + 2].to_string());
+            rest = &rest[closing + 2..];
+            continue;
         }
 
-        //If inside a string, we append the `complete_string` variable until we find a <";>
-        if inside_string {
-            complete_string.push_str(word);
-            if word.ends_with(&['\"'][..]) {
-                inside_string = false;
-                processed_content.push(complete_string[0..complete_string.len()].to_string());
-                complete_string.clear();
-            } else {
-                complete_string.push(' ');
-            }
+        if let Some(symbol) = special_symbols.iter().find(|s| rest.starts_with(**s)) {
+            push_word(&mut processed_content, &mut current_word);
+            processed_content.push(symbol.to_string());
+            rest = &rest[symbol.len()..];
+            continue;
+        }
+
+        current_word.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    push_word(&mut processed_content, &mut current_word);
+
+    Ok(processed_content)
+}
+
+//Splits a string with embedded expressions, like `"sum is {a + b}"`,
+//into its fragments and the tokens of each expression:
+//`INTERPSTART(sum is )`, `a`, `+`, `b`, `INTERPEND()`.
+//`{{` and `}}` stand for a bracket in the string itself.
+//Returns None if the word isn't an interpolated string
+fn interpolation_tokens(word: &str, line_number: usize) -> JuvinilResult<Option<Vec<Token>>> {
+    if !word.starts_with('"') || !word.contains(['{', '}']) {
+        return Ok(None);
+    }
 
+    let content = &word[1..word.len() - 1];
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut fragment = String::new();
+    let mut rest = content;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            fragment.push(c);
+            rest = &rest[2..];
             continue;
         }
 
-        let special_symbols = &[
-            "++", "--", "+=", "-=", "{", "}", "(", ")", "[", "]", ";", ",",
-        ];
-
-        let mut has_special_symbol = false;
-        for special_symbol in special_symbols {
-            if word.contains(special_symbol) {
-                has_special_symbol = true;
-                let mut pushed_special = false;
-                for part in word.split(special_symbol) {
-                    if !part.is_empty() {
-                        processed_content.push(part.to_string());
-                    } else if !pushed_special {
-                        processed_content.push(special_symbol.to_string());
-                        pushed_special = true;
-                    }
-                }
-            }
+        if c != '{' {
+            fragment.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
         }
 
-        if !has_special_symbol {
-            processed_content.push(word.to_string());
+        //The expression goes until the next closing bracket,
+        //since expressions never have brackets themselves
+        let closing = rest.find('}').ok_or(JuvinilError::UnclosedInterpolation(
+            content.into(),
+            line_number + 1,
+        ))?;
+
+        let fragment_type = match tokens.is_empty() {
+            true => TokenType::INTERPSTART,
+            false => TokenType::INTERPMIDDLE,
+        };
+        tokens.push(Token::new(
+            fragment_type,
+            std::mem::take(&mut fragment),
+            line_number + 1,
+        ));
+
+        for str_token in pre_process_line(&rest[1..closing], line_number)? {
+            tokens.push(process_token(str_token.as_str(), line_number + 1)?);
         }
+
+        rest = &rest[closing + 1..];
     }
 
-    if inside_string {
-        return Err(JuvinilError::UnclosedString(line_number));
+    //Without expressions, it's a plain string
+    //with brackets written as `{{` or `}}`
+    if tokens.is_empty() {
+        return Ok(Some(vec![Token::new(
+            TokenType::STRING,
+            fragment,
+            line_number + 1,
+        )]));
     }
 
-    Ok(processed_content)
+    tokens.push(Token::new(TokenType::INTERPEND, fragment, line_number + 1));
+
+    Ok(Some(tokens))
+}
+
+//Moves the word being built into the processed content, if there is one
+fn push_word(processed_content: &mut Vec<String>, current_word: &mut String) {
+    if !current_word.is_empty() {
+        processed_content.push(std::mem::take(current_word));
+    }
 }
 
 //Maps the current &str against some static vectors (token.rs)
@@ -141,7 +230,9 @@ fn process_token(token: &str, line_number: usize) -> JuvinilResult<Token> {
 
     let regex_token = regex_token::REGEX_TOKEN_MAP
         .iter()
-        .find(|op| Regex::new(op.regex_template).unwrap().is_match(token))
+        .zip(COMPILED_REGEX_TOKENS.iter())
+        .find(|(_, regex)| regex.is_match(token))
+        .map(|(op, _)| op)
         .ok_or(JuvinilError::LexicalError(String::from(token), line_number))?;
 
     Ok(Token::from_regex_token(regex_token, token, line_number))
@@ -16,10 +16,10 @@ impl RegexToken {
 
 //Map three types of REGEX statements:
 //Number (0-9 with a single .),
-//String (enclosed by quotes with no quotes between them),
+//String (enclosed by quotes, with any quote between them escaped),
 //and an ID (a-z, A-Z or underscore)
 pub const REGEX_TOKEN_MAP: [RegexToken; 3] = [
     RegexToken::new(r#"^[+-]?[0-9]+$"#, TokenType::NUMBER),
-    RegexToken::new(r#"^"[^"]*"$"#, TokenType::STRING),
+    RegexToken::new(r#"^"([^"\\]|\\.)*"$"#, TokenType::STRING),
     RegexToken::new(r#"^[a-zA-Z_]+$"#, TokenType::ID),
 ];
@@ -1,11 +1,12 @@
 use super::regex_token::RegexToken;
 
-pub const KEYWORDS: [&str; 11] = [
+pub const KEYWORDS: [&str; 13] = [
     "func", "return", "if", "else", "for", "do", "while", "break", "continue", "true", "false",
+    "const", "global",
 ];
 
-pub const OPERATORS: [&str; 13] = [
-    "=", "+", "-", "*", "/", "%", "!", "&", "|", "++", "--", "+=", "-=",
+pub const OPERATORS: [&str; 15] = [
+    "=", "+", "-", "*", "/", "%", "!", "&", "|", "++", "--", "+=", "-=", "?", ":",
 ];
 
 pub const JV_TYPES: [&str; 4] = ["void", "int", "boolean", "string"];
@@ -23,6 +24,9 @@ pub enum TokenType {
     COMPARATOR,
     ID,
     STRING,
+    INTERPSTART,  //First fragment of an interpolated string, before its first expression
+    INTERPMIDDLE, //Fragment between two expressions of an interpolated string
+    INTERPEND,    //Last fragment of an interpolated string, after its last expression
     NUMBER,
     EOF,
 }
@@ -67,8 +71,11 @@ impl Token {
         Token::new(TokenType::ID, value, file_line)
     }
 
+    //Only the enclosing quotes are removed,
+    //so escaped quotes stay in the string
     pub fn new_string(value: String, file_line: usize) -> Self {
-        Token::new(TokenType::STRING, value.replace('"', ""), file_line)
+        let content = value[1..value.len() - 1].to_string();
+        Token::new(TokenType::STRING, content, file_line)
     }
 
     pub fn new_number(value: String, file_line: usize) -> Self {
@@ -1,3 +1,10 @@
+pub mod bytecode;
+pub mod cli;
+pub mod code_generation;
+pub mod diagnostic;
 pub mod error;
+pub mod interpreter;
 pub mod lexical_analysis;
+pub mod semantic_analysis;
 pub mod syntax_analysis;
+pub mod toolchain;
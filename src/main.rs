@@ -1,66 +1,397 @@
-use std::{fs, process::Command};
+use std::{
+    fs,
+    io::Read,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
 
-use juvinil::{error::JuvinilResult, lexical_analysis::lex, syntax_analysis::parser::Parser};
+use juvinil::{
+    bytecode::{compiler, disassembler, file, vm::Vm},
+    cli::{Cli, CliCommand, Input, Stage, Target, HELP},
+    code_generation::{asm::AsmGenerator, c::CGenerator, cpp::CppGenerator, wat::WatGenerator},
+    diagnostic::{Diagnostic, Severity, WarningConfig},
+    error::{JuvinilError, JuvinilResult},
+    interpreter::{self, Interpreter},
+    lexical_analysis::lex,
+    syntax_analysis::{ast::Program, parser::Parser},
+    toolchain::{self, Assembler, Toolchain},
+};
 
 fn main() {
-    //Initializes logging
-    tracing_subscriber::fmt().pretty().init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    //Call the `run` function, returing an
-    //reporting an error in case one occurs
-    if let Err(err) = run("test_inputs/test.jv") {
-        tracing::error!("{}", err);
-        std::process::exit(1);
+    //A command line that can't be understood
+    //is reported before anything else runs
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("{}", Diagnostic::from_error(&err));
+            eprintln!("Run `juvinil help` to see the available commands");
+            std::process::exit(exit_code(&err));
+        }
+    };
+
+    //Initializes logging. Logs go to stderr, so they don't mix with
+    //what `emit` prints, and only show every step when asked to
+    let max_level = match cli.verbose {
+        true => tracing::Level::INFO,
+        false => tracing::Level::WARN,
+    };
+    tracing_subscriber::fmt()
+        .pretty()
+        .with_max_level(max_level)
+        .with_writer(std::io::stderr)
+        .init();
+
+    //Call the `run` function, reporting an error in case one occurs.
+    //Only errors change the exit code, warnings
+    //do so only if they were denied. `run` exits
+    //with the exit status of the program it ran
+    match run(&cli) {
+        Ok(0) => {}
+        Ok(status) => std::process::exit(status),
+        Err(err) => {
+            tracing::error!("{}", Diagnostic::from_error(&err));
+            std::process::exit(exit_code(&err));
+        }
+    }
+}
+
+//Mistakes in the command line exit with 2, a program that
+//timed out with 124 and one killed by a signal with 128
+//plus the signal, like shells do. Other errors exit with 1
+fn exit_code(err: &JuvinilError) -> i32 {
+    match err {
+        JuvinilError::InvalidArgument(..)
+        | JuvinilError::UnknownWarning(..)
+        | JuvinilError::MissingInput => 2,
+        JuvinilError::ProgramTimeout(..) => 124,
+        JuvinilError::ProgramSignal(signal) => 128 + signal,
+        _ => 1,
+    }
+}
+
+//Report a diagnostic with the log level matching its severity
+fn report(diagnostic: &Diagnostic) {
+    match diagnostic.severity {
+        Severity::Error => tracing::error!("{}", diagnostic),
+        Severity::Warning => tracing::warn!("{}", diagnostic),
+        Severity::Note | Severity::Help => tracing::info!("{}", diagnostic),
     }
 }
 
-//Run all steps of the compiler
-fn run(file_path: &str) -> JuvinilResult<()> {
+//Run the steps of the compiler the command needs,
+//returning the exit status
+fn run(cli: &Cli) -> JuvinilResult<i32> {
+    if cli.command == CliCommand::Help {
+        print!("{}", HELP);
+        return Ok(0);
+    }
+
+    //Bytecode files were already checked when they were built,
+    //so they go straight to the virtual machine
+    if cli.command == CliCommand::Exec {
+        tracing::info!("--------LOADING BYTECODE--------");
+        let module = file::decode(&read_bytes(&cli.input)?)?;
+        tracing::info!("Successfully loaded {:?}", cli.input);
+
+        tracing::info!("--------RUNNING BYTECODE--------");
+        let mut input = std::io::stdin().lock();
+        let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+        return Vm::new(&module, &mut input, &mut output).run();
+    }
+
+    //Warning levels come from the command line,
+    //like `-W error` or `-A unused-variable`
+    let warning_config = WarningConfig::from_args(&cli.warning_args)?;
+
     //Start by reading the given file into a String
     tracing::info!("--------READING INPUT--------");
-    let file = fs::read_to_string(file_path)?;
-    tracing::info!("Successfully read contents of file {}", file_path);
+    let file = read_input(&cli.input)?;
+    tracing::info!("Successfully read contents of {:?}", cli.input);
 
     //Take the current file and tokenize it (lex.rs)
     tracing::info!("--------LEXICAL ANALYSIS--------");
     let tokens = lex::tokenize(file)?;
     tracing::info!("Successfully tokenized file contents");
 
+    if let CliCommand::Emit(Stage::Tokens, output) = &cli.command {
+        let lines: Vec<String> = tokens
+            .iter()
+            .map(|token| format!("{} {:?}\n", token.file_line, token))
+            .collect();
+
+        write_output(output, &lines.concat())?;
+        return Ok(0);
+    }
+
     //Take the resulting tokens and parse them,
-    //which verifies the code syntax and also
-    //builds the intermediary code
+    //which verifies the code syntax and semantics
+    //and builds the program tree
     tracing::info!("--------SYNTAX ANALYSIS--------");
-    let mut parser = Parser::new(tokens)?;
-    parser.parse()?;
+    let mut parser = Parser::new(tokens)?.with_warning_config(warning_config);
+    let result = parser.parse();
+
+    //Warnings are reported even if the parse failed,
+    //since they may be the reason it failed
+    for diagnostic in parser.diagnostics() {
+        report(diagnostic);
+    }
+
+    let program = result?;
     tracing::info!("Successfully parsed file contents");
 
-    //Take the intermediary code from the parser
-    //and dump it into a `.c` file
+    //Interpreted programs run right away, without the native toolchain
+    if let (CliCommand::Run(_), Target::Interpreter) = (&cli.command, cli.target) {
+        tracing::info!("--------INTERPRETING PROGRAM--------");
+        return interpreter::with_stack(|| {
+            let mut input = std::io::stdin().lock();
+            let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+            Interpreter::new(&program, &mut input, &mut output).run()
+        });
+    }
+
+    if let (CliCommand::Run(_), Target::Vm) = (&cli.command, cli.target) {
+        tracing::info!("--------RUNNING BYTECODE--------");
+        let module = compiler::compile(&program);
+        let mut input = std::io::stdin().lock();
+        let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+        return Vm::new(&module, &mut input, &mut output).run();
+    }
+
+    if let (CliCommand::Build(output), Target::Bytecode) = (&cli.command, cli.target) {
+        tracing::info!("--------WRITING BYTECODE--------");
+        let module = compiler::compile(&program);
+        let path = default_output(&cli.input, output, file::EXTENSION);
+        fs::write(&path, file::encode(&module))?;
+        tracing::info!("Successfully wrote {}", path);
+        return Ok(0);
+    }
+
+    let (executable, program_args) = match &cli.command {
+        CliCommand::Check => return Ok(0),
+        CliCommand::Exec => unreachable!(),
+        CliCommand::Emit(Stage::Ast, output) => {
+            write_output(output, &format!("{:#?}\n", program))?;
+            return Ok(0);
+        }
+        CliCommand::Emit(Stage::Bytecode, output) => {
+            let module = compiler::compile(&program);
+            write_output(output, &disassembler::disassemble(&module))?;
+            return Ok(0);
+        }
+        CliCommand::Emit(Stage::Wat, output) => {
+            let module = WatGenerator::new()
+                .with_pause_on_exit(cli.pause_on_exit)
+                .generate(&program);
+            write_output(output, &module)?;
+            return Ok(0);
+        }
+        CliCommand::Emit(stage, output) => {
+            let target = match stage {
+                Stage::C => Target::C,
+                Stage::Asm => Target::Asm,
+                _ => Target::Cpp,
+            };
+            write_output(output, &intermediary_code(&program, target, cli))?;
+            return Ok(0);
+        }
+        CliCommand::Build(output) => (default_executable(&cli.input, output), None),
+        CliCommand::Run(program_args) => (temporary_path(""), Some(program_args)),
+        CliCommand::Help => unreachable!(),
+    };
+
+    //Generate the intermediary code from the program tree
+    //and dump it into a temporary `.cpp`, `.c` or `.s` file
     tracing::info!("--------DUMPING INTERMEDIARY CODE--------");
-    parser.dump_intermediary_code("compiler_results/result.cpp")?;
+    let intermediary = match cli.target {
+        Target::C => temporary_path(".c"),
+        Target::Asm => temporary_path(".s"),
+        _ => temporary_path(".cpp"),
+    };
+    fs::write(&intermediary, intermediary_code(&program, cli.target, cli))?;
     tracing::info!("Successfully dumped intermediary code");
 
+    tracing::info!("--------COMPILING INTERMEDIARY CODE--------");
+    let compiled = compile(cli, &intermediary, &executable);
+    let _ = fs::remove_file(&intermediary);
+    compiled?;
+
+    let Some(program_args) = program_args else {
+        return Ok(0);
+    };
+
+    //The program shares the standard input and output of the
+    //compiler, and its exit status becomes the one of the compiler
     tracing::info!("--------EXECUTING INTERMEDIARY CODE--------");
-    //Run gcc to compile the generated code
-    Command::new("g++.exe")
-        .arg("-o")
-        .arg("compiler_results/result.exe")
-        .arg("compiler_results/result.cpp")
-        .status()
-        .unwrap();
-
-    //Run the generated .exe
-    Command::new("compiler_results/result.exe")
-        .status()
-        .unwrap();
+    let status = execute(&executable, program_args, cli.timeout);
+    let _ = fs::remove_file(&executable);
+
+    status
+}
+
+//Runs an executable, returning its exit status. A program
+//that runs for longer than the timeout is killed
+fn execute(executable: &str, args: &[String], timeout: Option<u64>) -> JuvinilResult<i32> {
+    let mut child = Command::new(executable).args(args).spawn()?;
+
+    let status = match timeout {
+        None => child.wait()?,
+        Some(seconds) => {
+            let deadline = Instant::now() + Duration::from_secs(seconds);
+
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
+                }
+
+                if Instant::now() >= deadline {
+                    child.kill()?;
+                    child.wait()?;
+                    return Err(JuvinilError::ProgramTimeout(seconds));
+                }
+
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    };
+
+    if let Some(code) = status.code() {
+        return Ok(code);
+    }
+
+    //Without an exit code, the program was terminated by a signal
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Err(JuvinilError::ProgramSignal(signal));
+    }
+
+    Ok(1)
+}
+
+//Generates the C++, C or assembly code of the program
+fn intermediary_code(program: &Program, target: Target, cli: &Cli) -> String {
+    match target {
+        Target::Asm => AsmGenerator::new()
+            .with_pause_on_exit(cli.pause_on_exit)
+            .generate(program),
+        Target::C => CGenerator::new()
+            .with_pause_on_exit(cli.pause_on_exit)
+            .generate(program),
+        _ => CppGenerator::new()
+            .with_pause_on_exit(cli.pause_on_exit)
+            .generate(program),
+    }
+}
+
+//Compiles the intermediary code with the configured toolchain
+fn compile(cli: &Cli, intermediary: &str, executable: &str) -> JuvinilResult<()> {
+    //Assembly only needs to be assembled and linked
+    if cli.target == Target::Asm {
+        let assembler = Assembler::detect()?;
+        assembler.build(intermediary, executable)?;
+        tracing::info!("Successfully assembled with {}", assembler.assembler());
+        return Ok(());
+    }
+
+    let toolchain = match cli.target {
+        Target::C => Toolchain::detect_c(cli.cc.as_deref())?,
+        _ => Toolchain::detect(cli.cxx.as_deref())?,
+    };
+    let mut toolchain = toolchain.with_warning_flags(cli.cxx_flags.clone());
+    if let Some(opt_level) = &cli.opt_level {
+        toolchain = toolchain.with_opt_level(opt_level)?;
+    }
+
+    toolchain.compile(intermediary, executable)?;
+    tracing::info!("Successfully compiled with {}", toolchain.compiler());
+
+    Ok(())
+}
+
+//Reads the source code from a file or the standard input
+fn read_input(input: &Input) -> JuvinilResult<String> {
+    match input {
+        Input::File(path) => Ok(fs::read_to_string(path)?),
+        Input::Stdin => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        }
+    }
+}
+
+//Reads a bytecode file from a file or the standard input
+fn read_bytes(input: &Input) -> JuvinilResult<Vec<u8>> {
+    match input {
+        Input::File(path) => Ok(fs::read(path)?),
+        Input::Stdin => {
+            let mut content = Vec::new();
+            std::io::stdin().read_to_end(&mut content)?;
+            Ok(content)
+        }
+    }
+}
+
+//Writes what `emit` prints into a file, or the standard output
+fn write_output(output: &Option<String>, content: &str) -> JuvinilResult<()> {
+    match output {
+        Some(path) => fs::write(path, content)?,
+        None => print!("{}", content),
+    }
 
     Ok(())
 }
 
+//The executable is named after the file, unless `-o` was given
+fn default_executable(input: &Input, output: &Option<String>) -> String {
+    match output {
+        Some(output) => output.clone(),
+        None => toolchain::executable_path(&input_stem(input)),
+    }
+}
+
+//Like `default_executable`, for other files `build` writes
+fn default_output(input: &Input, output: &Option<String>, extension: &str) -> String {
+    match output {
+        Some(output) => output.clone(),
+        None => format!("{}.{}", input_stem(input), extension),
+    }
+}
+
+fn input_stem(input: &Input) -> String {
+    match input {
+        Input::File(path) => Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "main".into()),
+        Input::Stdin => "main".into(),
+    }
+}
+
+//Path of a file only used during this compilation
+fn temporary_path(extension: &str) -> String {
+    let name = format!("juvinil-{}", std::process::id());
+    let path = std::env::temp_dir().join(name);
+
+    match extension.is_empty() {
+        true => toolchain::executable_path(&path.to_string_lossy()),
+        false => format!("{}{}", path.to_string_lossy(), extension),
+    }
+}
+
 //Just some random tests
 #[cfg(test)]
 mod tests {
     use super::*;
+    use juvinil::bytecode::{vm, Function, Module, Op};
+    use juvinil::diagnostic::WarningLevel;
+    use juvinil::error::{JuvinilError, JuvinilWarning};
+    use juvinil::interpreter::value::Value;
+    use juvinil::lexical_analysis::token::TokenType;
+    use juvinil::syntax_analysis::builtins::BUILTINS;
+    use std::io::{self, Write};
 
     #[test]
     fn lex_operators_ok() {
@@ -131,4 +462,1914 @@ mod tests {
 
         assert!(result.is_ok(), "Should be OK");
     }
+
+    #[test]
+    fn boolexpr_ok() {
+        let file_content = fs::read_to_string("test_inputs/boolexpr.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn non_boolean_condition_err() {
+        let file_content = String::from("int x;\nx = 5;\nif ( x + 1 ) {\n    x = 0;\n}\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::InvalidCondition(..))),
+            "Should be an InvalidCondition error"
+        );
+    }
+
+    #[test]
+    fn increment_non_variable_err() {
+        let sources = [
+            "int x = ++ 5;\n",
+            "int y = 1;\nint x = -- ( y + 1 );\n",
+            "int y = 1;\nint x = ++ - y;\n",
+        ];
+
+        for source in sources {
+            let tokens = lex::tokenize(source.into());
+
+            assert!(tokens.is_ok(), "Should be OK");
+
+            let mut parser = Parser::new(tokens.unwrap()).unwrap();
+            let result = parser.parse();
+
+            assert!(
+                matches!(result, Err(JuvinilError::InvalidIncrement(..))),
+                "Should be an InvalidIncrement error: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn ternary_ok() {
+        let file_content = fs::read_to_string("test_inputs/ternary.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn ternary_mismatched_types_err() {
+        let file_content = String::from("int x;\nx = 5;\nx = x > 2 ? 1 : \"one\";\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::InvalidConditionalTypes(..))),
+            "Should be an InvalidConditionalTypes error"
+        );
+    }
+
+    #[test]
+    fn decls_ok() {
+        let file_content = fs::read_to_string("test_inputs/decls.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn const_reassignment_err() {
+        let file_content = String::from("const int LIMIT = 10;\nLIMIT = 5;\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::ConstantAssignment(..))),
+            "Should be a ConstantAssignment error"
+        );
+    }
+
+    #[test]
+    fn interleaved_ok() {
+        let file_content = fs::read_to_string("test_inputs/interleaved.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    //Builds a variable name out of letters only, since IDs can't have digits
+    fn letters_name(mut index: usize) -> String {
+        let mut name = String::from("v_");
+        loop {
+            name.push((b'a' + (index % 26) as u8) as char);
+            index /= 26;
+            if index == 0 {
+                return name;
+            }
+        }
+    }
+
+    #[test]
+    fn thousands_of_statements_ok() {
+        //Alternate declarations and statements, both at the top
+        //level and inside blocks, for a few thousand statements
+        let mut file_content = String::from("int total = 0;\n");
+        for index in 0..3000 {
+            let name = letters_name(index);
+            file_content.push_str(format!("int {} = {};\n", name, index).as_str());
+            file_content.push_str(format!("total += {};\n", name).as_str());
+            file_content.push_str("printf ( \"%d\\n\" , total ) ;\n");
+            file_content.push_str("{\n    int inner = total;\n    inner = inner + 1;\n}\n");
+        }
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn flow_ok() {
+        let file_content = fs::read_to_string("test_inputs/flow.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn unassigned_in_some_paths_err() {
+        let sources = [
+            //Only assigned when the if is taken
+            "int x;\nboolean c = true;\nif ( c ) {\n    x = 1;\n}\nprintf ( \"%d\" , x ) ;\n",
+            //The loop body may never run
+            "int x;\nboolean c = false;\nwhile ( c ) {\n    x = 1;\n}\nprintf ( \"%d\" , x ) ;\n",
+            //`continue` skips the assignment
+            "int x;\nboolean c = true;\ndo {\n    if ( c ) {\n        continue;\n    }\n    x = 1;\n} while ( c );\nprintf ( \"%d\" , x ) ;\n",
+            //`break` leaves the loop before the assignment
+            "int x;\nwhile ( true ) {\n    break;\n    x = 1;\n}\nprintf ( \"%d\" , x ) ;\n",
+            //Compound assignments read the variable
+            "int x;\nx += 1;\n",
+        ];
+
+        for source in sources {
+            let tokens = lex::tokenize(source.into());
+
+            assert!(tokens.is_ok(), "Should be OK");
+
+            let mut parser = Parser::new(tokens.unwrap()).unwrap();
+            let result = parser.parse();
+
+            assert!(
+                matches!(result, Err(JuvinilError::UnassignedVariable(..))),
+                "Should be an UnassignedVariable error: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn unused_warnings() {
+        let file_content = fs::read_to_string("test_inputs/unused.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+
+        let expected = [
+            JuvinilWarning::UnusedParameter("unused".into(), "twice".into(), 1),
+            JuvinilWarning::UnreadVariable("inner".into(), 19),
+            JuvinilWarning::UnusedVariable("declared_only".into(), 13),
+            JuvinilWarning::UnreadVariable("written_only".into(), 14),
+            JuvinilWarning::UnusedFunction("never_called".into(), 5),
+        ];
+
+        let messages: Vec<String> = parser
+            .diagnostics()
+            .iter()
+            .map(|d| d.message.clone())
+            .collect();
+        let expected: Vec<String> = expected.iter().map(|w| w.to_string()).collect();
+
+        assert_eq!(messages, expected);
+        assert!(parser
+            .diagnostics()
+            .iter()
+            .all(|d| d.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn shadowing_ok() {
+        let file_content = fs::read_to_string("test_inputs/shadowing.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        //Shadowing is allowed by default, so nothing is reported
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn shadowing_warnings() {
+        let file_content = fs::read_to_string("test_inputs/shadowing.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let config = WarningConfig::from_args(&args("-W shadowing")).unwrap();
+        let mut parser = Parser::new(tokens.unwrap())
+            .unwrap()
+            .with_warning_config(config);
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+
+        let expected = [
+            Diagnostic::from_warning(
+                &JuvinilWarning::ShadowedVariable("total".into(), 5, 2),
+                WarningLevel::Warn,
+            ),
+            Diagnostic::from_warning(
+                &JuvinilWarning::ShadowedVariable("i".into(), 15, 12),
+                WarningLevel::Warn,
+            ),
+            Diagnostic::from_warning(
+                &JuvinilWarning::ShadowedVariable("i".into(), 17, 15),
+                WarningLevel::Warn,
+            ),
+        ];
+
+        assert_eq!(parser.diagnostics(), expected);
+        assert!(expected[0]
+            .to_string()
+            .contains("note: the shadowed `total` is declared here - line 2"));
+    }
+
+    #[test]
+    fn missing_return_err() {
+        let sources = [
+            //The body is empty
+            "func int f ( ) {\n}\nprintln ( f ( ) );\n",
+            //Only returns when the if is taken
+            "func int f ( boolean c ) {\n    if ( c ) {\n        return 1;\n    }\n}\nprintln ( f ( true ) );\n",
+            //The loop body may never run
+            "func string f ( int n ) {\n    while ( n > 0 ) {\n        return \"positive\";\n    }\n}\nprintln ( f ( 1 ) );\n",
+            //`break` leaves the loop before the return
+            "func boolean f ( ) {\n    while ( true ) {\n        break;\n    }\n}\nprintln ( f ( ) );\n",
+        ];
+
+        for source in sources {
+            let tokens = lex::tokenize(source.into());
+
+            assert!(tokens.is_ok(), "Should be OK");
+
+            let mut parser = Parser::new(tokens.unwrap()).unwrap();
+            let result = parser.parse();
+
+            assert!(
+                matches!(result, Err(JuvinilError::MissingReturn(..))),
+                "Should be a MissingReturn error: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn redeclaration_err() {
+        let sources = [
+            //Same scope
+            "int x = 1;\nint x = 2;\n",
+            //Same declaration
+            "int x, x;\n",
+            //Repeated parameter
+            "func int f ( int a, int a ) {\n    return a;\n}\n",
+            //Parameter redeclared at the top of the function body
+            "func int f ( int a ) {\n    int a = 1;\n    return a;\n}\n",
+        ];
+
+        for source in sources {
+            let tokens = lex::tokenize(source.into());
+
+            assert!(tokens.is_ok(), "Should be OK");
+
+            let mut parser = Parser::new(tokens.unwrap()).unwrap();
+            let result = parser.parse();
+
+            assert!(
+                matches!(result, Err(JuvinilError::DuplicateVariable(..))),
+                "Should be a DuplicateVariable error: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn shadowed_in_own_initializer_err() {
+        //The initializer already refers to the new variable
+        let file_content = String::from("int x = 1;\n{\n    int x = x + 1;\n}\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::UnassignedVariable(..))),
+            "Should be an UnassignedVariable error"
+        );
+    }
+
+    #[test]
+    fn globals_ok() {
+        let file_content = fs::read_to_string("test_inputs/globals.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+
+        //Globals are emitted at file scope, before any function uses them
+        let code = CppGenerator::new().generate(&result.unwrap());
+        let global_pos = code.find("\nint counter = 0;\n").unwrap();
+        let tick_pos = code.find("void tick() {").unwrap();
+        let main_pos = code.find("int main() {").unwrap();
+
+        assert!(global_pos < tick_pos && tick_pos < main_pos);
+    }
+
+    #[test]
+    fn portable_main_ok() {
+        let file_content = fs::read_to_string("test_inputs/test.jv").unwrap();
+        let tokens = lex::tokenize(file_content).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let code = CppGenerator::new().generate(&program);
+        assert!(!code.contains("conio.h") && !code.contains("getch"));
+        assert!(code.ends_with("    return 0;\n}\n"));
+        assert!(!code.contains("getchar();"));
+
+        //Pausing is opt-in, and registered before the
+        //globals are initialized and `main` runs
+        let code = CppGenerator::new()
+            .with_pause_on_exit(true)
+            .generate(&program);
+        let registered = code.find("const int pause_registered = atexit(pause);\n");
+        assert!(registered.unwrap() < code.find("int main() {").unwrap());
+        assert!(code.ends_with("    return 0;\n}\n"));
+    }
+
+    #[test]
+    fn c_backend_ok() {
+        let file_content = fs::read_to_string("test_inputs/overloads.jv").unwrap();
+        let tokens = lex::tokenize(file_content).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let code = CGenerator::new().generate(&program);
+        assert!(!code.contains("std::") && !code.contains("using namespace"));
+        assert!(code.contains("int jv_fn_describe__int_int(int first, int second) {\n"));
+        assert!(code.contains("jv_fn_describe__string(\"text\")"));
+        assert!(code.ends_with("    return 0;\n}\n"));
+
+        //Globals are initialized in `main`, since C only
+        //allows constants as initializers at file scope
+        let source = "func int one ( ) {\n    return 1;\n}\nglobal int start = one ( );\nprintln ( start );\n";
+        let tokens = lex::tokenize(source.into()).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+        let code = CGenerator::new().generate(&program);
+        assert!(code.contains("\nint start = 0;\n"));
+        assert!(code.contains("int main(void) {\n    start = jv_fn_one();\n"));
+
+        //Every sample must print what the C++ backend prints, which
+        //can only be checked with both compilers installed
+        let (Some(c), Some(cpp)) = (
+            require_toolchain(Toolchain::detect_c(None), "c_backend_ok"),
+            require_toolchain(Toolchain::detect(None), "c_backend_ok"),
+        ) else {
+            return;
+        };
+
+        let dir = std::env::temp_dir();
+        for entry in fs::read_dir("test_inputs").unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            if name == "for" {
+                continue;
+            }
+
+            let file_content = fs::read_to_string(&path).unwrap();
+            let tokens = lex::tokenize(file_content).unwrap();
+            let Ok(program) = Parser::new(tokens).unwrap().parse() else {
+                continue;
+            };
+
+            let mut executables = Vec::new();
+            for (toolchain, code, extension) in [
+                (&c, CGenerator::new().generate(&program), "c"),
+                (&cpp, CppGenerator::new().generate(&program), "cpp"),
+            ] {
+                let source = dir.join(format!("juvinil_backend_{}.{}", name, extension));
+                let executable = dir.join(format!("juvinil_backend_{}_{}", name, extension));
+                fs::write(&source, code).unwrap();
+                toolchain
+                    .compile(source.to_str().unwrap(), executable.to_str().unwrap())
+                    .unwrap();
+
+                executables.push(executable);
+            }
+
+            for input in [
+                "5 abc\n  tail\ntrue".as_bytes(),
+                b"-7 +8 2147483648\n\n",
+                b"",
+            ] {
+                assert_eq!(
+                    run_native(&executables[0], input),
+                    run_native(&executables[1], input),
+                    "{}.jv should print the same",
+                    name
+                );
+            }
+        }
+    }
+
+    //Tests that build native programs are skipped without the toolchain.
+    //The skip is written past the captured output so it's always shown,
+    //and `JUVINIL_REQUIRE_TOOLCHAINS` turns it into a failure
+    fn require_toolchain<T>(toolchain: JuvinilResult<T>, test: &str) -> Option<T> {
+        match toolchain {
+            Ok(toolchain) => Some(toolchain),
+            Err(err) if std::env::var_os("JUVINIL_REQUIRE_TOOLCHAINS").is_some() => {
+                panic!("{} needs a toolchain: {}", test, err)
+            }
+            Err(err) => {
+                let _ = writeln!(
+                    io::stderr(),
+                    "skipping the native part of {}: {}",
+                    test,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    //Runs a compiled program with the given input,
+    //returning its exit status and what it printed
+    fn run_native(executable: &Path, input: &[u8]) -> (Option<i32>, Vec<u8>) {
+        let mut child = Command::new(executable)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        //Programs that don't read may end before getting their input
+        let _ = std::io::Write::write_all(child.stdin.as_mut().unwrap(), input);
+        let output = child.wait_with_output().unwrap();
+        (output.status.code(), output.stdout)
+    }
+
+    #[test]
+    fn asm_backend_ok() {
+        let file_content = fs::read_to_string("test_inputs/overloads.jv").unwrap();
+        let tokens = lex::tokenize(file_content).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let code = AsmGenerator::new().generate(&program);
+        assert!(code.contains("\n_start:\n    call jv_main\n"));
+        assert!(code.contains("\njv_fn_describe__int_int:\n    pushq %rbp\n"));
+        assert!(code.contains("    call jv_fn_describe__string\n"));
+
+        //Arguments past the sixth go on the stack, which stays aligned
+        let source = "func int sum ( int a, int b, int c, int d, int e, int f, int g ) {\n    return a + b + c + d + e + f + g;\n}\nprintln ( sum ( 1, 2, 3, 4, 5, 6, 7 ) );\n";
+        let tokens = lex::tokenize(source.into()).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+        let code = AsmGenerator::new().generate(&program);
+        assert!(code.contains("    movq 16(%rbp), %rax\n"));
+        assert!(code.contains("    subq $16, %rsp\n"));
+        assert!(code.contains("    movq %rax, 48(%rsp)\n"));
+
+        //Every sample must print what the C++ backend prints, which can
+        //only be checked on x86-64 Linux with both toolchains installed
+        let (Some(assembler), Some(cpp)) = (
+            require_toolchain(Assembler::detect(), "asm_backend_ok"),
+            require_toolchain(Toolchain::detect(None), "asm_backend_ok"),
+        ) else {
+            return;
+        };
+
+        let dir = std::env::temp_dir();
+        for entry in fs::read_dir("test_inputs").unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            if name == "for" {
+                continue;
+            }
+
+            let file_content = fs::read_to_string(&path).unwrap();
+            let tokens = lex::tokenize(file_content).unwrap();
+            let Ok(program) = Parser::new(tokens).unwrap().parse() else {
+                continue;
+            };
+
+            let asm_source = dir.join(format!("juvinil_asm_{}.s", name));
+            let asm_executable = dir.join(format!("juvinil_asm_{}_s", name));
+            fs::write(&asm_source, AsmGenerator::new().generate(&program)).unwrap();
+            assembler
+                .build(
+                    asm_source.to_str().unwrap(),
+                    asm_executable.to_str().unwrap(),
+                )
+                .unwrap();
+
+            let cpp_source = dir.join(format!("juvinil_asm_{}.cpp", name));
+            let cpp_executable = dir.join(format!("juvinil_asm_{}_cpp", name));
+            fs::write(&cpp_source, CppGenerator::new().generate(&program)).unwrap();
+            cpp.compile(
+                cpp_source.to_str().unwrap(),
+                cpp_executable.to_str().unwrap(),
+            )
+            .unwrap();
+
+            for input in [
+                "5 abc\n  tail\ntrue".as_bytes(),
+                b"-7 +8 2147483648\n\n",
+                b"",
+            ] {
+                assert_eq!(
+                    run_native(&asm_executable, input),
+                    run_native(&cpp_executable, input),
+                    "{}.jv should print the same",
+                    name
+                );
+            }
+        }
+    }
+
+    //Assembles a WebAssembly text module and validates it, checking
+    //that it also exports the `main` function the host calls
+    fn validate_wat(source: &str) -> Result<(), String> {
+        let binary = wat::parse_str(source).map_err(|error| error.to_string())?;
+        wasmparser::Validator::new()
+            .validate_all(&binary)
+            .map_err(|error| error.to_string())?;
+
+        for payload in wasmparser::Parser::new(0).parse_all(&binary) {
+            if let Ok(wasmparser::Payload::ExportSection(exports)) = payload {
+                for export in exports.into_iter().flatten() {
+                    if export.name == "main" && export.kind == wasmparser::ExternalKind::Func {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Err("The module doesn't export main".into())
+    }
+
+    #[test]
+    fn pause_on_early_exit_ok() {
+        //Returning early and calling `exit` pause like reaching the end
+        let sources = [
+            ("println ( 1 );\nif ( true ) {\n    return 3;\n}\nprintln ( 2 );\n", 3),
+            ("func void stop ( ) {\n    exit ( 4 );\n}\nprintln ( 1 );\nstop ( );\nprintln ( 2 );\n", 4),
+        ];
+
+        for (index, (source, status)) in sources.into_iter().enumerate() {
+            let tokens = lex::tokenize(source.into()).unwrap();
+            let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+            let code = WatGenerator::new()
+                .with_pause_on_exit(true)
+                .generate(&program);
+            let lines: Vec<&str> = code.lines().map(str::trim).collect();
+            assert!(lines.windows(2).any(|pair| pair[0] == "call $jv_pause"
+                && ["return", "call $jv_exit"].contains(&pair[1])));
+            validate_wat(&code).unwrap();
+
+            //Running them needs the toolchain of each backend
+            let dir = std::env::temp_dir();
+            let mut executables = Vec::new();
+            for (extension, toolchain) in [
+                ("cpp", Toolchain::detect(None)),
+                ("c", Toolchain::detect_c(None)),
+            ] {
+                let Some(toolchain) = require_toolchain(toolchain, "pause_on_early_exit_ok") else {
+                    continue;
+                };
+
+                let code = match extension {
+                    "c" => CGenerator::new()
+                        .with_pause_on_exit(true)
+                        .generate(&program),
+                    _ => CppGenerator::new()
+                        .with_pause_on_exit(true)
+                        .generate(&program),
+                };
+                let source = dir.join(format!("juvinil_pause_{}.{}", index, extension));
+                let executable = dir.join(format!("juvinil_pause_{}_{}", index, extension));
+                fs::write(&source, code).unwrap();
+                toolchain
+                    .compile(source.to_str().unwrap(), executable.to_str().unwrap())
+                    .unwrap();
+                executables.push(executable);
+            }
+
+            if let Some(assembler) =
+                require_toolchain(Assembler::detect(), "pause_on_early_exit_ok")
+            {
+                let source = dir.join(format!("juvinil_pause_{}.s", index));
+                let executable = dir.join(format!("juvinil_pause_{}_s", index));
+                fs::write(
+                    &source,
+                    AsmGenerator::new()
+                        .with_pause_on_exit(true)
+                        .generate(&program),
+                )
+                .unwrap();
+                assembler
+                    .build(source.to_str().unwrap(), executable.to_str().unwrap())
+                    .unwrap();
+                executables.push(executable);
+            }
+
+            for executable in executables {
+                assert_eq!(
+                    run_native(&executable, b"\n"),
+                    (Some(status), b"1\nPress Enter to exit...".to_vec()),
+                    "{} should pause",
+                    executable.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wat_backend_ok() {
+        let file_content = fs::read_to_string("test_inputs/overloads.jv").unwrap();
+        let tokens = lex::tokenize(file_content).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let code = WatGenerator::new().generate(&program);
+        assert!(code
+            .starts_with("(module\n  (import \"env\" \"write\" (func $write (param i32 i32)))\n"));
+        assert!(code.contains("\n  (func $jv_fn_describe__int_int (param $first i32) (param $second i32) (result i32)\n"));
+        assert!(code.contains("    call $jv_fn_describe__string\n"));
+        assert!(code.contains("  (export \"main\" (func $jv_main))\n"));
+
+        //Every sample must be a valid module, with and without pausing
+        for entry in fs::read_dir("test_inputs").unwrap() {
+            let path = entry.unwrap().path();
+            let file_content = fs::read_to_string(&path).unwrap();
+            let tokens = lex::tokenize(file_content).unwrap();
+            let Ok(program) = Parser::new(tokens).unwrap().parse() else {
+                continue;
+            };
+
+            for pause_on_exit in [false, true] {
+                let code = WatGenerator::new()
+                    .with_pause_on_exit(pause_on_exit)
+                    .generate(&program);
+                if let Err(error) = validate_wat(&code) {
+                    panic!("{} should be valid: {}", path.display(), error);
+                }
+            }
+        }
+
+        //The validator must reject broken modules
+        let code = WatGenerator::new().generate(&program);
+        let broken = [
+            code.replacen("    i32.add\n", "", 1),
+            code.replacen(
+                "    call $jv_fn_describe__string\n",
+                "    call $jv_fn_missing\n",
+                1,
+            ),
+            code.replacen("      end\n", "", 1),
+            code.replacen("(export \"main\"", "(export \"start\"", 1),
+            code.replacen("local.get $first", "local.get $third", 1),
+        ];
+        for module in broken {
+            assert!(validate_wat(&module).is_err());
+        }
+    }
+
+    #[test]
+    fn globals_err() {
+        let sources = [
+            //Functions can't use globals declared after them
+            "func int f ( ) {\n    return later;\n}\nglobal int later = 1;\nprintf ( \"%d\" , f ( ) ) ;\n",
+            //Globals must be initialized
+            "global int counter;\n",
+            //A global's initializer can't read itself
+            "global int counter = counter + 1;\n",
+            //Constant globals can't be assigned by functions
+            "global const int LIMIT = 1;\nfunc void f ( ) {\n    LIMIT = 2;\n}\nf ( );\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::UndeclaredVariable(..))
+        ));
+        assert!(matches!(results[1], Err(JuvinilError::SyntaxError(..))));
+        assert!(matches!(
+            results[2],
+            Err(JuvinilError::UnassignedVariable(..))
+        ));
+        assert!(matches!(
+            results[3],
+            Err(JuvinilError::ConstantAssignment(..))
+        ));
+    }
+
+    #[test]
+    fn overloads_ok() {
+        let file_content = fs::read_to_string("test_inputs/overloads.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn overloads_err() {
+        let overloads = "func int f ( int a ) {\n    return a;\n}\nfunc int f ( boolean b ) {\n    return b ? 1 : 0;\n}\n";
+
+        let duplicate = format!(
+            "{}func boolean f ( int c ) {{\n    return c > 0;\n}}\n",
+            overloads
+        );
+        let tokens = lex::tokenize(duplicate).unwrap();
+        let result = Parser::new(tokens).unwrap().parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::DuplicateFunction(..))),
+            "Should be a DuplicateFunction error"
+        );
+
+        let no_match = format!("{}int x = f ( \"text\" );\n", overloads);
+        let tokens = lex::tokenize(no_match).unwrap();
+        let result = Parser::new(tokens).unwrap().parse();
+
+        match result {
+            Err(err @ JuvinilError::NoMatchingOverload(..)) => {
+                let message = err.to_string();
+                assert!(message.contains("takes (string)"), "{}", message);
+                assert!(message.contains("`f(int)`, `f(boolean)`"), "{}", message);
+            }
+            _ => panic!("Should be a NoMatchingOverload error"),
+        }
+    }
+
+    #[test]
+    fn defaults_ok() {
+        let file_content = fs::read_to_string("test_inputs/defaults.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn arguments_err() {
+        let func =
+            "func int f ( int a, int b = 2, boolean c = true ) {\n    return c ? a : b;\n}\n";
+
+        let calls = [
+            "f ( );",
+            "f ( 1, 2, true, 4 );",
+            "f ( b: 1 );",
+            "f ( 1, d: 2 );",
+            "f ( 1, a: 2 );",
+            "f ( a: 1, 2 );",
+            "f ( 1, c: 2 );",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = calls
+            .iter()
+            .map(|call| {
+                let tokens = lex::tokenize(format!("{}int x = {}\n", func, call)).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::InvalidParamRange(_, 1, 3, 0, _))
+        ));
+        assert!(matches!(
+            results[1],
+            Err(JuvinilError::InvalidParamRange(_, 1, 3, 4, _))
+        ));
+        assert!(matches!(results[2], Err(JuvinilError::MissingArgument(..))));
+        assert!(matches!(results[3], Err(JuvinilError::UnknownArgument(..))));
+        assert!(matches!(
+            results[4],
+            Err(JuvinilError::DuplicateArgument(..))
+        ));
+        assert!(matches!(
+            results[5],
+            Err(JuvinilError::PositionalAfterNamed(..))
+        ));
+        assert!(matches!(
+            results[6],
+            Err(JuvinilError::InvalidParamType(..))
+        ));
+
+        let declarations = [
+            //A default in the middle of the parameters
+            "func int f ( int a = 1, int b ) {\n    return a + b;\n}\n",
+            //Defaults can't depend on variables
+            "global int base = 1;\nfunc int f ( int a = base ) {\n    return a;\n}\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = declarations
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::MissingDefaultValue(..))
+        ));
+        assert!(matches!(
+            results[1],
+            Err(JuvinilError::InvalidDefaultValue(..))
+        ));
+    }
+
+    #[test]
+    fn ambiguous_call_err() {
+        let file_content = String::from(
+            "func int f ( int a ) {\n    return a;\n}\nfunc int f ( int a, int b = 1 ) {\n    return a + b;\n}\nint x = f ( 1 );\n",
+        );
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        match result {
+            Err(err @ JuvinilError::AmbiguousCall(..)) => {
+                let message = err.to_string();
+                assert!(message.contains("`f(int)`, `f(int, int)`"), "{}", message);
+            }
+            _ => panic!("Should be an AmbiguousCall error"),
+        }
+    }
+
+    #[test]
+    fn print_ok() {
+        let file_content = fs::read_to_string("test_inputs/print.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn print_err() {
+        let sources = [
+            //A string formatted as an integer
+            "printf ( \"%d\\n\" , \"text\" ) ;\n",
+            //More specifiers than values
+            "printf ( \"%d %d\\n\" , 1 ) ;\n",
+            //Unsupported specifier
+            "printf ( \"%f\\n\" , 1 ) ;\n",
+            //The format must be a literal
+            "string format = \"%d\";\nprintf ( format , 1 ) ;\n",
+            //Nothing to print
+            "func void nothing ( ) {\n    return;\n}\nprint ( nothing ( ) );\n",
+            //Builtins can't be redeclared
+            "func void print ( int x ) {\n    return;\n}\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::InvalidFormatArgument(..))
+        ));
+        assert!(matches!(results[1], Err(JuvinilError::InvalidFormat(..))));
+        assert!(matches!(results[2], Err(JuvinilError::InvalidFormat(..))));
+        assert!(matches!(results[3], Err(JuvinilError::InvalidFormat(..))));
+        assert!(matches!(
+            results[4],
+            Err(JuvinilError::InvalidParamType(..))
+        ));
+        assert!(matches!(
+            results[5],
+            Err(JuvinilError::DuplicateFunction(..))
+        ));
+    }
+
+    #[test]
+    fn punctuation_strings_ok() {
+        //Strings are never mistaken for the symbols they contain
+        let file_content = String::from("println ( \")\" , \",\" , \"!\" , \"-\" , \"(\" ) ;\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn escaped_quotes_ok() {
+        //Escaped quotes neither end the string nor get removed from it
+        let file_content = String::from("println ( \"q\\\"q\" ) ;\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let tokens = tokens.unwrap();
+        assert_eq!(tokens[2].values(), (TokenType::STRING, "q\\\"q"));
+
+        let mut parser = Parser::new(tokens).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+    }
+
+    #[test]
+    fn input_ok() {
+        let file_content = fs::read_to_string("test_inputs/input.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+
+        //The reading functions are only emitted for programs that read
+        let code = CppGenerator::new().generate(&result.unwrap());
+        assert!(code.contains("namespace jv {"));
+        assert!(code.contains("int value = jv::read_int();"));
+
+        let tokens = lex::tokenize("println ( 1 );\n".into()).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+        let code = CppGenerator::new().generate(&program);
+        assert!(!code.contains("namespace jv {"));
+    }
+
+    #[test]
+    fn input_err() {
+        let file_content = String::from("int x = read_int ( 10 );\n");
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::InvalidParamCount(..))),
+            "Should be an InvalidParamCount error"
+        );
+    }
+
+    #[test]
+    fn string_operations_ok() {
+        let file_content = fs::read_to_string("test_inputs/strings_ops.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn string_operations_err() {
+        let sources = [
+            "string s = \"a\" + 1;\n",
+            "string s = \"a\" - \"b\";\n",
+            "string s = \"a\";\ns -= \"b\";\n",
+            "int n = len ( 1 );\n",
+            "string s = substr ( \"abc\", 1 );\n",
+            "string s = to_string ( \"1\" );\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(results[0], Err(JuvinilError::InvalidOperands(..))));
+        assert!(matches!(results[1], Err(JuvinilError::InvalidOperands(..))));
+        assert!(matches!(results[2], Err(JuvinilError::InvalidOperands(..))));
+        assert!(matches!(
+            results[3],
+            Err(JuvinilError::InvalidParamType(..))
+        ));
+        assert!(matches!(
+            results[4],
+            Err(JuvinilError::InvalidParamCount(..))
+        ));
+        assert!(matches!(
+            results[5],
+            Err(JuvinilError::InvalidParamType(..))
+        ));
+    }
+
+    #[test]
+    fn interpolation_ok() {
+        let file_content = fs::read_to_string("test_inputs/interpolation.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let tokens = tokens.unwrap();
+        assert!(tokens
+            .iter()
+            .any(|t| t.values() == (TokenType::INTERPSTART, "sum is ")));
+        assert!(tokens
+            .iter()
+            .any(|t| t.values() == (TokenType::INTERPMIDDLE, ", {literal} ")));
+        assert!(tokens
+            .iter()
+            .any(|t| t.values() == (TokenType::STRING, "{no expressions}")));
+
+        let mut parser = Parser::new(tokens).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+
+        let code = CppGenerator::new().generate(&result.unwrap());
+        assert!(code.contains("(string(\"hello, \") + name + string(\"!\"))"));
+        assert!(code.contains("string(even ? \"true\" : \"false\")"));
+    }
+
+    #[test]
+    fn interpolation_err() {
+        let sources = [
+            "println ( \"sum is {1 + 2\" );\n",
+            "func void f ( ) {\n}\nprintln ( \"f is {f ( )}\" );\n",
+            "println ( \"x is {x}\" );\n",
+            "int n = \"{1}\";\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string())?;
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::UnclosedInterpolation(..))
+        ));
+        assert!(matches!(
+            results[1],
+            Err(JuvinilError::InvalidInterpolation(..))
+        ));
+        assert!(matches!(
+            results[2],
+            Err(JuvinilError::UndeclaredVariable(..))
+        ));
+        assert!(matches!(
+            results[3],
+            Err(JuvinilError::InvalidValueType(..))
+        ));
+    }
+
+    #[test]
+    fn toolchain_ok() {
+        let toolchain = Toolchain::new("clang++")
+            .with_opt_level("0")
+            .unwrap()
+            .with_warning_flags(args("-Wall -Wextra"));
+
+        assert_eq!(toolchain.compiler(), "clang++");
+        assert_eq!(
+            toolchain.args("result.cpp", "result"),
+            args("-O0 -fwrapv -Wall -Wextra -o result result.cpp")
+        );
+        assert_eq!(
+            Toolchain::new("gcc")
+                .with_standard("c99")
+                .args("result.c", "result"),
+            args("-O2 -fwrapv -std=c99 -o result result.c")
+        );
+        assert_eq!(
+            toolchain::executable_path("result"),
+            format!("result{}", std::env::consts::EXE_SUFFIX)
+        );
+    }
+
+    #[test]
+    fn toolchain_err() {
+        assert!(matches!(
+            Toolchain::new("g++").with_opt_level("9"),
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            Toolchain::detect(Some("juvinil-missing-compiler")),
+            Err(JuvinilError::CompilerNotFound(..))
+        ));
+
+        //Compilation failures can only be checked with a compiler installed
+        let Ok(toolchain) = Toolchain::detect(None) else {
+            return;
+        };
+
+        let dir = std::env::temp_dir();
+        let source = dir.join("juvinil_toolchain_err.cpp");
+        let output = dir.join("juvinil_toolchain_err");
+        fs::write(&source, "int main() { return undeclared; }\n").unwrap();
+
+        let result = toolchain.compile(source.to_str().unwrap(), output.to_str().unwrap());
+        assert!(matches!(
+            result,
+            Err(JuvinilError::ToolError(_, ref stderr)) if stderr.contains("undeclared")
+        ));
+
+        //The assembler and the linker report their failures the same way
+        if let Ok(assembler) = Assembler::detect() {
+            let source = dir.join("juvinil_toolchain_err.s");
+            fs::write(&source, "    not_an_instruction\n").unwrap();
+
+            let result = assembler.build(source.to_str().unwrap(), output.to_str().unwrap());
+            assert!(matches!(
+                result,
+                Err(JuvinilError::ToolError(ref tool, _)) if tool == assembler.assembler()
+            ));
+        }
+
+        //C compilers report their failures the same way, naming themselves
+        let Ok(c) = Toolchain::detect_c(None) else {
+            return;
+        };
+
+        let source = dir.join("juvinil_toolchain_err.c");
+        fs::write(&source, "int main(void) { return undeclared; }\n").unwrap();
+
+        let result = c.compile(source.to_str().unwrap(), output.to_str().unwrap());
+        assert!(matches!(
+            result,
+            Err(JuvinilError::ToolError(ref tool, _)) if tool == c.compiler()
+        ));
+    }
+
+    #[test]
+    fn cli_ok() {
+        let cli = Cli::parse(&args("build test.jv -o out -D unused-variable --opt 0")).unwrap();
+        assert_eq!(cli.command, CliCommand::Build(Some("out".into())));
+        assert_eq!(cli.input, Input::File("test.jv".into()));
+        assert_eq!(cli.warning_args, args("-D unused-variable"));
+        assert_eq!(cli.opt_level, Some("0".into()));
+
+        //Everything after `--` goes to the program, even flags
+        let cli = Cli::parse(&args("run - --pause --timeout 3 -- first -W error")).unwrap();
+        assert_eq!(cli.command, CliCommand::Run(args("first -W error")));
+        assert_eq!(cli.timeout, Some(3));
+        assert_eq!(cli.input, Input::Stdin);
+        assert!(cli.pause_on_exit && cli.warning_args.is_empty());
+
+        let cli = Cli::parse(&args("run test.jv --target interpreter")).unwrap();
+        assert_eq!(cli.target, Target::Interpreter);
+
+        let cli = Cli::parse(&args("run test.jv --target vm")).unwrap();
+        assert_eq!(cli.target, Target::Vm);
+
+        let cli = Cli::parse(&args("run test.jv --target c --cc clang --timeout 2")).unwrap();
+        assert_eq!((cli.target, cli.cc), (Target::C, Some("clang".into())));
+
+        let cli = Cli::parse(&args("emit test.jv --stage c")).unwrap();
+        assert_eq!(cli.command, CliCommand::Emit(Stage::C, None));
+
+        let cli = Cli::parse(&args("build test.jv --target asm -o out")).unwrap();
+        assert_eq!(
+            (cli.command, cli.target),
+            (CliCommand::Build(Some("out".into())), Target::Asm)
+        );
+
+        let cli = Cli::parse(&args("emit test.jv --stage asm")).unwrap();
+        assert_eq!(cli.command, CliCommand::Emit(Stage::Asm, None));
+
+        let cli = Cli::parse(&args("emit test.jv --stage wat -o out.wat")).unwrap();
+        assert_eq!(
+            cli.command,
+            CliCommand::Emit(Stage::Wat, Some("out.wat".into()))
+        );
+
+        let cli = Cli::parse(&args("build test.jv --target bytecode")).unwrap();
+        assert_eq!(cli.target, Target::Bytecode);
+        assert_eq!(
+            default_output(&cli.input, &None, file::EXTENSION),
+            "test.jvc"
+        );
+
+        let cli = Cli::parse(&args("exec test.jvc")).unwrap();
+        assert_eq!(cli.command, CliCommand::Exec);
+
+        let cli = Cli::parse(&args("emit test.jv --stage bytecode")).unwrap();
+        assert_eq!(cli.command, CliCommand::Emit(Stage::Bytecode, None));
+
+        let cli = Cli::parse(&args("emit test.jv --stage tokens")).unwrap();
+        assert_eq!(cli.command, CliCommand::Emit(Stage::Tokens, None));
+
+        let cli = Cli::parse(&args("emit test.jv")).unwrap();
+        assert_eq!(cli.command, CliCommand::Emit(Stage::Cpp, None));
+
+        assert_eq!(Cli::parse(&[]).unwrap().command, CliCommand::Help);
+        assert_eq!(
+            Cli::parse(&args("--help")).unwrap().command,
+            CliCommand::Help
+        );
+        assert_eq!(
+            default_executable(&Input::File("dir/test.jv".into()), &None),
+            toolchain::executable_path("test")
+        );
+    }
+
+    #[test]
+    fn cli_err() {
+        let commands = [
+            "compile test.jv",
+            "check",
+            "check a.jv b.jv",
+            "emit test.jv --stage bytes",
+            "build test.jv --stage ast",
+            "check test.jv --unknown",
+            "build test.jv -o",
+            "build test.jv -- args",
+            "build test.jv --timeout 5",
+            "run test.jv --timeout soon",
+            "build test.jv --target interpreter",
+            "run test.jv --target interpreter --timeout 1",
+            "run test.jv --target vm --timeout 1",
+            "run test.jv --target interpreter -- a",
+            "run test.jv --target vm -- a b",
+            "run test.jv --target bytecode",
+            "exec test.jvc -o out",
+            "emit test.jv --target c",
+            "check test.jv --target asm",
+        ];
+
+        let results: Vec<JuvinilResult<Cli>> = commands
+            .iter()
+            .map(|command| Cli::parse(&args(command)))
+            .collect();
+
+        assert!(matches!(results[0], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[1], Err(JuvinilError::MissingInput)));
+        assert!(matches!(results[2], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[3], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[4], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[5], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[6], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[7], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[8], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(results[9], Err(JuvinilError::InvalidArgument(..))));
+        assert!(matches!(
+            results[10],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[11],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[12],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[13],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[14],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[15],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+        assert!(matches!(
+            results[16],
+            Err(JuvinilError::InvalidArgument(..))
+        ));
+
+        for result in &results {
+            assert_eq!(exit_code(result.as_ref().unwrap_err()), 2);
+        }
+    }
+
+    #[test]
+    fn exit_status_ok() {
+        let file_content = fs::read_to_string("test_inputs/exit.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let mut parser = Parser::new(tokens.unwrap()).unwrap();
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert!(parser.diagnostics().is_empty());
+
+        //A return without a value ends the program with status 0
+        let code = CppGenerator::new().generate(&result.unwrap());
+        assert!(code.contains("exit(2);"));
+        assert!(code.contains("        return 0;\n"));
+        assert!(code.contains("return total % 5;"));
+    }
+
+    #[test]
+    fn exit_status_err() {
+        let sources = [
+            "return \"done\";\n",
+            "return true;\n",
+            "exit ( \"1\" );\n",
+            "exit ( );\n",
+            "func void exit ( int code ) {\n}\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                Parser::new(tokens).unwrap().parse()
+            })
+            .collect();
+
+        assert!(matches!(
+            results[0],
+            Err(JuvinilError::InvalidReturnType(..))
+        ));
+        assert!(matches!(
+            results[1],
+            Err(JuvinilError::InvalidReturnType(..))
+        ));
+        assert!(matches!(
+            results[2],
+            Err(JuvinilError::InvalidParamType(..))
+        ));
+        assert!(matches!(
+            results[3],
+            Err(JuvinilError::InvalidParamCount(..))
+        ));
+        assert!(matches!(
+            results[4],
+            Err(JuvinilError::DuplicateFunction(..))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn execute_status() {
+        assert_eq!(execute("sh", &args("-c true"), None).unwrap(), 0);
+        assert_eq!(
+            execute("sh", &["-c".into(), "exit 3".into()], Some(5)).unwrap(),
+            3
+        );
+
+        let killed = execute("sh", &["-c".into(), "kill -9 $$".into()], None);
+        assert!(matches!(killed, Err(JuvinilError::ProgramSignal(9))));
+        assert_eq!(exit_code(&killed.unwrap_err()), 137);
+
+        let timed_out = execute("sleep", &args("5"), Some(0));
+        assert!(matches!(timed_out, Err(JuvinilError::ProgramTimeout(0))));
+        assert_eq!(exit_code(&timed_out.unwrap_err()), 124);
+    }
+
+    //Parses a file of `test_inputs` and interprets it with the given input
+    fn interpret(file_path: &str, input: &str) -> JuvinilResult<(i32, String)> {
+        let file_content = fs::read_to_string(file_path).unwrap();
+        let tokens = lex::tokenize(file_content)?;
+        let program = Parser::new(tokens)?.parse()?;
+
+        interpreter::run_captured(&program, input)
+    }
+
+    #[test]
+    fn interpreter_ok() {
+        let result = interpret("test_inputs/recursion.jv", "");
+
+        assert!(result.is_ok(), "Should be OK");
+        assert_eq!(
+            result.unwrap(),
+            (
+                0,
+                "0 1 1 2 3 5 8 13 21 34 \nsum: 05050|+7|-3  |ab\n".to_string()
+            )
+        );
+
+        let (status, output) = interpret("test_inputs/print.jv", "").unwrap();
+        assert_eq!(status, 0);
+        assert!(output.starts_with("count: 3\ndone: true, name: juvinil\n"));
+        assert!(output.contains("juvinil |    3|%\n"));
+
+        let (_, output) = interpret("test_inputs/interpolation.jv", "").unwrap();
+        assert!(output.starts_with("sum is 5, even false, {literal} 23\n"));
+
+        let (status, output) = interpret("test_inputs/exit.jv", "").unwrap();
+        assert_eq!((status, output.as_str()), (2, "total is 7\n"));
+    }
+
+    #[test]
+    fn interpreter_input_ok() {
+        let source = "int n = read_int ( );\nboolean ok = input_ok ( );\nstring rest = read_line ( );\nboolean b = read_bool ( );\nprintln ( n , ok , \"[\" , rest , \"]\" , b , input_ok ( ) );\nint m = read_int ( );\nprintln ( m , input_ok ( ) , input_eof ( ) );\n";
+        let tokens = lex::tokenize(source.into()).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let (_, output) = interpreter::run_captured(&program, "42 tail\n  yes\n").unwrap();
+        assert_eq!(output, "42true[ tail]falsefalse\n0falsetrue\n");
+    }
+
+    #[test]
+    fn interpreter_err() {
+        let sources = [
+            "int zero = parse_int ( \"0\" );\nprintln ( 10 / zero );\n",
+            "int zero = 0;\nprintln ( 10 % zero );\n",
+            "func int forever ( int n ) {\n    return forever ( n + 1 );\n}\nprintln ( forever ( 0 ) );\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                let program = Parser::new(tokens).unwrap().parse().unwrap();
+                interpreter::run_captured(&program, "")
+            })
+            .collect();
+
+        assert!(matches!(results[0], Err(JuvinilError::DivisionByZero(2))));
+        assert!(matches!(results[1], Err(JuvinilError::DivisionByZero(2))));
+        assert!(matches!(
+            results[2],
+            Err(JuvinilError::StackOverflow(ref name, 2)) if name == "forever"
+        ));
+    }
+
+    #[test]
+    fn deep_recursion_ok() {
+        //Ten thousand nested calls fit in the interpreter and the
+        //virtual machine, even in debug builds
+        let expected = (0, "50005000\n".to_string());
+        assert_eq!(interpret("test_inputs/deep.jv", "").unwrap(), expected);
+
+        let module = compile_bytecode("test_inputs/deep.jv").unwrap();
+        assert_eq!(vm::run_captured(&module, "").unwrap(), expected);
+    }
+
+    fn compile_bytecode(file_path: &str) -> JuvinilResult<Module> {
+        let file_content = fs::read_to_string(file_path).unwrap();
+        let tokens = lex::tokenize(file_content)?;
+        let program = Parser::new(tokens)?.parse()?;
+
+        Ok(compiler::compile(&program))
+    }
+
+    #[test]
+    fn vm_ok() {
+        let module = compile_bytecode("test_inputs/recursion.jv").unwrap();
+        assert_eq!(
+            vm::run_captured(&module, "").unwrap(),
+            (
+                0,
+                "0 1 1 2 3 5 8 13 21 34 \nsum: 05050|+7|-3  |ab\n".to_string()
+            )
+        );
+
+        let module = compile_bytecode("test_inputs/exit.jv").unwrap();
+        assert_eq!(
+            vm::run_captured(&module, "").unwrap(),
+            (2, "total is 7\n".to_string())
+        );
+
+        //Every sample behaves like it does in the interpreter,
+        //except the ones that never end or take too long
+        for entry in fs::read_dir("test_inputs").unwrap() {
+            let path = entry.unwrap().path();
+            let file_path = path.to_str().unwrap();
+            if file_path.ends_with("for.jv") || file_path.ends_with("benchmark.jv") {
+                continue;
+            }
+
+            let Ok(module) = compile_bytecode(file_path) else {
+                continue;
+            };
+            let input = "5 abc\ntrue\n";
+
+            assert_eq!(
+                vm::run_captured(&module, input).unwrap(),
+                interpret(file_path, input).unwrap(),
+                "{} should run like in the interpreter",
+                file_path
+            );
+        }
+    }
+
+    #[test]
+    fn vm_err() {
+        let sources = [
+            "int zero = parse_int ( \"0\" );\nprintln ( 10 / zero );\n",
+            "int zero = 0;\nprintln ( 10 % zero );\n",
+            "func int forever ( int n ) {\n    return forever ( n + 1 );\n}\nprintln ( forever ( 0 ) );\n",
+        ];
+
+        let results: Vec<JuvinilResult<_>> = sources
+            .iter()
+            .map(|source| {
+                let tokens = lex::tokenize(source.to_string()).unwrap();
+                let program = Parser::new(tokens).unwrap().parse().unwrap();
+                vm::run_captured(&compiler::compile(&program), "")
+            })
+            .collect();
+
+        assert!(matches!(results[0], Err(JuvinilError::DivisionByZero(2))));
+        assert!(matches!(results[1], Err(JuvinilError::DivisionByZero(2))));
+        assert!(matches!(
+            results[2],
+            Err(JuvinilError::StackOverflow(ref name, 2)) if name == "forever"
+        ));
+    }
+
+    #[test]
+    fn byte_strings_ok() {
+        //Strings are bytes, so cutting a character in half keeps
+        //its bytes instead of replacing it, like the native backends
+        let (_, output) = interpret("test_inputs/bytes.jv", "").unwrap();
+        assert_eq!(
+            output,
+            "1 6 2\ntrue héllo\ntrue true\n[é  ][h\u{fffd}][   é][hé]\né\n"
+        );
+    }
+
+    #[test]
+    fn arithmetic_overflow_ok() {
+        //Overflows wrap around, and the other backends are
+        //compared with these results by the backend tests
+        let (status, output) = interpret("test_inputs/overflow.jv", "").unwrap();
+        assert_eq!(
+            (status, output.as_str()),
+            (
+                0,
+                "48\n-2147483648 -2147483648 2147483647\n-2 1 0\n-2147483648 2147483647\n"
+            )
+        );
+    }
+
+    #[test]
+    fn division_overflow_ok() {
+        //Dividing the smallest int by -1 wraps, and the other backends
+        //are compared with these results by the backend tests
+        let (status, output) = interpret("test_inputs/division.jv", "").unwrap();
+        assert_eq!(
+            (status, output.as_str()),
+            (0, "-2147483648 0\n-3 1 -3 -1\n-7 0\n")
+        );
+
+        //Only divisors that aren't literals can be -1
+        let file_content = fs::read_to_string("test_inputs/division.jv").unwrap();
+        let tokens = lex::tokenize(file_content).unwrap();
+        let program = Parser::new(tokens).unwrap().parse().unwrap();
+
+        let code = CppGenerator::new().generate(&program);
+        assert!(code.contains("jv::div(smallest, divisor)"));
+        assert!(code.contains("(-7) / 2, \" \", (-7) % 2"));
+
+        let code = CGenerator::new().generate(&program);
+        assert!(code.contains("jv_rem(smallest, divisor)"));
+        assert!(WatGenerator::new()
+            .generate(&program)
+            .contains("call $jv_div"));
+    }
+
+    #[test]
+    fn disassembler_ok() {
+        let module = compile_bytecode("test_inputs/recursion.jv").unwrap();
+        let listing = disassembler::disassemble(&module);
+
+        assert!(listing.starts_with("constants:\n    0    Int(0)\n"));
+        assert!(listing.contains("\nfn main (params: 0, locals: 1):\n"));
+        assert!(listing.contains("\nfn fib (params: 1, locals: 1):\n"));
+        assert!(listing.contains("0003  line 25   Call(1)              ; fib\n"));
+        assert!(listing.contains("0005  line 25   CallBuiltin(0, 2)    ; print\n"));
+    }
+
+    #[test]
+    fn bytecode_file_ok() {
+        for entry in fs::read_dir("test_inputs").unwrap() {
+            let path = entry.unwrap().path();
+            let Ok(module) = compile_bytecode(path.to_str().unwrap()) else {
+                continue;
+            };
+
+            let bytes = file::encode(&module);
+            assert!(bytes.starts_with(file::MAGIC));
+            assert_eq!(file::decode(&bytes).unwrap(), module, "{:?}", path);
+        }
+
+        let module = compile_bytecode("test_inputs/recursion.jv").unwrap();
+        let loaded = file::decode(&file::encode(&module)).unwrap();
+        assert_eq!(
+            vm::run_captured(&loaded, "").unwrap(),
+            vm::run_captured(&module, "").unwrap()
+        );
+    }
+
+    #[test]
+    fn bytecode_file_err() {
+        let module = compile_bytecode("test_inputs/recursion.jv").unwrap();
+        let bytes = file::encode(&module);
+
+        let mut newer = bytes.clone();
+        newer[4] += 1;
+
+        let mut damaged = bytes.clone();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xff;
+
+        //Files with a valid checksum are still checked, so
+        //broken code can't crash the virtual machine
+        let mut bad_jump = module.clone();
+        bad_jump.functions[0].code[0] = Op::Jump(1000);
+
+        let mut underflow = module.clone();
+        underflow.functions[0].code.insert(0, Op::Pop);
+        underflow.functions[0].lines.insert(0, 1);
+
+        let mut past_end = module.clone();
+        past_end.functions[0].code.pop();
+        past_end.functions[0].lines.pop();
+
+        let mut oversized = module.clone();
+        oversized.functions[0].locals = 0x7fffffff;
+
+        let results = [
+            file::decode(b"not bytecode"),
+            file::decode(&newer),
+            file::decode(&damaged),
+            file::decode(&bytes[..bytes.len() / 2]),
+            file::decode(&file::encode(&bad_jump)),
+            file::decode(&file::encode(&underflow)),
+            file::decode(&file::encode(&past_end)),
+            file::decode(&file::encode(&oversized)),
+        ];
+
+        assert!(matches!(results[0], Err(JuvinilError::InvalidBytecode(..))));
+        assert!(matches!(
+            results[1],
+            Err(JuvinilError::IncompatibleBytecode(2, 1))
+        ));
+        for result in &results[2..] {
+            assert!(matches!(result, Err(JuvinilError::InvalidBytecode(..))));
+        }
+    }
+
+    #[test]
+    fn bytecode_type_confusion_err() {
+        let builtin = |name: &str| BUILTINS.iter().position(|b| *b == name).unwrap() as u32;
+        let constants = vec![
+            Value::Str("a".into()),
+            Value::Int(1),
+            Value::Str("%d".into()),
+        ];
+
+        //The verifier only counts values, so these pass it
+        //with values of the wrong type for their instructions
+        let programs = [
+            vec![Op::Const(0), Op::Const(0), Op::Add],
+            vec![Op::Const(1), Op::Const(0), Op::Lt],
+            vec![Op::Const(1), Op::Const(0), Op::Concat],
+            vec![Op::Const(0), Op::Not],
+            vec![Op::Const(1), Op::JumpIfFalse(2), Op::Const(1)],
+            vec![Op::Const(1), Op::CallBuiltin(builtin("len"), 1)],
+            vec![
+                Op::Const(2),
+                Op::Const(0),
+                Op::CallBuiltin(builtin("printf"), 2),
+            ],
+            vec![Op::Const(0), Op::CallBuiltin(builtin("exit"), 1)],
+        ];
+
+        for mut code in programs {
+            code.extend([Op::Pop, Op::ReturnVoid]);
+            let module = Module {
+                constants: constants.clone(),
+                globals: Vec::new(),
+                functions: vec![Function {
+                    name: "main".into(),
+                    arity: 0,
+                    locals: 0,
+                    lines: vec![1; code.len()],
+                    code,
+                }],
+            };
+
+            let loaded = file::decode(&file::encode(&module)).unwrap();
+            assert!(
+                matches!(
+                    vm::run_captured(&loaded, ""),
+                    Err(JuvinilError::InvalidBytecode(..))
+                ),
+                "{:?}",
+                loaded.functions[0].code
+            );
+        }
+    }
+
+    fn args(flags: &str) -> Vec<String> {
+        flags.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn warning_config_levels() {
+        let config = WarningConfig::from_args(&args("-A unused-function -D unused-variable"));
+
+        assert!(config.is_ok(), "Should be OK");
+
+        let config = config.unwrap();
+        assert_eq!(config.level("unused-function"), WarningLevel::Allow);
+        assert_eq!(config.level("unused-variable"), WarningLevel::Deny);
+        assert_eq!(config.level("unread-variable"), WarningLevel::Warn);
+
+        assert!(
+            matches!(
+                WarningConfig::from_args(&args("-D no-such-warning")),
+                Err(JuvinilError::UnknownWarning(..))
+            ),
+            "Should be an UnknownWarning error"
+        );
+        assert!(
+            matches!(
+                WarningConfig::from_args(&args("-D")),
+                Err(JuvinilError::InvalidArgument(..))
+            ),
+            "Should be an InvalidArgument error"
+        );
+    }
+
+    #[test]
+    fn allowed_warnings_not_reported() {
+        let file_content = fs::read_to_string("test_inputs/unused.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let config = WarningConfig::from_args(&args("-A unused-function")).unwrap();
+        let mut parser = Parser::new(tokens.unwrap())
+            .unwrap()
+            .with_warning_config(config);
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "Should be OK");
+        assert_eq!(parser.diagnostics().len(), 4);
+        assert!(parser
+            .diagnostics()
+            .iter()
+            .all(|d| d.lint.as_deref() != Some("unused-function")));
+    }
+
+    #[test]
+    fn warnings_as_errors_err() {
+        let file_content = fs::read_to_string("test_inputs/unused.jv").unwrap();
+
+        let tokens = lex::tokenize(file_content);
+
+        assert!(tokens.is_ok(), "Should be OK");
+
+        let config = WarningConfig::from_args(&args("-W error -W unused-function")).unwrap();
+        let mut parser = Parser::new(tokens.unwrap())
+            .unwrap()
+            .with_warning_config(config);
+        let result = parser.parse();
+
+        assert!(
+            matches!(result, Err(JuvinilError::DeniedWarnings(4))),
+            "Should be a DeniedWarnings error"
+        );
+
+        let errors = parser
+            .diagnostics()
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        assert_eq!(errors, 4);
+        assert_eq!(parser.diagnostics().len(), 5);
+    }
 }
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+
+use crate::syntax_analysis::ast::{Expr, ExprKind, FuncDecl, Stmt, StmtKind};
+
+//Something that happens to a variable inside a basic block.
+//Variables are identified by a unique id instead of their name,
+//since different scopes can declare variables with the same name
+#[derive(Debug, Clone)]
+pub enum VarEvent {
+    Def(usize),                //The variable was assigned
+    Use(usize, String, usize), //The variable was read (id, name and line)
+}
+
+//A sequence of variable events that always run together,
+//followed by the blocks control can flow to
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock {
+    pub events: Vec<VarEvent>,
+    pub successors: Vec<usize>,
+}
+
+//Control flow graph of a function body or of the main program.
+//The entry is always the first block
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub exit: usize,
+    pub end: usize, //Block that falls through to the exit at the end of the body
+    pub var_count: usize,
+}
+
+impl ControlFlowGraph {
+    pub const ENTRY: usize = 0;
+
+    //Builds the graph of a function, whose parameters
+    //are assigned as soon as the function is entered
+    pub fn from_function(function: &FuncDecl) -> Self {
+        let mut builder = CfgBuilder::new();
+
+        for (_, param_name) in &function.params {
+            let id = builder.declare(param_name);
+            builder.push_event(VarEvent::Def(id));
+        }
+
+        builder.stmts(&function.body);
+        builder.finish()
+    }
+
+    //Builds the graph of the main program
+    pub fn from_stmts(stmts: &[Stmt]) -> Self {
+        let mut builder = CfgBuilder::new();
+        builder.stmts(stmts);
+        builder.finish()
+    }
+
+    //List the predecessors of every block
+    pub fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            for successor in &block.successors {
+                predecessors[*successor].push(index);
+            }
+        }
+
+        predecessors
+    }
+
+    //Flag every block that control can flow to from the entry
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut pending = vec![Self::ENTRY];
+
+        while let Some(index) = pending.pop() {
+            if !reachable[index] {
+                reachable[index] = true;
+                pending.extend(&self.blocks[index].successors);
+            }
+        }
+
+        reachable
+    }
+}
+
+struct CfgBuilder {
+    blocks: Vec<BasicBlock>,
+    current: usize,                      //Block receiving new events
+    exit: usize,                         //Block reached by `return` and by the end of the body
+    scopes: Vec<HashMap<String, usize>>, //Variables visible in each nested scope
+    var_count: usize,
+    loops: Vec<(usize, usize)>, //`continue` and `break` targets of the enclosing loops
+}
+
+impl CfgBuilder {
+    fn new() -> Self {
+        let mut builder = CfgBuilder {
+            blocks: Vec::new(),
+            current: 0,
+            exit: 0,
+            scopes: vec![HashMap::new()],
+            var_count: 0,
+            loops: Vec::new(),
+        };
+
+        builder.current = builder.new_block();
+        builder.exit = builder.new_block();
+
+        builder
+    }
+
+    fn finish(mut self) -> ControlFlowGraph {
+        self.edge(self.current, self.exit);
+
+        ControlFlowGraph {
+            blocks: self.blocks,
+            exit: self.exit,
+            end: self.current,
+            var_count: self.var_count,
+        }
+    }
+
+    fn new_block(&mut self) -> usize {
+        self.blocks.push(BasicBlock::default());
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize) {
+        self.blocks[from].successors.push(to);
+    }
+
+    fn push_event(&mut self, event: VarEvent) {
+        self.blocks[self.current].events.push(event);
+    }
+
+    //After a `break`, `continue`, `return` or `exit`, the following
+    //statements go into a block that nothing flows into
+    fn start_unreachable_block(&mut self) {
+        self.current = self.new_block();
+    }
+
+    //Give a new id to a variable declared in the innermost scope
+    fn declare(&mut self, var_name: &str) -> usize {
+        let id = self.var_count;
+        self.var_count += 1;
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(var_name.into(), id);
+        }
+
+        id
+    }
+
+    //Find the id of a variable, starting from the innermost scope
+    fn resolve(&self, var_name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(var_name).copied())
+    }
+
+    fn use_var(&mut self, var_name: &str, line: usize) {
+        if let Some(id) = self.resolve(var_name) {
+            self.push_event(VarEvent::Use(id, var_name.into(), line));
+        }
+    }
+
+    fn def_var(&mut self, var_name: &str) {
+        if let Some(id) = self.resolve(var_name) {
+            self.push_event(VarEvent::Def(id));
+        }
+    }
+
+    //Record every variable read by an expression.
+    //Expressions can't assign variables, except for `++` and `--`
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Int(_) | ExprKind::Bool(_) | ExprKind::Str(_) => {}
+            ExprKind::Var(var_name) => self.use_var(var_name, expr.line),
+            ExprKind::Call(_, args) => args.iter().for_each(|arg| self.expr(arg)),
+            ExprKind::Unary(operator, operand) => {
+                self.expr(operand);
+
+                if let ExprKind::Var(var_name) = &operand.kind {
+                    if operator == "++" || operator == "--" {
+                        self.def_var(var_name);
+                    }
+                }
+            }
+            ExprKind::Binary(_, left, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                self.expr(condition);
+                self.expr(then_expr);
+                self.expr(else_expr);
+            }
+            ExprKind::Interpolation(parts) => parts.iter().for_each(|part| self.expr(part)),
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    //Statements inside brackets live in their own scope
+    fn scoped_stmts(&mut self, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        self.stmts(stmts);
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(_, _, declarators) => {
+                //The variable exists before its initializer runs,
+                //so the initializer can't read a shadowed variable
+                for (var_name, initializer) in declarators {
+                    let id = self.declare(var_name);
+
+                    if let Some(value) = initializer {
+                        self.expr(value);
+                        self.push_event(VarEvent::Def(id));
+                    }
+                }
+            }
+            StmtKind::Asgn(var_name, operator, value) => {
+                self.expr(value);
+
+                //`+=` and `-=` read the variable before assigning it
+                if operator != "=" {
+                    self.use_var(var_name, stmt.line);
+                }
+
+                self.def_var(var_name);
+            }
+            StmtKind::Call(call) => {
+                self.expr(call);
+
+                //`exit` never returns, so it ends the block like `return`
+                if matches!(&call.kind, ExprKind::Call(name, _) if name == "exit") {
+                    self.edge(self.current, self.exit);
+                    self.start_unreachable_block();
+                }
+            }
+            StmtKind::Block(stmts) => self.scoped_stmts(stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                self.expr(condition);
+                let condition_block = self.current;
+                let join_block = self.new_block();
+
+                self.current = self.new_block();
+                self.edge(condition_block, self.current);
+                self.scoped_stmts(then_block);
+                self.edge(self.current, join_block);
+
+                match else_block {
+                    Some(else_block) => {
+                        self.current = self.new_block();
+                        self.edge(condition_block, self.current);
+                        self.scoped_stmts(else_block);
+                        self.edge(self.current, join_block);
+                    }
+                    None => self.edge(condition_block, join_block),
+                }
+
+                self.current = join_block;
+            }
+            StmtKind::While(condition, body) => self.loop_stmt(condition, body),
+            StmtKind::For(init, condition, body) => {
+                self.stmt(init);
+                self.loop_stmt(condition, body);
+            }
+            StmtKind::DoWhile(body, condition) => {
+                let body_block = self.new_block();
+                let condition_block = self.new_block();
+                let exit_block = self.new_block();
+
+                self.edge(self.current, body_block);
+                self.current = body_block;
+
+                self.loops.push((condition_block, exit_block));
+                self.scoped_stmts(body);
+                self.loops.pop();
+
+                self.edge(self.current, condition_block);
+                self.current = condition_block;
+                self.expr(condition);
+
+                if constant_condition(condition) != Some(false) {
+                    self.edge(condition_block, body_block);
+                }
+
+                if constant_condition(condition) != Some(true) {
+                    self.edge(condition_block, exit_block);
+                }
+
+                self.current = exit_block;
+            }
+            StmtKind::Break => {
+                if let Some(&(_, break_target)) = self.loops.last() {
+                    self.edge(self.current, break_target);
+                }
+
+                self.start_unreachable_block();
+            }
+            StmtKind::Continue => {
+                if let Some(&(continue_target, _)) = self.loops.last() {
+                    self.edge(self.current, continue_target);
+                }
+
+                self.start_unreachable_block();
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+
+                self.edge(self.current, self.exit);
+                self.start_unreachable_block();
+            }
+        }
+    }
+
+    //Loops that check their condition before each iteration (`while` and `for`)
+    fn loop_stmt(&mut self, condition: &Expr, body: &[Stmt]) {
+        let header_block = self.new_block();
+        let body_block = self.new_block();
+        let exit_block = self.new_block();
+
+        self.edge(self.current, header_block);
+        self.current = header_block;
+        self.expr(condition);
+
+        //A constant condition always takes the same branch,
+        //so `while (true)` is only left through a `break`
+        if constant_condition(condition) != Some(false) {
+            self.edge(header_block, body_block);
+        }
+
+        if constant_condition(condition) != Some(true) {
+            self.edge(header_block, exit_block);
+        }
+
+        self.current = body_block;
+
+        self.loops.push((header_block, exit_block));
+        self.scoped_stmts(body);
+        self.loops.pop();
+
+        self.edge(self.current, header_block);
+        self.current = exit_block;
+    }
+}
+
+//The value of a condition that is a `true` or `false` literal
+fn constant_condition(condition: &Expr) -> Option<bool> {
+    match condition.kind {
+        ExprKind::Bool(value) => Some(value),
+        _ => None,
+    }
+}
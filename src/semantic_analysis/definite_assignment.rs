@@ -0,0 +1,99 @@
+use crate::{
+    error::{JuvinilError, JuvinilResult},
+    syntax_analysis::ast::Program,
+};
+
+use super::cfg::{ControlFlowGraph, VarEvent};
+
+//Checks that every variable is assigned before being read,
+//in the global declarations, in every function and in the main program.
+//Globals are always initialized, so they are never reported elsewhere
+pub fn check(program: &Program) -> JuvinilResult<()> {
+    check_graph(&ControlFlowGraph::from_stmts(&program.globals))?;
+
+    for function in &program.functions {
+        check_graph(&ControlFlowGraph::from_function(function))?;
+    }
+
+    check_graph(&ControlFlowGraph::from_stmts(&program.body))
+}
+
+//Forward dataflow analysis over the control flow graph.
+//A variable is definitely assigned at the start of a block only
+//if it is assigned at the end of every predecessor, so a read is
+//only valid if every path from the entry assigns the variable first.
+fn check_graph(cfg: &ControlFlowGraph) -> JuvinilResult<()> {
+    let predecessors = cfg.predecessors();
+
+    //Blocks start with every variable assigned, which is the identity
+    //of the intersection. Blocks that can't be reached keep this value,
+    //so reads in dead code are never reported
+    let mut assigned_out: Vec<Vec<bool>> = vec![vec![true; cfg.var_count]; cfg.blocks.len()];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (index, block) in cfg.blocks.iter().enumerate() {
+            let mut assigned = assigned_in(cfg, &predecessors, &assigned_out, index);
+
+            for event in &block.events {
+                if let VarEvent::Def(id) = event {
+                    assigned[*id] = true;
+                }
+            }
+
+            if assigned != assigned_out[index] {
+                assigned_out[index] = assigned;
+                changed = true;
+            }
+        }
+    }
+
+    //With the fixed point computed, replay each block to find
+    //reads of variables that aren't assigned yet
+    let mut first_error: Option<(String, usize)> = None;
+
+    for (index, block) in cfg.blocks.iter().enumerate() {
+        let mut assigned = assigned_in(cfg, &predecessors, &assigned_out, index);
+
+        for event in &block.events {
+            match event {
+                VarEvent::Def(id) => assigned[*id] = true,
+                VarEvent::Use(id, var_name, line) => {
+                    let is_earlier = first_error.as_ref().is_none_or(|(_, l)| line < l);
+
+                    if !assigned[*id] && is_earlier {
+                        first_error = Some((var_name.clone(), *line));
+                    }
+                }
+            }
+        }
+    }
+
+    match first_error {
+        Some((var_name, line)) => Err(JuvinilError::UnassignedVariable(var_name, line)),
+        None => Ok(()),
+    }
+}
+
+//Variables assigned when entering a block
+fn assigned_in(
+    cfg: &ControlFlowGraph,
+    predecessors: &[Vec<usize>],
+    assigned_out: &[Vec<bool>],
+    index: usize,
+) -> Vec<bool> {
+    if index == ControlFlowGraph::ENTRY {
+        return vec![false; cfg.var_count];
+    }
+
+    let mut assigned = vec![true; cfg.var_count];
+    for predecessor in &predecessors[index] {
+        for (id, value) in assigned_out[*predecessor].iter().enumerate() {
+            assigned[id] &= value;
+        }
+    }
+
+    assigned
+}
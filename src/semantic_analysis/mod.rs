@@ -0,0 +1,3 @@
+pub mod cfg;
+pub mod definite_assignment;
+pub mod returns;
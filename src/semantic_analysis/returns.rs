@@ -0,0 +1,28 @@
+use crate::{
+    error::{JuvinilError, JuvinilResult},
+    syntax_analysis::ast::Program,
+};
+
+use super::cfg::ControlFlowGraph;
+
+//Checks that functions returning a value can't reach the end of
+//their body without a `return`. The end is unreachable when every
+//path returns first, or gets stuck in a loop without a `break`
+pub fn check(program: &Program) -> JuvinilResult<()> {
+    for function in &program.functions {
+        if function.return_type == "void" {
+            continue;
+        }
+
+        let cfg = ControlFlowGraph::from_function(function);
+        if cfg.reachable()[cfg.end] {
+            return Err(JuvinilError::MissingReturn(
+                function.func_name.clone(),
+                function.return_type.clone(),
+                function.line,
+            ));
+        }
+    }
+
+    Ok(())
+}
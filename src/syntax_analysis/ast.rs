@@ -0,0 +1,190 @@
+//An expression node built by the parser.
+//Every expression carries the Juvinil type it evaluates to,
+//which is resolved while parsing
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub jv_type: String, //Type the expression evaluates to
+    pub line: usize,     //Line where the expression starts
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Int(i32),                                 //Integer literal
+    Bool(bool),                               //`true` or `false`
+    Str(String),                              //String literal (without quotes)
+    Var(String),                              //Variable read
+    Call(String, Vec<Expr>),                  //Function call with its arguments
+    Unary(String, Box<Expr>),                 //Unary operator and its operand
+    Binary(String, Box<Expr>, Box<Expr>),     //Binary operator and its operands
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>), //Condition, then and else values
+    Interpolation(Vec<Expr>), //String fragments alternating with embedded expressions
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, jv_type: &str, line: usize) -> Self {
+        Expr {
+            kind,
+            jv_type: jv_type.into(),
+            line,
+        }
+    }
+
+    //Checks if the expression, or any expression inside it, matches `predicate`
+    pub fn any(&self, predicate: &dyn Fn(&Expr) -> bool) -> bool {
+        if predicate(self) {
+            return true;
+        }
+
+        match &self.kind {
+            ExprKind::Int(_) | ExprKind::Bool(_) | ExprKind::Str(_) | ExprKind::Var(_) => false,
+            ExprKind::Call(_, args) => args.iter().any(|arg| arg.any(predicate)),
+            ExprKind::Unary(_, operand) => operand.any(predicate),
+            ExprKind::Binary(_, left, right) => left.any(predicate) || right.any(predicate),
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                condition.any(predicate) || then_expr.any(predicate) || else_expr.any(predicate)
+            }
+            ExprKind::Interpolation(parts) => parts.iter().any(|part| part.any(predicate)),
+        }
+    }
+
+    //Checks if the expression only has literals, so
+    //its value doesn't depend on where it is evaluated
+    pub fn is_constant(&self) -> bool {
+        match &self.kind {
+            ExprKind::Int(_) | ExprKind::Bool(_) | ExprKind::Str(_) => true,
+            ExprKind::Var(_) | ExprKind::Call(..) => false,
+            ExprKind::Unary(operator, operand) => {
+                operator != "++" && operator != "--" && operand.is_constant()
+            }
+            ExprKind::Binary(_, left, right) => left.is_constant() && right.is_constant(),
+            ExprKind::Ternary(condition, then_expr, else_expr) => {
+                condition.is_constant() && then_expr.is_constant() && else_expr.is_constant()
+            }
+            ExprKind::Interpolation(parts) => parts.iter().all(Expr::is_constant),
+        }
+    }
+
+    //Checks if the expression is a `/` or `%` that can divide the smallest
+    //int by -1, which overflows. Literals are never negative, since `-1`
+    //is a negation, so only a divisor that isn't a literal can be -1
+    pub fn may_overflow_division(&self) -> bool {
+        match &self.kind {
+            ExprKind::Binary(operator, _, right) if operator == "/" || operator == "%" => {
+                !matches!(right.kind, ExprKind::Int(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+//A statement node built by the parser
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub line: usize, //Line where the statement starts
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
+    Decl(bool, String, Vec<(String, Option<Expr>)>), //Constant flag, type and declarators
+    Asgn(String, String, Expr),                      //Variable, operator and value
+    Call(Expr),                                      //Function call used as a statement
+    Block(Vec<Stmt>),                                //Statements inside brackets
+    If(Expr, Vec<Stmt>, Option<Vec<Stmt>>),          //Condition, then and else blocks
+    While(Expr, Vec<Stmt>),                          //Condition and body
+    DoWhile(Vec<Stmt>, Expr),                        //Body and condition
+    For(Box<Stmt>, Expr, Vec<Stmt>),                 //Initial assignment, condition and body
+    Break,
+    Continue,
+    Return(Option<Expr>), //Returned value, if any
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, line: usize) -> Self {
+        Stmt { kind, line }
+    }
+
+    //Checks if any expression of the statement, including
+    //the ones in nested statements, matches `predicate`
+    pub fn any_expr(&self, predicate: &dyn Fn(&Expr) -> bool) -> bool {
+        let any_in = |stmts: &[Stmt]| stmts.iter().any(|stmt| stmt.any_expr(predicate));
+
+        match &self.kind {
+            StmtKind::Decl(_, _, declarators) => declarators
+                .iter()
+                .any(|(_, value)| value.as_ref().is_some_and(|v| v.any(predicate))),
+            StmtKind::Asgn(_, _, value) | StmtKind::Call(value) => value.any(predicate),
+            StmtKind::Block(stmts) => any_in(stmts),
+            StmtKind::If(condition, then_block, else_block) => {
+                condition.any(predicate)
+                    || any_in(then_block)
+                    || else_block.as_deref().is_some_and(any_in)
+            }
+            StmtKind::While(condition, body) | StmtKind::DoWhile(body, condition) => {
+                condition.any(predicate) || any_in(body)
+            }
+            StmtKind::For(init, condition, body) => {
+                init.any_expr(predicate) || condition.any(predicate) || any_in(body)
+            }
+            StmtKind::Break | StmtKind::Continue => false,
+            StmtKind::Return(value) => value.as_ref().is_some_and(|v| v.any(predicate)),
+        }
+    }
+}
+
+//A function declaration, with its parameters
+//as (type, name) pairs and the statements of its body
+#[derive(Debug, Clone)]
+pub struct FuncDecl {
+    pub return_type: String,
+    pub func_name: String,
+    pub params: Vec<(String, String)>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+}
+
+//The whole parsed file: every global declaration and
+//function declaration, followed by the statements of the main program
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub globals: Vec<Stmt>, //Declarations visible to functions and to the main program
+    pub functions: Vec<FuncDecl>,
+    pub body: Vec<Stmt>,
+}
+
+impl Program {
+    //Checks if any expression of the program matches `predicate`
+    pub fn any_expr(&self, predicate: &dyn Fn(&Expr) -> bool) -> bool {
+        self.globals
+            .iter()
+            .chain(self.functions.iter().flat_map(|f| f.body.iter()))
+            .chain(self.body.iter())
+            .any(|stmt| stmt.any_expr(predicate))
+    }
+
+    //Checks if the program calls the function with the given name
+    pub fn calls(&self, func_name: &str) -> bool {
+        self.any_expr(&|expr| matches!(&expr.kind, ExprKind::Call(name, _) if name == func_name))
+    }
+
+    //Checks if any division of the program can overflow
+    pub fn may_overflow_division(&self) -> bool {
+        self.any_expr(&Expr::may_overflow_division)
+    }
+
+    //Index of the overload of a function that takes the types of
+    //the arguments of a call. The parser already resolved the call,
+    //so exactly one overload matches
+    pub fn function_index(&self, func_name: &str, args: &[Expr]) -> Option<usize> {
+        self.functions.iter().position(|function| {
+            function.func_name == func_name
+                && function.params.len() == args.len()
+                && function
+                    .params
+                    .iter()
+                    .zip(args)
+                    .all(|((param_type, _), arg)| *param_type == arg.jv_type)
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use crate::error::{JuvinilError, JuvinilResult};
+
+use super::ast::{Expr, ExprKind};
+
+//Functions provided by the language. They can't be declared by
+//the program, and their calls are checked here instead of
+//against a function declaration
+pub const BUILTINS: [&str; 15] = [
+    "print",
+    "println",
+    "printf",
+    "read_int",
+    "read_line",
+    "read_bool",
+    "input_ok",
+    "input_eof",
+    "len",
+    "substr",
+    "contains",
+    "to_string",
+    "parse_int",
+    "is_int",
+    "exit",
+];
+
+//Builtins that need helper functions in the generated code
+pub const RUNTIME_BUILTINS: [&str; 8] = [
+    "read_int",
+    "read_line",
+    "read_bool",
+    "input_ok",
+    "input_eof",
+    "substr",
+    "parse_int",
+    "is_int",
+];
+
+//Parameter types and return type of the builtins
+//that take a fixed list of parameters
+const SIGNATURES: [(&str, &[&str], &str); 12] = [
+    ("read_int", &[], "int"),
+    ("read_line", &[], "string"),
+    ("read_bool", &[], "boolean"),
+    ("input_ok", &[], "boolean"),
+    ("input_eof", &[], "boolean"),
+    ("len", &["string"], "int"),
+    ("substr", &["string", "int", "int"], "string"),
+    ("contains", &["string", "string"], "boolean"),
+    ("to_string", &["int"], "string"),
+    ("parse_int", &["string"], "int"),
+    ("is_int", &["string"], "boolean"),
+    ("exit", &["int"], "void"),
+];
+
+pub fn is_builtin(func_name: &str) -> bool {
+    BUILTINS.contains(&func_name)
+}
+
+//Number of parameters of a builtin, if it takes a fixed list of them
+pub fn param_count(func_name: &str) -> Option<usize> {
+    SIGNATURES
+        .iter()
+        .find(|(name, ..)| *name == func_name)
+        .map(|(_, params, _)| params.len())
+}
+
+//Checks the arguments of a call to a builtin function,
+//returning the type of the value it returns
+pub fn check_call(func_name: &str, args: &[Expr], line: usize) -> JuvinilResult<String> {
+    match func_name {
+        //`print` and `println` take any number of values of any type
+        "print" | "println" => {
+            for arg in args {
+                if arg.jv_type == "void" {
+                    return Err(JuvinilError::InvalidParamType(
+                        func_name.into(),
+                        "int, boolean or string".into(),
+                        arg.jv_type.clone(),
+                        arg.line,
+                    ));
+                }
+            }
+
+            Ok("void".into())
+        }
+        "printf" => {
+            check_printf(args, line)?;
+            Ok("void".into())
+        }
+        _ => check_signature(func_name, args, line),
+    }
+}
+
+//Checks a call to a builtin with a fixed list of parameters
+fn check_signature(func_name: &str, args: &[Expr], line: usize) -> JuvinilResult<String> {
+    let Some((_, params, return_type)) = SIGNATURES.iter().find(|(name, ..)| *name == func_name)
+    else {
+        return Err(JuvinilError::UndeclaredFunction(func_name.into(), line));
+    };
+
+    if params.len() != args.len() {
+        return Err(JuvinilError::InvalidParamCount(
+            func_name.into(),
+            params.len(),
+            args.len(),
+            line,
+        ));
+    }
+
+    for (param_type, arg) in params.iter().zip(args) {
+        if *param_type != arg.jv_type {
+            return Err(JuvinilError::InvalidParamType(
+                func_name.into(),
+                param_type.to_string(),
+                arg.jv_type.clone(),
+                arg.line,
+            ));
+        }
+    }
+
+    Ok(return_type.to_string())
+}
+
+//The format string of `printf` must be a string literal, and each
+//of its specifiers must match the type of the argument it formats
+fn check_printf(args: &[Expr], line: usize) -> JuvinilResult<()> {
+    let Some(format_arg) = args.first() else {
+        return Err(JuvinilError::InvalidParamCount("printf".into(), 1, 0, line));
+    };
+
+    let ExprKind::Str(format) = &format_arg.kind else {
+        return Err(JuvinilError::InvalidFormat(
+            String::new(),
+            "must be a string literal".into(),
+            format_arg.line,
+        ));
+    };
+
+    let specifiers = format_specifiers(format)
+        .map_err(|reason| JuvinilError::InvalidFormat(format.clone(), reason, line))?;
+
+    let values = &args[1..];
+    if specifiers.len() != values.len() {
+        return Err(JuvinilError::InvalidFormat(
+            format.clone(),
+            format!(
+                "has {} specifiers, but {} values were given",
+                specifiers.len(),
+                values.len()
+            ),
+            line,
+        ));
+    }
+
+    //Like in C, `%d` prints booleans as 0 or 1
+    for ((specifier, spec_type), value) in specifiers.iter().zip(values) {
+        let boolean_as_int = *spec_type == "int" && value.jv_type == "boolean";
+
+        if *spec_type != value.jv_type && !boolean_as_int {
+            return Err(JuvinilError::InvalidFormatArgument(
+                specifier.clone(),
+                spec_type.to_string(),
+                value.jv_type.clone(),
+                value.line,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+//List the specifiers of a `printf` format string, along with the type
+//of the value each of them formats. Specifiers can have flags, a width
+//and a precision, like `%-5d`, and `%%` prints a percent sign
+pub fn format_specifiers(format: &str) -> Result<Vec<(String, &'static str)>, String> {
+    let mut specifiers = Vec::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        let mut specifier = String::from("%");
+        while let Some(&next) = chars.peek() {
+            if !"-+ #0123456789.".contains(next) {
+                break;
+            }
+
+            specifier.push(next);
+            chars.next();
+        }
+
+        let Some(conversion) = chars.next() else {
+            return Err("ends in the middle of a specifier".into());
+        };
+        specifier.push(conversion);
+
+        let spec_type = match conversion {
+            '%' if specifier == "%%" => continue,
+            'd' | 'i' => "int",
+            's' => "string",
+            _ => return Err(format!("has an unsupported specifier `{}`", specifier)),
+        };
+
+        specifiers.push((specifier, spec_type));
+    }
+
+    Ok(specifiers)
+}
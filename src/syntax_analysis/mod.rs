@@ -1,2 +1,4 @@
+pub mod ast;
+pub mod builtins;
 pub mod parser;
 pub mod scope;
@@ -1,11 +1,15 @@
-use std::fs::{self};
-
 use crate::{
-    error::{JuvinilError, JuvinilResult},
+    diagnostic::{Diagnostic, Severity, WarningConfig, WarningLevel},
+    error::{JuvinilError, JuvinilResult, JuvinilWarning},
     lexical_analysis::token::{Token, TokenType},
+    semantic_analysis::{definite_assignment, returns},
 };
 
-use super::scope::{JvFunction, JvVariable, Scope};
+use super::{
+    ast::{Expr, ExprKind, FuncDecl, Program, Stmt, StmtKind},
+    builtins,
+    scope::{JvFunction, JvVariable, Scope},
+};
 
 pub struct Parser {
     tokens: Vec<Token>,           //List of tokens created by the lexical analyzer
@@ -13,8 +17,10 @@ pub struct Parser {
     current_token: Token,         //Reference to the current token (tokens[pos])
     lookahead: Option<Token>,     //Reference to the lookahead (tokens[pos + 1])
     current_scope: Option<Scope>, //Current active scope
-    intermediary_code: String,    //Intermediary code generated during the parse routine
-    scope_level: usize,           //Current scope level to determine tabs in intermediary code
+    scope_level: usize,           //Current scope level, used for logging
+    current_function: Option<JvFunction>, //Function whose body is being parsed, if any
+    warning_config: WarningConfig, //Level of each warning, configured by the user
+    diagnostics: Vec<Diagnostic>, //Warnings found during the parse routine
 }
 
 // General parsing methods (consuming, advancing tokens, etc)
@@ -28,10 +34,10 @@ impl Parser {
             current_token: Token::new(TokenType::EOF, String::new(), 0),
             lookahead: None,
             current_scope: Some(Scope::new(None)),
-            intermediary_code: String::from(
-                "#include <stdio.h>\n#include <string>\n#include <cstdlib>\n#include <conio.h>\nusing namespace std;\n\n",
-            ),
-            scope_level: 0
+            scope_level: 0,
+            current_function: None,
+            warning_config: WarningConfig::default(),
+            diagnostics: Vec::new(),
         };
 
         //We call the `next()` function
@@ -42,31 +48,47 @@ impl Parser {
         Ok(parser)
     }
 
-    //Dumps the intermediary code generated during the
-    //parse routine to the specified file
-    pub fn dump_intermediary_code(&self, filepath: &str) -> JuvinilResult<()> {
-        fs::write(filepath, self.intermediary_code.clone())?;
-        Ok(())
+    //Sets the level of each warning reported by the parser
+    pub fn with_warning_config(mut self, warning_config: WarningConfig) -> Self {
+        self.warning_config = warning_config;
+        self
     }
 
-    //Initiates the parsing routine, starting with the `program`
-    pub fn parse(&mut self) -> JuvinilResult<()> {
-        self.start()?;
-        Ok(())
+    //Initiates the parsing routine, starting with the `program`.
+    //The parsed program then goes through the flow analysis,
+    //which needs the whole program to be built.
+    //Warnings don't stop the parse routine, but the program
+    //is rejected at the end if any of them were denied
+    pub fn parse(&mut self) -> JuvinilResult<Program> {
+        let program = self.start()?;
+        definite_assignment::check(&program)?;
+        returns::check(&program)?;
+
+        let denied = self
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+
+        if denied > 0 {
+            return Err(JuvinilError::DeniedWarnings(denied));
+        }
+
+        Ok(program)
     }
 
-    fn push_intermediary_code(&mut self, text: &str) {
-        self.intermediary_code.push_str(text);
+    //Diagnostics reported during the parse routine
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
-    //Maps a JvType to a C type
-    fn map_type(&self, value: &str) -> String {
-        match value {
-            "void" => "void".into(),
-            "int" => "int".into(),
-            "boolean" => "bool".into(),
-            "string" => "string".into(),
-            _ => panic!("Wtf"),
+    //Report a warning, unless the user allowed it
+    fn warn(&mut self, warning: JuvinilWarning) {
+        let level = self.warning_config.level(warning.lint());
+
+        if level != WarningLevel::Allow {
+            self.diagnostics
+                .push(Diagnostic::from_warning(&warning, level));
         }
     }
 
@@ -80,14 +102,52 @@ impl Parser {
     }
 
     //Take the current scope and throw it away,
-    //making the scope's parent the new current scope
+    //making the scope's parent the new current scope.
+    //Before that, warn about everything in it that was never used
     fn pop_scope(&mut self) {
-        let last_scope = self.current_scope.take();
-        let parent = last_scope.unwrap().parent;
-        self.current_scope = *parent;
+        let last_scope = self.current_scope.take().unwrap();
+        self.warn_unused(&last_scope);
+
+        self.current_scope = *last_scope.parent;
         self.scope_level -= 1;
     }
 
+    //Warn about variables that were never read and functions
+    //that were never called. Names starting with an underscore
+    //are intentionally unused, so they're ignored
+    fn warn_unused(&mut self, scope: &Scope) {
+        for variable in scope.variables.iter() {
+            if variable.read || variable.var_name.starts_with('_') {
+                continue;
+            }
+
+            let warning = if variable.param {
+                let func_name = self
+                    .current_function
+                    .as_ref()
+                    .map(|f| f.func_name.clone())
+                    .unwrap_or_default();
+
+                JuvinilWarning::UnusedParameter(variable.var_name.clone(), func_name, variable.line)
+            } else if variable.written {
+                JuvinilWarning::UnreadVariable(variable.var_name.clone(), variable.line)
+            } else {
+                JuvinilWarning::UnusedVariable(variable.var_name.clone(), variable.line)
+            };
+
+            self.warn(warning);
+        }
+
+        for function in scope.functions.iter() {
+            if !function.called && !function.func_name.starts_with('_') {
+                self.warn(JuvinilWarning::UnusedFunction(
+                    function.func_name.clone(),
+                    function.line,
+                ));
+            }
+        }
+    }
+
     //Search for a variable inside the current scope
     //If the variable wasn't found, we search recursively
     //through the scope's parent until we find it or the
@@ -111,47 +171,54 @@ impl Parser {
         None
     }
 
-    //Search for a function inside the current scope
-    //If the function wasn't found, we search recursively
-    //through the scope's parent until we find it or the
-    //parent is null
-    fn search_func_in_scope(&mut self, func_name: String) -> Option<JvFunction> {
+    //Search for a variable the same way as `search_var_in_scope`,
+    //also returning how many scopes above the current one it was
+    //found, with 0 being the current scope itself
+    fn search_var_with_depth(&self, var_name: &str) -> Option<(usize, JvVariable)> {
         let mut scope = &self.current_scope;
+        let mut depth = 0;
 
         while let Some(inner_scope) = scope {
             let search_result = inner_scope
-                .functions
+                .variables
                 .iter()
-                .find(|x| x.func_name == func_name);
+                .find(|x| x.var_name == var_name);
 
             if let Some(result) = search_result {
-                return Some(result.clone());
+                return Some((depth, result.clone()));
             }
 
             scope = &inner_scope.parent;
+            depth += 1;
         }
 
         None
     }
 
-    //Register a variable in the current scope
-    fn register_variable_in_scope(&mut self, var_type: String, var_name: String) {
-        tracing::info!(
-            "Registering variable `{}` in scope {}",
-            var_name,
-            self.scope_level
-        );
+    //Search for every overload of a function, starting from the
+    //current scope and going through the scope's parents
+    fn search_overloads_in_scope(&self, func_name: &str) -> Vec<JvFunction> {
+        let mut scope = &self.current_scope;
+        let mut overloads = Vec::new();
 
-        if let Some(current_scope) = self.current_scope.as_mut() {
-            current_scope.variables.push(JvVariable {
-                var_type,
-                var_name,
-                assigned: false,
-            });
+        while let Some(inner_scope) = scope {
+            overloads.extend(
+                inner_scope
+                    .functions
+                    .iter()
+                    .filter(|x| x.func_name == func_name)
+                    .cloned(),
+            );
+
+            scope = &inner_scope.parent;
         }
+
+        overloads
     }
 
-    fn mark_variable_as_assigned(&mut self, var_name: String) -> JuvinilResult<()> {
+    //Search for a variable the same way as `search_var_in_scope`,
+    //applying `update` to it if it was found
+    fn update_var_in_scope(&mut self, var_name: &str, update: impl FnOnce(&mut JvVariable)) {
         let mut scope = &mut self.current_scope;
 
         while let Some(inner_scope) = scope {
@@ -161,17 +228,33 @@ impl Parser {
                 .find(|x| x.var_name == var_name);
 
             if let Some(result) = search_result {
-                result.assigned = true;
-                return Ok(());
+                update(result);
+                return;
             }
 
             scope = &mut inner_scope.parent;
         }
+    }
 
-        Err(JuvinilError::UndeclaredVariable(
-            var_name,
-            self.current_token.file_line,
-        ))
+    fn mark_variable_as_read(&mut self, var_name: &str) {
+        self.update_var_in_scope(var_name, |variable| variable.read = true);
+    }
+
+    fn mark_variable_as_written(&mut self, var_name: &str) {
+        self.update_var_in_scope(var_name, |variable| variable.written = true);
+    }
+
+    //Register a variable in the current scope
+    fn register_variable_in_scope(&mut self, variable: JvVariable) {
+        tracing::info!(
+            "Registering variable `{}` in scope {}",
+            variable.var_name,
+            self.scope_level
+        );
+
+        if let Some(current_scope) = self.current_scope.as_mut() {
+            current_scope.variables.push(variable);
+        }
     }
 
     //We collect the variable name
@@ -198,53 +281,256 @@ impl Parser {
         Ok(variable)
     }
 
-    fn assert_id_assigned(&mut self) -> JuvinilResult<()> {
+    //Assert that the ID we're declaring can be declared in the current scope.
+    //A variable can't be declared twice in the same scope, and the top level
+    //of a function body can't redeclare a parameter, since in C++ they share
+    //the same scope. Variables of outer scopes (including globals shadowed by
+    //parameters) can be shadowed, which is reported by the `shadowing` warning
+    fn assert_can_declare(&mut self) -> JuvinilResult<()> {
         let var_name = self.current_token.value.clone();
-        let variable = self.search_var_in_scope(var_name.clone());
+        let line = self.current_token.file_line;
 
-        if let Some(result) = variable {
-            if result.assigned {
-                return Ok(());
+        match self.search_var_with_depth(&var_name) {
+            Some((0, _)) => Err(JuvinilError::DuplicateVariable(var_name, line)),
+            Some((1, shadowed)) if shadowed.param => {
+                Err(JuvinilError::DuplicateVariable(var_name, line))
+            }
+            Some((_, shadowed)) => {
+                self.warn(JuvinilWarning::ShadowedVariable(
+                    var_name,
+                    line,
+                    shadowed.line,
+                ));
+                Ok(())
             }
+            None => Ok(()),
         }
+    }
 
-        return Err(JuvinilError::UnassignedVariable(
-            var_name,
-            self.current_token.file_line,
-        ));
+    //Register a function in the parent of the current scope,
+    //which is the scope around the function being declared
+    fn register_func_in_parent_scope(&mut self, function: JvFunction) {
+        if let Some(parent_scope) = self
+            .current_scope
+            .as_mut()
+            .and_then(|scope| scope.parent.as_mut().as_mut())
+        {
+            parent_scope.functions.push(function);
+        }
     }
 
-    //Register a function in the current scope
-    fn register_func_in_scope(
-        &mut self,
-        return_type: String,
-        func_name: String,
-        params: Vec<String>,
-    ) {
-        if let Some(current_scope) = self.current_scope.as_mut() {
-            current_scope.functions.push(JvFunction {
-                return_type,
-                func_name,
-                params,
-            });
+    //Recursive calls don't count, so a function that
+    //only calls itself is still reported as unused
+    fn mark_func_as_called(&mut self, func_name: &str, params: &[String]) {
+        let recursive = self
+            .current_function
+            .as_ref()
+            .is_some_and(|f| f.func_name == func_name && f.params == params);
+        if recursive {
+            return;
+        }
+
+        let mut scope = &mut self.current_scope;
+
+        while let Some(inner_scope) = scope {
+            let search_result = inner_scope
+                .functions
+                .iter_mut()
+                .find(|x| x.func_name == func_name && x.params == params);
+
+            if let Some(result) = search_result {
+                result.called = true;
+                return;
+            }
+
+            scope = &mut inner_scope.parent;
         }
     }
 
-    //We collect the variable name
+    //We collect the function name
     //to check if it was already declared.
     //if it wasn't, we return an error
-    fn assert_func_declared(&mut self, declared: bool) -> JuvinilResult<Option<JvFunction>> {
+    fn assert_func_declared(&mut self) -> JuvinilResult<Vec<JvFunction>> {
         let func_name = self.current_token.value.clone();
-        let function = self.search_func_in_scope(func_name.clone());
+        let overloads = self.search_overloads_in_scope(&func_name);
 
-        if function.is_some() != declared {
+        if overloads.is_empty() {
             return Err(JuvinilError::UndeclaredFunction(
                 func_name,
                 self.current_token.file_line,
             ));
         }
 
-        Ok(function)
+        Ok(overloads)
+    }
+
+    //Functions can share a name as long as their parameter types
+    //differ, so a declaration is only a duplicate if another
+    //overload already has the same parameter types.
+    //Builtin functions can't be overloaded
+    fn assert_overload_not_declared(&self, function: &JvFunction) -> JuvinilResult<()> {
+        if builtins::is_builtin(&function.func_name) {
+            return Err(JuvinilError::DuplicateFunction(
+                function.func_name.clone(),
+                function.line,
+            ));
+        }
+
+        let duplicate = self
+            .search_overloads_in_scope(&function.func_name)
+            .iter()
+            .any(|overload| overload.params == function.params);
+
+        if duplicate {
+            return Err(JuvinilError::DuplicateFunction(
+                function.signature(),
+                function.line,
+            ));
+        }
+
+        Ok(())
+    }
+
+    //Pick the overload called with the given arguments. A function without
+    //overloads reports exactly what is wrong with the call, otherwise the
+    //error lists every candidate. Along with the overload, returns the
+    //value of each of its parameters, in order
+    fn resolve_overload(
+        &self,
+        func_name: &str,
+        overloads: Vec<JvFunction>,
+        args: &[(Option<String>, Expr)],
+    ) -> JuvinilResult<(JvFunction, Vec<Expr>)> {
+        if let [func] = overloads.as_slice() {
+            let bound_args = self.bind_args(func, args)?;
+            return Ok((func.clone(), bound_args));
+        }
+
+        let arg_types: Vec<String> = args
+            .iter()
+            .map(|(name, arg)| match name {
+                Some(name) => format!("{}: {}", name, arg.jv_type),
+                None => arg.jv_type.clone(),
+            })
+            .collect();
+        let candidates = |functions: &[&JvFunction]| {
+            functions
+                .iter()
+                .map(|f| format!("`{}`", f.signature()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let viable: Vec<(&JvFunction, Vec<Expr>)> = overloads
+            .iter()
+            .filter_map(|f| self.bind_args(f, args).ok().map(|bound| (f, bound)))
+            .collect();
+
+        let line = self.current_token.file_line;
+
+        match viable.as_slice() {
+            [(func, bound_args)] => Ok(((*func).clone(), bound_args.clone())),
+            [] => Err(JuvinilError::NoMatchingOverload(
+                func_name.into(),
+                arg_types.join(", "),
+                candidates(&overloads.iter().collect::<Vec<_>>()),
+                line,
+            )),
+            _ => Err(JuvinilError::AmbiguousCall(
+                func_name.into(),
+                arg_types.join(", "),
+                candidates(&viable.iter().map(|(f, _)| *f).collect::<Vec<_>>()),
+                line,
+            )),
+        }
+    }
+
+    //Match the arguments of a call to the parameters of a function.
+    //Positional arguments fill the first parameters, named arguments
+    //fill the parameter with their name, and every parameter left
+    //takes its default value
+    fn bind_args(
+        &self,
+        func: &JvFunction,
+        args: &[(Option<String>, Expr)],
+    ) -> JuvinilResult<Vec<Expr>> {
+        let line = self.current_token.file_line;
+        let func_name = &func.func_name;
+
+        //Assert that param count is within the number of parameters
+        let count_error = || {
+            let required = func.required_params();
+
+            if required == func.params.len() {
+                JuvinilError::InvalidParamCount(func_name.clone(), required, args.len(), line)
+            } else {
+                JuvinilError::InvalidParamRange(
+                    func_name.clone(),
+                    required,
+                    func.params.len(),
+                    args.len(),
+                    line,
+                )
+            }
+        };
+
+        if args.len() > func.params.len() {
+            return Err(count_error());
+        }
+
+        let mut bound_args: Vec<Option<Expr>> = vec![None; func.params.len()];
+
+        for (position, (name, arg)) in args.iter().enumerate() {
+            let index = match name {
+                Some(name) => func
+                    .param_names
+                    .iter()
+                    .position(|param_name| param_name == name)
+                    .ok_or(JuvinilError::UnknownArgument(
+                        func_name.clone(),
+                        name.clone(),
+                        arg.line,
+                    ))?,
+                None => position,
+            };
+
+            if bound_args[index].is_some() {
+                return Err(JuvinilError::DuplicateArgument(
+                    func_name.clone(),
+                    func.param_names[index].clone(),
+                    arg.line,
+                ));
+            }
+
+            //Assert that each argument matches the type of its parameter
+            if func.params[index] != arg.jv_type {
+                return Err(JuvinilError::InvalidParamType(
+                    func_name.clone(),
+                    func.params[index].clone(),
+                    arg.jv_type.clone(),
+                    arg.line,
+                ));
+            }
+
+            bound_args[index] = Some(arg.clone());
+        }
+
+        let named = args.iter().any(|(name, _)| name.is_some());
+
+        bound_args
+            .into_iter()
+            .enumerate()
+            .map(|(index, arg)| match (arg, &func.defaults[index]) {
+                (Some(arg), _) => Ok(arg),
+                (None, Some(default)) => Ok(default.clone()),
+                (None, None) if named => Err(JuvinilError::MissingArgument(
+                    func_name.clone(),
+                    func.param_names[index].clone(),
+                    line,
+                )),
+                (None, None) => Err(count_error()),
+            })
+            .collect()
     }
 
     //Iterate over the list of tokens.
@@ -270,6 +556,16 @@ impl Parser {
         res
     }
 
+    //Checks if the current token has the given value. String literals
+    //never match, so a `")"` string isn't mistaken for a parenthesis
+    fn current_is(&self, value: &str) -> bool {
+        self.current_token.token_type != TokenType::STRING && self.current_token.value == value
+    }
+
+    fn current_is_any(&self, values: &[&str]) -> bool {
+        values.iter().any(|value| self.current_is(value))
+    }
+
     //Consume a token. If the token is of a different type or value
     //than the provided values, we throw a Syntax Error.
     //We only check for the token `value` property if an actual value
@@ -305,532 +601,734 @@ impl Parser {
     //Start is the first parse instruction
     //First it parses all function declarations,
     //then parses the rest of the program
-    fn start(&mut self) -> JuvinilResult<()> {
-        //Parse all function declarations
-        //before the main program
-        while self.current_token.value == "func" {
-            self.funcdecl()?;
+    fn start(&mut self) -> JuvinilResult<Program> {
+        let mut program = Program::default();
+
+        //Parse all global and function declarations
+        //before the main program. Globals are registered in
+        //the root scope, so only functions declared after them
+        //can use them
+        loop {
+            if self.current_is("func") {
+                let function = self.funcdecl()?;
+                program.functions.push(function);
+            } else if self.current_is("global") {
+                let global = self.globaldecl()?;
+                program.globals.push(global);
+            } else {
+                break;
+            }
         }
 
-        self.push_intermediary_code("int main() {\n");
-
-        self.program()?;
+        program.body = self.program()?;
 
-        self.push_intermediary_code("\n\ngetch();\n");
-        self.push_intermediary_code("}\n");
+        //The root scope is never popped, so its
+        //unused variables and functions are checked here
+        if let Some(root_scope) = self.current_scope.take() {
+            self.warn_unused(&root_scope);
+            self.current_scope = Some(root_scope);
+        }
 
-        Ok(())
+        Ok(program)
     }
 
     //Program is the first parse instruction of the whole file
-    fn program(&mut self) -> JuvinilResult<()> {
-        //If the current token is an open bracket ({),
-        //we're looking at a block
-        if self.current_token.value == "{" {
-            self.block()?;
-        }
+    //program -> stmts EOF
+    fn program(&mut self) -> JuvinilResult<Vec<Stmt>> {
+        let stmts = self.stmts()?;
 
-        //If the current token type is a TYPE, we're
-        //looking at a declaration (decls)
-        if self.current_token.token_type == TokenType::TYPE {
-            self.decls()?;
-        }
-
-        self.stmts()?;
-
-        //If we're not at the end of the file, repeat!
-        if self.current_token.token_type != TokenType::EOF {
-            self.program()?;
-        }
+        //`stmts` only stops at a closing bracket or at
+        //the end of the file, so anything else is an error
+        self.consume(TokenType::EOF, None)?;
 
-        Ok(())
+        Ok(stmts)
     }
 
-    //block -> { decls stmts }
-    fn block(&mut self) -> JuvinilResult<()> {
+    //block -> { stmts }
+    fn block(&mut self) -> JuvinilResult<Vec<Stmt>> {
         self.push_scope();
 
         self.consume(TokenType::SYMBOL, Some("{"))?;
-        self.push_intermediary_code("{\n");
-
-        //If the current token is an open bracket ({),
-        //we're looking at a block
-        while self.current_token.value != "}" {
-            if self.current_token.value == "{" {
-                self.block()?;
-            }
-
-            //If the current token type is a TYPE, we're
-            //looking at a declaration (decls)
-            if self.current_token.token_type == TokenType::TYPE {
-                self.decls()?;
-            }
-
-            self.stmts()?;
-        }
-
+        let stmts = self.stmts()?;
         self.consume(TokenType::SYMBOL, Some("}"))?;
-        self.push_intermediary_code("}\n");
 
         self.pop_scope();
 
-        Ok(())
+        Ok(stmts)
     }
 
-    //decls -> decls decl
-    fn decls(&mut self) -> JuvinilResult<()> {
-        //We run the declaration parsing until we no longer
-        //have a TYPE as the current
-        while self.current_token.token_type == TokenType::TYPE {
-            self.decl()?;
-        }
+    //Checks if the current token starts a declaration,
+    //which is either a TYPE or the `const` keyword
+    fn is_decl_start(&self) -> bool {
+        self.current_token.token_type == TokenType::TYPE || self.current_is("const")
+    }
 
-        Ok(())
+    //globaldecl -> global decl
+    fn globaldecl(&mut self) -> JuvinilResult<Stmt> {
+        self.consume(TokenType::KEYWORD, Some("global"))?;
+        self.decl(true)
     }
 
-    //decl -> TYPE ID endexpr
-    fn decl(&mut self) -> JuvinilResult<()> {
+    //decl -> const? TYPE declarators endexpr
+    //declarators -> declarator , declarators | declarator
+    //declarator -> ID | ID = expr
+    fn decl(&mut self, global: bool) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
+
+        let constant = self.current_is("const");
+        if constant {
+            self.consume(TokenType::KEYWORD, Some("const"))?;
+        }
+
         let var_type = self.current_token.value.clone();
         self.jvtype()?;
 
-        //Assert that the ID we're declaring wasn't
-        //already declared
-        self.assert_id_declared(false)?;
+        let mut declarators: Vec<(String, Option<Expr>)> = Vec::new();
+
+        loop {
+            //Assert that the ID we're declaring doesn't
+            //conflict with another variable
+            self.assert_can_declare()?;
+
+            let var_name = self.current_token.value.clone();
+            let var_line = self.current_token.file_line;
+            self.consume(TokenType::ID, None)?;
+
+            //Like in C++, the variable exists as soon as its name is
+            //declared, so its own initializer already refers to it
+            //instead of a shadowed variable, and `int x = x;` complains
+            //about reading `x` before it is assigned
+            self.register_variable_in_scope(JvVariable {
+                var_type: var_type.clone(),
+                var_name: var_name.clone(),
+                constant,
+                param: false,
+                read: false,
+                written: false,
+                line: var_line,
+            });
 
-        let var_name = self.current_token.value.clone();
-        self.consume(TokenType::ID, None)?;
-        self.endexpr()?;
+            let mut initializer: Option<Expr> = None;
+            if self.current_is("=") {
+                self.consume(TokenType::OPERATOR, Some("="))?;
 
-        self.intermediary_code
-            .push_str(format!("{} {};\n", self.map_type(var_type.as_str()), var_name).as_str());
+                let line = self.current_token.file_line;
+                let expr = self.expr()?;
 
-        self.register_variable_in_scope(var_type, var_name);
+                if expr.jv_type != var_type {
+                    return Err(JuvinilError::InvalidValueType(
+                        var_name,
+                        var_type,
+                        expr.jv_type,
+                        line,
+                    ));
+                }
 
-        Ok(())
+                initializer = Some(expr);
+            } else if constant || global {
+                //A constant can never be assigned later, and a global
+                //can be read by any function at any time,
+                //so they must be initialized right away
+                return Err(JuvinilError::SyntaxError(
+                    TokenType::OPERATOR,
+                    "=".into(),
+                    self.current_token.clone(),
+                    self.current_token.file_line,
+                ));
+            }
+
+            if initializer.is_some() {
+                self.mark_variable_as_written(&var_name);
+            }
+
+            declarators.push((var_name, initializer));
+
+            //If the current token is a comma (,)
+            //We consume another declarator
+            if !self.current_is(",") {
+                break;
+            }
+
+            self.consume(TokenType::SYMBOL, Some(","))?;
+        }
+
+        self.endexpr()?;
+
+        Ok(Stmt::new(
+            StmtKind::Decl(constant, var_type, declarators),
+            line,
+        ))
     }
 
     //stmts -> stmts stmt
-    fn stmts(&mut self) -> JuvinilResult<()> {
-        //Parse a statement if the current token is one
-        //of the following values or types
-        let stmt_values = [
-            "for", "if", "while", "do", "break", "continue", "return", "{", "func",
-        ];
+    fn stmts(&mut self) -> JuvinilResult<Vec<Stmt>> {
+        let mut stmts = Vec::new();
 
-        let stmt_types = [TokenType::ID];
-
-        while stmt_values.contains(&self.current_token.value.as_str())
-            || stmt_types.contains(&self.current_token.token_type)
-        {
-            self.stmt()?;
+        //Statements are parsed one after the other until
+        //the end of the enclosing block or of the file
+        while !self.is_stmts_end() {
+            stmts.push(self.stmt()?);
         }
 
-        Ok(())
+        Ok(stmts)
+    }
+
+    //Checks if the current token ends a list of statements,
+    //which is either a closing bracket or the end of the file
+    fn is_stmts_end(&self) -> bool {
+        (self.current_token.token_type == TokenType::SYMBOL && self.current_is("}"))
+            || self.current_token.token_type == TokenType::EOF
     }
 
-    //Statement can be pretty much everything that is not a declaration
-    fn stmt(&mut self) -> JuvinilResult<()> {
+    //Statement can be pretty much everything, including declarations
+    fn stmt(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
+
+        //If the current token is a TYPE or `const`,
+        //we're looking at a declaration (decl)
+        if self.is_decl_start() {
+            return self.decl(false);
+        }
+
         //If current token is an ID, we're either looking at a function call (func)
         //or a assignment (asgn)
         if self.current_token.token_type == TokenType::ID {
-            if let Some(lookahead) = self.lookahead.clone() {
-                //It's a function call if the lookahead token is a parenthesis
-                if lookahead.value == "(" {
-                    let func_value = self.func()?;
-                    self.endexpr()?;
-
-                    self.intermediary_code
-                        .push_str(format!("{};\n", func_value).as_str());
+            //It's a function call if the lookahead token is a parenthesis
+            if self.lookahead.as_ref().is_some_and(|l| l.value == "(") {
+                let func_value = self.func()?;
+                self.endexpr()?;
 
-                    return Ok(());
-                }
-
-                //Otherwise, it's an assignment
-                self.asgn()?;
-                self.push_intermediary_code("\n");
+                return Ok(Stmt::new(StmtKind::Call(func_value), line));
             }
 
-            return Ok(());
+            //Otherwise, it's an assignment
+            return self.asgn();
         }
 
         //Parse a block if the current token is a "{"
-        if self.current_token.value == "{" {
-            self.block()?;
-            return Ok(());
+        if self.current_is("{") {
+            let stmts = self.block()?;
+            return Ok(Stmt::new(StmtKind::Block(stmts), line));
         }
 
         //Parse a for expression if the current token is a for
-        if self.current_token.value == "for" {
-            self.stmt_for()?;
-            return Ok(());
+        if self.current_is("for") {
+            return self.stmt_for();
         }
 
         //Parse an if expression if the current token is an if
-        if self.current_token.value == "if" {
-            self.stmt_if()?;
-            return Ok(());
+        if self.current_is("if") {
+            return self.stmt_if();
         }
 
         //Parse a while expression if the current token is a while
-        if self.current_token.value == "while" {
-            self.stmt_while()?;
-            return Ok(());
+        if self.current_is("while") {
+            return self.stmt_while();
         }
 
         //Parse a do while expression if the current token is a do while
-        if self.current_token.value == "do" {
-            self.stmt_do_while()?;
-            return Ok(());
+        if self.current_is("do") {
+            return self.stmt_do_while();
         }
 
         //Parse a break if the current token is 'break'
-        if self.current_token.value == "break" {
+        if self.current_is("break") {
             self.consume(TokenType::KEYWORD, Some("break"))?;
             self.endexpr()?;
 
-            self.push_intermediary_code("break;\n");
-            return Ok(());
+            return Ok(Stmt::new(StmtKind::Break, line));
         }
 
         //Parse a continue if the current token is 'continue'
-        if self.current_token.value == "continue" {
+        if self.current_is("continue") {
             self.consume(TokenType::KEYWORD, Some("continue"))?;
             self.endexpr()?;
-            self.push_intermediary_code("continue;\n");
-            return Ok(());
+
+            return Ok(Stmt::new(StmtKind::Continue, line));
         }
 
         //If all of the above fail, the remaning condition
         //is to parse a 'return'
-        self.stmt_return()?;
-        Ok(())
+        self.stmt_return()
     }
 
-    fn stmt_return(&mut self) -> JuvinilResult<()> {
+    fn stmt_return(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("return"))?;
 
         //return something (expr) if the current token is
         //not a semicolon
-        let mut expr_result = String::new();
-        if self.current_token.value != ";" {
-            if self.current_token.token_type == TokenType::STRING {
-                expr_result = format!("\"{}\"", self.current_token.value);
-                self.consume(TokenType::STRING, None)?;
-            } else {
-                expr_result = self.expr()?;
-            }
+        let mut expr_result: Option<Expr> = None;
+        if !self.current_is(";") {
+            expr_result = Some(self.expr()?);
         }
 
         self.endexpr()?;
 
-        self.intermediary_code
-            .push_str(format!("return {};\n", expr_result).as_str());
+        let expr_type = expr_result.as_ref().map_or("void", |e| e.jv_type.as_str());
+
+        //Inside a function, the returned value must match
+        //the declared return type
+        if let Some(func) = &self.current_function {
+            if func.return_type != expr_type {
+                return Err(JuvinilError::InvalidReturnType(
+                    func.func_name.clone(),
+                    func.return_type.clone(),
+                    expr_type.into(),
+                    line,
+                ));
+            }
 
-        Ok(())
+            return Ok(Stmt::new(StmtKind::Return(expr_result), line));
+        }
+
+        //Outside of functions, returning ends the program, and
+        //the returned `int` is its exit status (0 if there's none)
+        match expr_result {
+            None => {
+                let status = Expr::new(ExprKind::Int(0), "int", line);
+                Ok(Stmt::new(StmtKind::Return(Some(status)), line))
+            }
+            Some(status) if status.jv_type == "int" => {
+                Ok(Stmt::new(StmtKind::Return(Some(status)), line))
+            }
+            Some(status) => Err(JuvinilError::InvalidReturnType(
+                "main".into(),
+                "int".into(),
+                status.jv_type,
+                line,
+            )),
+        }
     }
 
     //Parse a for expression
-    fn stmt_for(&mut self) -> JuvinilResult<()> {
+    fn stmt_for(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("for"))?;
         self.consume(TokenType::SYMBOL, Some("("))?;
 
-        self.push_intermediary_code("for(");
-
-        self.asgn()?;
-        let boolexpr_result = self.boolexpr()?;
+        let init = self.asgn()?;
+        let condition = self.condition()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
 
-        self.intermediary_code
-            .push_str(format!(" {} ;)", boolexpr_result).as_str());
-
-        self.block()?;
+        let body = self.block()?;
 
-        Ok(())
+        Ok(Stmt::new(
+            StmtKind::For(Box::new(init), condition, body),
+            line,
+        ))
     }
 
     //Parse an if expression
-    fn stmt_if(&mut self) -> JuvinilResult<()> {
+    fn stmt_if(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("if"))?;
         self.consume(TokenType::SYMBOL, Some("("))?;
-        let boolexpr_result = self.boolexpr()?;
+        let condition = self.condition()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
 
-        let if_expr = format!("if ({})", boolexpr_result);
-        self.push_intermediary_code(if_expr.as_str());
-
-        self.block()?;
+        let then_block = self.block()?;
 
-        if self.current_token.value == "else" {
+        let mut else_block: Option<Vec<Stmt>> = None;
+        if self.current_is("else") {
             self.consume(TokenType::KEYWORD, Some("else"))?;
-            self.block()?;
+            else_block = Some(self.block()?);
         }
 
-        Ok(())
+        Ok(Stmt::new(
+            StmtKind::If(condition, then_block, else_block),
+            line,
+        ))
     }
 
     //Parse a while expression
-    fn stmt_while(&mut self) -> JuvinilResult<()> {
+    fn stmt_while(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("while"))?;
         self.consume(TokenType::SYMBOL, Some("("))?;
-        let boolexpr_result = self.boolexpr()?;
+        let condition = self.condition()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
 
-        self.intermediary_code
-            .push_str(format!("while({}) ", boolexpr_result).as_str());
-
-        self.block()?;
+        let body = self.block()?;
 
-        Ok(())
+        Ok(Stmt::new(StmtKind::While(condition, body), line))
     }
 
     //Parse a do while expression
-    fn stmt_do_while(&mut self) -> JuvinilResult<()> {
+    fn stmt_do_while(&mut self) -> JuvinilResult<Stmt> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("do"))?;
-        self.push_intermediary_code("do ");
 
-        self.block()?;
+        let body = self.block()?;
         self.consume(TokenType::KEYWORD, Some("while"))?;
         self.consume(TokenType::SYMBOL, Some("("))?;
-        let boolexpr_result = self.boolexpr()?;
+        let condition = self.condition()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
         self.endexpr()?;
 
-        self.intermediary_code
-            .push_str(format!("while({});\n", boolexpr_result).as_str());
+        Ok(Stmt::new(StmtKind::DoWhile(body, condition), line))
+    }
 
-        Ok(())
+    //Parse the condition of an if, for or while.
+    //Any expression is accepted, as long as it is a boolean
+    fn condition(&mut self) -> JuvinilResult<Expr> {
+        let line = self.current_token.file_line;
+        let condition = self.expr()?;
+
+        if condition.jv_type != "boolean" {
+            return Err(JuvinilError::InvalidCondition(condition.jv_type, line));
+        }
+
+        Ok(condition)
     }
 
-    //Parse a boolean expression
-    fn boolexpr(&mut self) -> JuvinilResult<String> {
-        let mut result = String::new();
+    //Parse an expression, which is the lowest precedence level
+    //expr -> boolexpr ? expr : expr | boolexpr
+    fn expr(&mut self) -> JuvinilResult<Expr> {
+        let line = self.current_token.file_line;
+        let condition = self.boolexpr()?;
 
-        //A boolexpr will always end up as a join
-        let join_result = self.join()?;
-        result.push_str(join_result.as_str());
+        if !self.current_is("?") {
+            return Ok(condition);
+        }
 
-        //After we consume a join, we check if we have the base case
-        //for the boolexpr, which is a ||
-        //if we do, we consume the comparator and rerun the boolexpr
-        if self.current_token.value == "||" {
-            let comparator = self.current_token.value.clone();
+        //The conditional expression is right associative,
+        //so `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+        self.consume(TokenType::OPERATOR, Some("?"))?;
+        let then_expr = self.expr()?;
+        self.consume(TokenType::OPERATOR, Some(":"))?;
+        let else_expr = self.expr()?;
+
+        if condition.jv_type != "boolean" {
+            return Err(JuvinilError::InvalidCondition(condition.jv_type, line));
+        }
+
+        //Both branches must evaluate to the same type,
+        //which becomes the type of the whole expression
+        if then_expr.jv_type != else_expr.jv_type || then_expr.jv_type == "void" {
+            return Err(JuvinilError::InvalidConditionalTypes(
+                then_expr.jv_type,
+                else_expr.jv_type,
+                line,
+            ));
+        }
 
+        let jv_type = then_expr.jv_type.clone();
+        Ok(Expr::new(
+            ExprKind::Ternary(
+                Box::new(condition),
+                Box::new(then_expr),
+                Box::new(else_expr),
+            ),
+            jv_type.as_str(),
+            line,
+        ))
+    }
+
+    //boolexpr -> boolexpr || join
+    fn boolexpr(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.join()?;
+
+        //After we consume a join, we check if we have a ||.
+        //If we do, we consume the comparator and the next join,
+        //joining both sides in a new binary expression
+        while self.current_is("||") {
+            let comparator = self.current_token.value.clone();
             self.consume(TokenType::COMPARATOR, None)?;
-            let recursive_result = self.boolexpr()?;
+            let right = self.join()?;
 
-            result.push_str(format!(" {} {}", comparator, recursive_result).as_str());
+            result = self.binary(comparator, result, right)?;
         }
 
         Ok(result)
     }
 
-    fn join(&mut self) -> JuvinilResult<String> {
-        let mut result = String::new();
+    //join -> join && equality
+    fn join(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.equality()?;
 
-        //A join will always end up as an equality
-        let equality_result = self.equality()?;
-        result.push_str(equality_result.as_str());
-
-        //After we consume an equality, we check if we have the base case
-        //for the join, which is a &&
-        //if we do, we consume the comparator and rerun the join
-        if self.current_token.value == "&&" {
+        while self.current_is("&&") {
             let comparator = self.current_token.value.clone();
             self.consume(TokenType::COMPARATOR, None)?;
-            let recursive_result = self.join()?;
+            let right = self.equality()?;
 
-            result.push_str(format!(" {} {}", comparator, recursive_result).as_str());
+            result = self.binary(comparator, result, right)?;
         }
 
         Ok(result)
     }
 
-    fn equality(&mut self) -> JuvinilResult<String> {
-        let mut result = String::new();
-        //An equality will always end up as a cmp
-        let cmp_result = self.cmp()?;
-        result.push_str(cmp_result.as_str());
+    //equality -> equality == cmp | equality != cmp
+    fn equality(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.cmp()?;
 
-        //After we consume a cmp, we check if we have the base case
-        //for the equality, which is a == or !=
-        //if we do, we consume the comparator and rerun the equality
-        if self.current_token.value == "==" || self.current_token.value == "!=" {
+        while self.current_is("==") || self.current_is("!=") {
             let comparator = self.current_token.value.clone();
             self.consume(TokenType::COMPARATOR, None)?;
-            let recursive_result = self.equality()?;
+            let right = self.cmp()?;
 
-            result.push_str(recursive_result.as_str());
-            result.push_str(format!(" {} {}", comparator, recursive_result).as_str());
+            result = self.binary(comparator, result, right)?;
         }
 
         Ok(result)
     }
 
-    fn cmp(&mut self) -> JuvinilResult<String> {
-        //The cmp is the easiest,
-        //it's just an expression followed by a comparator followed by another expression
-        let expr1_result = self.expr()?;
-        let comparator = self.current_token.value.clone();
-        self.consume(TokenType::COMPARATOR, None)?;
-        let expr2_result = self.expr()?;
+    //cmp -> arith < arith | arith <= arith | arith > arith | arith >= arith | arith
+    fn cmp(&mut self) -> JuvinilResult<Expr> {
+        let left = self.arith()?;
 
-        let result = format!("{} {} {}", expr1_result, comparator, expr2_result);
+        //Comparisons don't chain, so `a < b < c` is a syntax error
+        let comparators = ["<", "<=", ">", ">="];
+        if self.current_is_any(&comparators) {
+            let comparator = self.current_token.value.clone();
+            self.consume(TokenType::COMPARATOR, None)?;
+            let right = self.arith()?;
 
-        Ok(result.into())
-    }
+            return self.binary(comparator, left, right);
+        }
 
-    //Parse an expression
-    fn expr(&mut self) -> JuvinilResult<String> {
-        let mut expr_result = String::new();
+        Ok(left)
+    }
 
-        //An expr will always end up as a bnr
-        let bnr_result = self.bnr()?;
-        expr_result.push_str(bnr_result.as_str());
+    //arith -> arith + bnr | arith - bnr
+    fn arith(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.bnr()?;
 
-        //After we consume a bnr, we check if we have the base case
-        //for the expr, which is a + or -
-        //if we do, we consume the operator and rerun the expr
-        if self.current_token.value == "+" || self.current_token.value == "-" {
+        while self.current_is("+") || self.current_is("-") {
             let operator = self.current_token.value.clone();
             self.consume(TokenType::OPERATOR, None)?;
-            expr_result.push_str(operator.as_str());
+            let right = self.bnr()?;
 
-            let recursive_result = self.expr()?;
-            expr_result.push_str(recursive_result.as_str());
+            result = self.binary(operator, result, right)?;
         }
 
-        Ok(expr_result)
+        Ok(result)
     }
 
-    fn bnr(&mut self) -> JuvinilResult<String> {
-        let mut bnr_result = String::new();
-
-        //a bnr will always end up in a term
-        let term_result = self.term()?;
-        bnr_result.push_str(term_result.as_str());
+    //bnr -> bnr & term | bnr | term
+    fn bnr(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.term()?;
 
-        //After we consume a term, we check if we have the base case
-        //for the bnr, which is a & or |
-        //if we do, we consume the operator an rerun the bnr
-        if self.current_token.value == "&" || self.current_token.value == "|" {
+        while self.current_is("&") || self.current_is("|") {
             let operator = self.current_token.value.clone();
             self.consume(TokenType::OPERATOR, None)?;
-            bnr_result.push_str(operator.as_str());
+            let right = self.term()?;
 
-            let recursive_result = self.bnr()?;
-            bnr_result.push_str(recursive_result.as_str());
+            result = self.binary(operator, result, right)?;
         }
 
-        Ok(bnr_result)
+        Ok(result)
     }
 
-    fn term(&mut self) -> JuvinilResult<String> {
-        let mut term_result = String::new();
-
-        //a term will always end up in a unit
-        let unit_result = self.unit()?;
-        term_result.push_str(unit_result.as_str());
+    //term -> term * unit | term / unit | term % unit
+    fn term(&mut self) -> JuvinilResult<Expr> {
+        let mut result = self.unit()?;
 
-        //After we consume a unit, we check if we have the base case
-        //for the term, which is a *, / or %
-        //if we do, we consume the operator an rerun the bnr
-        if self.current_token.value == "*"
-            || self.current_token.value == "/"
-            || self.current_token.value == "%"
-        {
+        while self.current_is("*") || self.current_is("/") || self.current_is("%") {
             let operator = self.current_token.value.clone();
             self.consume(TokenType::OPERATOR, None)?;
-            term_result.push_str(operator.as_str());
+            let right = self.unit()?;
 
-            let recursive_result = self.term()?;
-            term_result.push_str(recursive_result.as_str());
+            result = self.binary(operator, result, right)?;
         }
 
-        Ok(term_result)
+        Ok(result)
     }
 
-    //A unit is a raw number that can have a modifier (++, --, -)
-    fn unit(&mut self) -> JuvinilResult<String> {
-        let mut unit_result = String::new();
-
-        let unit_values = ["-", "++", "--"];
-        if unit_values.contains(&self.current_token.value.as_str()) {
+    //A unit is a factor that can have a modifier (++, --, -, !)
+    fn unit(&mut self) -> JuvinilResult<Expr> {
+        let unit_values = ["-", "++", "--", "!"];
+        if self.current_is_any(&unit_values) {
             let operator = self.current_token.value.clone();
+            let line = self.current_token.file_line;
             self.consume(TokenType::OPERATOR, None)?;
-            unit_result.push_str(operator.as_str());
 
-            //Unfortunately, unit is recursive to the right,
-            //so we can't use the same approach as the bnr
-            let recursive_result = self.unit()?;
-            unit_result.push_str(recursive_result.as_str());
-        }
+            //Unit is recursive to the right, so `- - x` is valid
+            let operand = self.unit()?;
+
+            //`!` only applies to booleans, while the
+            //other modifiers only apply to integers
+            let expected_type = if operator == "!" { "boolean" } else { "int" };
+            if operand.jv_type != expected_type {
+                return Err(JuvinilError::InvalidOperand(
+                    operator,
+                    operand.jv_type,
+                    line,
+                ));
+            }
+
+            //Only variables can be incremented or decremented
+            let is_increment = operator == "++" || operator == "--";
+            if is_increment && !matches!(operand.kind, ExprKind::Var(_)) {
+                return Err(JuvinilError::InvalidIncrement(operator, line));
+            }
 
-        let factor_result = self.factor()?;
-        unit_result.push_str(factor_result.as_str());
+            //Incrementing or decrementing a variable assigns it,
+            //which isn't allowed for constants
+            if let ExprKind::Var(var_name) = &operand.kind {
+                let variable = self.search_var_in_scope(var_name.clone());
+                if is_increment && variable.is_some_and(|v| v.constant) {
+                    return Err(JuvinilError::ConstantAssignment(var_name.clone(), line));
+                }
 
-        Ok(unit_result)
+                if is_increment {
+                    self.mark_variable_as_written(var_name);
+                }
+            }
+
+            return Ok(Expr::new(
+                ExprKind::Unary(operator, Box::new(operand)),
+                expected_type,
+                line,
+            ));
+        }
+
+        self.factor()
     }
 
-    //A factor is basically a raw number or variable
-    fn factor(&mut self) -> JuvinilResult<String> {
+    //A factor is a literal, a variable, a function call
+    //or a parenthesized expression
+    fn factor(&mut self) -> JuvinilResult<Expr> {
+        let line = self.current_token.file_line;
+
         if self.current_token.token_type == TokenType::NUMBER {
             let number = self.current_token.value.clone();
+            let value = number
+                .parse::<i32>()
+                .map_err(|_| JuvinilError::LexicalError(number, line))?;
             self.consume(TokenType::NUMBER, None)?;
 
-            return Ok(format!("{}", number));
+            return Ok(Expr::new(ExprKind::Int(value), "int", line));
         }
 
-        if self.current_token.value == "(" {
+        if self.current_token.token_type == TokenType::INTERPSTART {
+            return self.interpolation();
+        }
+
+        if self.current_token.token_type == TokenType::STRING {
+            let value = self.current_token.value.clone();
+            self.consume(TokenType::STRING, None)?;
+
+            return Ok(Expr::new(ExprKind::Str(value), "string", line));
+        }
+
+        if self.current_is("(") {
             self.consume(TokenType::SYMBOL, Some("("))?;
             let expr_result = self.expr()?;
             self.consume(TokenType::SYMBOL, Some(")"))?;
 
-            return Ok(format!("({})", expr_result));
+            return Ok(expr_result);
         }
 
         //If the current token is an ID and the lookahead isn't
-        //a parenthesis, then it's just an ID asgn (x = y)
+        //a parenthesis, then it's a variable read
         if self.current_token.token_type == TokenType::ID {
             if let Some(lookahead) = self.lookahead.clone() {
                 if lookahead.value != "(" {
-                    //Assert that the ID is assigned before being used
-                    self.assert_id_assigned()?;
+                    //Assert that the ID is declared. Whether it
+                    //was assigned is checked by the flow analysis
+                    let variable = self.assert_id_declared(true)?.unwrap();
+                    self.mark_variable_as_read(&variable.var_name);
 
-                    let var_name = self.current_token.value.clone();
                     self.consume(TokenType::ID, None)?;
 
-                    return Ok(var_name);
+                    return Ok(Expr::new(
+                        ExprKind::Var(variable.var_name),
+                        variable.var_type.as_str(),
+                        line,
+                    ));
                 }
             }
         }
 
         //If the token is a primitive true or false,
         //consume the respective keywords
-        if self.current_token.value == "true" || self.current_token.value == "false" {
-            let bool_value = self.current_token.value.clone();
+        if self.current_is("true") || self.current_is("false") {
+            let bool_value = self.current_is("true");
             self.consume(TokenType::KEYWORD, None)?;
 
-            return Ok(bool_value);
+            return Ok(Expr::new(ExprKind::Bool(bool_value), "boolean", line));
         }
 
-        //If the current token is not a number nor a parenthesis,
+        //If the current token is none of the above,
         //the only remaining option is for it to be a function
-        let func_result = self.func()?;
+        self.func()
+    }
+
+    //An interpolated string, whose fragments were split by the lexer
+    //interpolation -> INTERPSTART expr (INTERPMIDDLE expr)* INTERPEND
+    fn interpolation(&mut self) -> JuvinilResult<Expr> {
+        let line = self.current_token.file_line;
+        let mut parts: Vec<Expr> = Vec::new();
+
+        loop {
+            let fragment = self.current_token.value.clone();
+            parts.push(Expr::new(ExprKind::Str(fragment), "string", line));
 
-        Ok(func_result)
+            if self.current_token.token_type == TokenType::INTERPEND {
+                self.consume(TokenType::INTERPEND, None)?;
+                break;
+            }
+
+            match self.current_token.token_type {
+                TokenType::INTERPSTART => self.consume(TokenType::INTERPSTART, None)?,
+                _ => self.consume(TokenType::INTERPMIDDLE, None)?,
+            }
+
+            //Values of any type are converted to a string
+            //when the code is generated
+            let expr = self.expr()?;
+            if expr.jv_type == "void" {
+                return Err(JuvinilError::InvalidInterpolation(expr.jv_type, expr.line));
+            }
+
+            parts.push(expr);
+        }
+
+        Ok(Expr::new(ExprKind::Interpolation(parts), "string", line))
+    }
+
+    //Build a binary expression, checking if the operator
+    //can be applied to the types of both operands
+    fn binary(&self, operator: String, left: Expr, right: Expr) -> JuvinilResult<Expr> {
+        let operands = (left.jv_type.as_str(), right.jv_type.as_str());
+
+        let result_type = match (operator.as_str(), operands) {
+            ("||" | "&&", ("boolean", "boolean")) => Some("boolean"),
+            ("==" | "!=", (l, r)) if l == r && l != "void" => Some("boolean"),
+            ("<" | "<=" | ">" | ">=", ("int", "int")) => Some("boolean"),
+            ("+" | "-" | "*" | "/" | "%" | "&" | "|", ("int", "int")) => Some("int"),
+            ("<" | "<=" | ">" | ">=", ("string", "string")) => Some("boolean"),
+            ("+", ("string", "string")) => Some("string"),
+            _ => None,
+        };
+
+        match result_type {
+            Some(jv_type) => {
+                let line = left.line;
+                Ok(Expr::new(
+                    ExprKind::Binary(operator, Box::new(left), Box::new(right)),
+                    jv_type,
+                    line,
+                ))
+            }
+            None => Err(JuvinilError::InvalidOperands(
+                operator,
+                left.jv_type,
+                right.jv_type,
+                self.current_token.file_line,
+            )),
+        }
     }
 
     //Parse a function declaration
-    fn funcdecl(&mut self) -> JuvinilResult<()> {
+    fn funcdecl(&mut self) -> JuvinilResult<FuncDecl> {
+        let line = self.current_token.file_line;
         self.consume(TokenType::KEYWORD, Some("func"))?;
 
         let return_type = self.current_token.value.clone();
         self.jvtype()?;
 
-        //Assert that the function hasn't already
-        //been declared
-        self.assert_func_declared(false)?;
-
         let func_name = self.current_token.value.clone();
         self.consume(TokenType::ID, None)?;
 
@@ -839,88 +1337,140 @@ impl Parser {
         self.push_scope();
 
         self.consume(TokenType::SYMBOL, Some("("))?;
-        let params_value = self.paramsdecl()?;
+        let params = self.paramsdecl()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
 
-        self.intermediary_code.push_str(
-            format!(
-                "{} {} ({})",
-                self.map_type(return_type.as_str()),
-                func_name,
-                params_value
-            )
-            .as_str(),
-        );
+        //Keep track of the function being parsed,
+        //so return statements can be type checked
+        let function = JvFunction {
+            return_type: return_type.clone(),
+            func_name: func_name.clone(),
+            params: params.iter().map(|(p, _)| p.var_type.clone()).collect(),
+            param_names: params.iter().map(|(p, _)| p.var_name.clone()).collect(),
+            defaults: params.iter().map(|(_, d)| d.clone()).collect(),
+            called: false,
+            line,
+        };
 
-        self.block()?;
+        //Assert that the function hasn't already
+        //been declared with the same parameter types
+        self.assert_overload_not_declared(&function)?;
 
-        //Leave function scope after block ends
-        self.pop_scope();
+        //The function is registered in the scope around it
+        //before its body is parsed, so it can call itself
+        self.register_func_in_parent_scope(function.clone());
+        self.current_function = Some(function);
 
-        //Extract params from `params_value` string
-        let func_params: Vec<String> = params_value
-            .split(',')
-            .map(|s| {
-                s.trim()
-                    .split_whitespace()
-                    .next()
-                    .unwrap_or_default()
-                    .to_string()
-            })
-            .filter(|s| !s.is_empty())
-            .collect();
+        let body = self.block()?;
 
-        self.register_func_in_scope(return_type, func_name, func_params);
+        //Leave function scope after block ends. The function
+        //is still current, so unused parameters can name it
+        self.pop_scope();
 
-        Ok(())
+        self.current_function = None;
+
+        Ok(FuncDecl {
+            return_type,
+            func_name,
+            params: params
+                .into_iter()
+                .map(|(p, _)| (p.var_type, p.var_name))
+                .collect(),
+            body,
+            line,
+        })
     }
 
     //Parse the parameters of a function declaration
-    fn paramsdecl(&mut self) -> JuvinilResult<String> {
-        let mut params_decl_result = String::new();
+    //paramdecl -> TYPE ID | TYPE ID = expr
+    fn paramsdecl(&mut self) -> JuvinilResult<Vec<(JvVariable, Option<Expr>)>> {
+        let mut params_decl_result = Vec::new();
 
         //If the current token is a closing brackets ')',
         //then there are no parameters and we return early
-        if self.current_token.value == ")" {
+        if self.current_is(")") {
             return Ok(params_decl_result);
         }
 
-        //Consume a parameter declaration
-        let param_type_value = self.current_token.value.clone();
-        self.consume(TokenType::TYPE, None)?;
+        loop {
+            //Consume a parameter declaration
+            let param_type_value = self.current_token.value.clone();
+            self.consume(TokenType::TYPE, None)?;
+
+            //Parameters can't repeat names, but they can shadow globals
+            self.assert_can_declare()?;
+
+            let id_value = self.current_token.value.clone();
+            let id_line = self.current_token.file_line;
+            self.consume(TokenType::ID, None)?;
+
+            //Register variable in current scope. Parameters are
+            //always assigned, since their values come from outside
+            let param = JvVariable {
+                var_type: param_type_value,
+                var_name: id_value,
+                constant: false,
+                param: true,
+                read: false,
+                written: true,
+                line: id_line,
+            };
+
+            //Default values are evaluated at each call site, so they
+            //can only be constant expressions. Once a parameter has
+            //a default value, every parameter after it needs one
+            let mut default: Option<Expr> = None;
+            if self.current_is("=") {
+                self.consume(TokenType::OPERATOR, Some("="))?;
+
+                let line = self.current_token.file_line;
+                let expr = self.expr()?;
+
+                if !expr.is_constant() {
+                    return Err(JuvinilError::InvalidDefaultValue(param.var_name, line));
+                }
 
-        let id_value = self.current_token.value.clone();
-        self.consume(TokenType::ID, None)?;
+                if expr.jv_type != param.var_type {
+                    return Err(JuvinilError::InvalidValueType(
+                        param.var_name,
+                        param.var_type,
+                        expr.jv_type,
+                        line,
+                    ));
+                }
 
-        //Register variable in current scope
-        //and also mark it as declared, since
-        //it's a value that's coming from outside
-        self.register_variable_in_scope(param_type_value.clone(), id_value.clone());
-        self.mark_variable_as_assigned(id_value.clone())?;
+                default = Some(expr);
+            } else if params_decl_result
+                .iter()
+                .any(|(_, d): &(JvVariable, Option<Expr>)| d.is_some())
+            {
+                return Err(JuvinilError::MissingDefaultValue(param.var_name, id_line));
+            }
 
-        params_decl_result.push_str(
-            format!("{} {}", self.map_type(param_type_value.as_str()), id_value).as_str(),
-        );
+            self.register_variable_in_scope(param.clone());
+            params_decl_result.push((param, default));
+
+            //If the current token is a comma (,)
+            //We consume a new parameter declaration
+            if !self.current_is(",") {
+                break;
+            }
 
-        //If the current token is a comma (,)
-        //We consume a new paramsdecl
-        if self.current_token.value == "," {
             self.consume(TokenType::SYMBOL, Some(","))?;
-            params_decl_result.push_str(",");
-            params_decl_result.push_str(self.paramsdecl()?.as_str());
         }
 
         Ok(params_decl_result)
     }
 
     //Parse a function call
-    fn func(&mut self) -> JuvinilResult<String> {
+    fn func(&mut self) -> JuvinilResult<Expr> {
         //Assert that the current function
-        //has already been declared, unless it's the printf function
+        //has already been declared, unless it's a builtin function
         let func_name = self.current_token.value.clone();
-        let mut func_ref: Option<JvFunction> = None;
-        if func_name != "printf" {
-            func_ref = self.assert_func_declared(true)?;
+        let line = self.current_token.file_line;
+        let mut overloads: Vec<JvFunction> = Vec::new();
+        if !builtins::is_builtin(&func_name) {
+            overloads = self.assert_func_declared()?;
         }
 
         self.consume(TokenType::ID, None)?;
@@ -928,79 +1478,108 @@ impl Parser {
         let func_params = self.params()?;
         self.consume(TokenType::SYMBOL, Some(")"))?;
 
-        //Assert that param count is the required number of parameters
-        if let Some(func) = func_ref {
-            //Extract params from `params_value` string
-            let params_vec: Vec<String> = func_params
-                .split(',')
-                .map(|s| {
-                    s.trim()
-                        .split_whitespace()
-                        .next()
-                        .unwrap_or_default()
-                        .to_string()
-                })
-                .filter(|s| !s.is_empty())
-                .collect();
-
-            if func.params.len() != params_vec.len() {
-                return Err(JuvinilError::InvalidParamCount(
-                    func_name,
-                    func.params.len(),
-                    params_vec.len(),
-                    self.current_token.file_line,
-                ));
+        //Builtin functions have their own checks,
+        //and they have no parameter names
+        if overloads.is_empty() {
+            let mut args = Vec::new();
+
+            for (name, arg) in func_params {
+                if let Some(name) = name {
+                    return Err(JuvinilError::UnknownArgument(func_name, name, arg.line));
+                }
+
+                args.push(arg);
             }
+
+            let return_type = builtins::check_call(&func_name, &args, line)?;
+            return Ok(Expr::new(
+                ExprKind::Call(func_name, args),
+                &return_type,
+                line,
+            ));
         }
 
-        Ok(format!("{} ({})", func_name, func_params))
+        //The overload is picked once the argument types are known.
+        //The call always passes every parameter, with default values
+        //filled in, so the code generation doesn't need to know about them
+        let (func, args) = self.resolve_overload(&func_name, overloads, &func_params)?;
+        self.mark_func_as_called(&func_name, &func.params);
+
+        Ok(Expr::new(
+            ExprKind::Call(func_name, args),
+            func.return_type.as_str(),
+            line,
+        ))
     }
 
     //Parse the parameters of a function call
-    fn params(&mut self) -> JuvinilResult<String> {
-        let mut params_result = String::new();
+    fn params(&mut self) -> JuvinilResult<Vec<(Option<String>, Expr)>> {
+        let mut params_result = Vec::new();
 
         //If the current token is a closing brackets ')',
         //then there are no parameters and we return early
-        if self.current_token.value == ")" {
-            return Ok(String::new());
+        if self.current_is(")") {
+            return Ok(params_result);
         }
 
-        let param_result: String;
+        //Parameters can be any expression
+        params_result.push(self.param()?);
 
-        //If the parameter is not a STRING, then
-        //it is any expression
-        if self.current_token.token_type == TokenType::STRING {
-            param_result = format!("\"{}\"", self.current_token.value.clone());
-            self.consume(TokenType::STRING, None)?;
-        } else {
-            //Parameters can be any expression
-            param_result = self.expr()?;
-        }
-
-        params_result.push_str(param_result.as_str());
-
-        //If the current token is a comma (,)
+        //While the current token is a comma (,)
         //We consume a new param
-        if self.current_token.value == "," {
+        while self.current_is(",") {
             self.consume(TokenType::SYMBOL, Some(","))?;
-            params_result.push_str(", ");
-            let recursive_result = self.params()?;
-            params_result.push_str(recursive_result.as_str());
+            let param = self.param()?;
+
+            //Once an argument is named, the position
+            //of the following ones means nothing
+            let after_named = params_result
+                .last()
+                .is_some_and(|(name, _): &(Option<String>, Expr)| name.is_some());
+
+            if after_named && param.0.is_none() {
+                return Err(JuvinilError::PositionalAfterNamed(param.1.line));
+            }
+
+            params_result.push(param);
         }
 
         Ok(params_result)
     }
 
+    //param -> expr | ID : expr
+    fn param(&mut self) -> JuvinilResult<(Option<String>, Expr)> {
+        let mut name: Option<String> = None;
+
+        //It's a named argument if the ID is followed by a colon
+        if self.current_token.token_type == TokenType::ID
+            && self.lookahead.as_ref().is_some_and(|l| l.value == ":")
+        {
+            name = Some(self.current_token.value.clone());
+            self.consume(TokenType::ID, None)?;
+            self.consume(TokenType::OPERATOR, Some(":"))?;
+        }
+
+        Ok((name, self.expr()?))
+    }
+
     //Parse an assignment
-    fn asgn(&mut self) -> JuvinilResult<()> {
+    fn asgn(&mut self) -> JuvinilResult<Stmt> {
+        let stmt_line = self.current_token.file_line;
+
         //Assert that the current variable
         //was declared before doing the assignment
         let id_ref = self.assert_id_declared(true)?.unwrap();
 
-        self.consume(TokenType::ID, None)?;
+        //Constants are assigned once, in their declaration
+        if id_ref.constant {
+            return Err(JuvinilError::ConstantAssignment(
+                id_ref.var_name,
+                self.current_token.file_line,
+            ));
+        }
 
-        self.mark_variable_as_assigned(id_ref.var_name.clone())?;
+        self.consume(TokenType::ID, None)?;
 
         //Match the current token value to check the operator
         let operator_value = self.current_token.value.clone();
@@ -1010,38 +1589,48 @@ impl Parser {
             _ => self.consume(TokenType::OPERATOR, Some("="))?,
         }
 
-        //If the value after the operator is a string, consume it
-        let expr_value: String;
-        if self.current_token.token_type == TokenType::STRING {
-            if id_ref.var_type != "string" {
-                return Err(JuvinilError::InvalidValueType(
-                    id_ref.var_name.clone(),
-                    id_ref.var_type.clone(),
-                    "string".into(),
-                    self.current_token.file_line,
-                ));
-            }
+        //Compound assignments work on integers,
+        //and `+=` also concatenates strings
+        let line = self.current_token.file_line;
+        let expr = self.expr()?;
+        let compound_ok = matches!(
+            (
+                operator_value.as_str(),
+                id_ref.var_type.as_str(),
+                expr.jv_type.as_str()
+            ),
+            ("=", _, _) | ("+=" | "-=", "int", "int") | ("+=", "string", "string")
+        );
+        if !compound_ok {
+            return Err(JuvinilError::InvalidOperands(
+                operator_value,
+                id_ref.var_type.clone(),
+                expr.jv_type,
+                line,
+            ));
+        }
 
-            expr_value = format!("\"{}\"", self.current_token.value.clone());
-            self.consume(TokenType::STRING, None)?;
-        } else {
-            //Otherwise, consume an expression
-            expr_value = self.expr()?;
+        if id_ref.var_type != expr.jv_type {
+            return Err(JuvinilError::InvalidValueType(
+                id_ref.var_name.clone(),
+                id_ref.var_type.clone(),
+                expr.jv_type,
+                line,
+            ));
         }
 
         self.endexpr()?;
 
-        self.intermediary_code.push_str(
-            format!(
-                "{} {} {};",
-                id_ref.var_name.clone(),
-                operator_value,
-                expr_value
-            )
-            .as_str(),
-        );
+        //`+=` and `-=` read the variable before assigning it
+        self.mark_variable_as_written(&id_ref.var_name);
+        if operator_value != "=" {
+            self.mark_variable_as_read(&id_ref.var_name);
+        }
 
-        Ok(())
+        Ok(Stmt::new(
+            StmtKind::Asgn(id_ref.var_name, operator_value, expr),
+            stmt_line,
+        ))
     }
 
     //Parse a TYPE expression
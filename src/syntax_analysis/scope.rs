@@ -1,15 +1,39 @@
+use super::ast::Expr;
+
 #[derive(Clone)]
 pub struct JvVariable {
     pub var_type: String, //Type of the variable
     pub var_name: String, //Name of the variable
-    pub assigned: bool,   //Flag to check if the variable value has been assigned
+    pub constant: bool,   //Flag to check if the variable was declared as `const`
+    pub param: bool,      //Flag to check if the variable is a function parameter
+    pub read: bool,       //Flag to check if the variable value has been read
+    pub written: bool,    //Flag to check if the variable value has been assigned
+    pub line: usize,      //Line where the variable was declared
 }
 
 #[derive(Clone)]
 pub struct JvFunction {
-    pub return_type: String, //Return type of the function
-    pub func_name: String,   //Name of the function
-    pub params: Vec<String>, //Parameters of the function (just their type, not name)
+    pub return_type: String,         //Return type of the function
+    pub func_name: String,           //Name of the function
+    pub params: Vec<String>,         //Parameters of the function (just their type, not name)
+    pub param_names: Vec<String>,    //Names of the parameters, used by named arguments
+    pub defaults: Vec<Option<Expr>>, //Default value of each parameter, if any
+    pub called: bool,                //Flag to check if the function has been called
+    pub line: usize,                 //Line where the function was declared
+}
+
+impl JvFunction {
+    //Name and parameter types of the function, like `sum(int, int)`,
+    //which tells overloads apart
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.func_name, self.params.join(", "))
+    }
+
+    //Number of parameters without a default value. Defaults
+    //are always last, so these are the first parameters
+    pub fn required_params(&self) -> usize {
+        self.defaults.iter().filter(|d| d.is_none()).count()
+    }
 }
 
 //A scope contains a reference to it's parent (also a scope),
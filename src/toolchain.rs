@@ -0,0 +1,202 @@
+use std::process::Command;
+
+use crate::error::{JuvinilError, JuvinilResult};
+
+//Compilers tried, in order, when none is configured
+pub const CANDIDATES: [&str; 3] = ["g++", "clang++", "c++"];
+pub const C_CANDIDATES: [&str; 3] = ["gcc", "clang", "cc"];
+
+//Optimization levels accepted by every candidate, as in `-O2`
+pub const OPT_LEVELS: [&str; 5] = ["0", "1", "2", "3", "s"];
+
+//Native C or C++ compiler used to build the generated code
+#[derive(Debug, Clone)]
+pub struct Toolchain {
+    compiler: String,           //Program that compiles the code, like `g++`
+    opt_level: String,          //Passed as `-O<level>`
+    standard: Option<String>,   //Passed as `-std=<standard>`
+    warning_flags: Vec<String>, //Passed as they are, like `-Wall`
+}
+
+impl Toolchain {
+    //Finds the C++ compiler to use. One given by the command line comes
+    //first, then the `CXX` environment variable, and then the first
+    //of the candidates that is installed
+    pub fn detect(requested: Option<&str>) -> JuvinilResult<Self> {
+        detect_from(requested, "CXX", &CANDIDATES)
+    }
+
+    //Like `detect`, for a C compiler and the `CC` environment variable
+    pub fn detect_c(requested: Option<&str>) -> JuvinilResult<Self> {
+        Ok(detect_from(requested, "CC", &C_CANDIDATES)?.with_standard("c99"))
+    }
+
+    pub fn new(compiler: &str) -> Self {
+        Toolchain {
+            compiler: compiler.into(),
+            opt_level: "2".into(),
+            standard: None,
+            warning_flags: Vec::new(),
+        }
+    }
+
+    pub fn with_opt_level(mut self, opt_level: &str) -> JuvinilResult<Self> {
+        if !OPT_LEVELS.contains(&opt_level) {
+            return Err(JuvinilError::InvalidArgument(format!("-O{}", opt_level)));
+        }
+
+        self.opt_level = opt_level.into();
+        Ok(self)
+    }
+
+    //Language standard the code is compiled with, like `c99`
+    pub fn with_standard(mut self, standard: &str) -> Self {
+        self.standard = Some(standard.into());
+        self
+    }
+
+    pub fn with_warning_flags(mut self, warning_flags: Vec<String>) -> Self {
+        self.warning_flags = warning_flags;
+        self
+    }
+
+    pub fn compiler(&self) -> &str {
+        &self.compiler
+    }
+
+    //Arguments that compile `source` into the executable `output`.
+    //Signed overflow is undefined in C and C++, so `-fwrapv` makes
+    //it wrap around like in the interpreter and the other backends
+    pub fn args(&self, source: &str, output: &str) -> Vec<String> {
+        let mut args = vec![format!("-O{}", self.opt_level), "-fwrapv".into()];
+        if let Some(standard) = &self.standard {
+            args.push(format!("-std={}", standard));
+        }
+        args.extend(self.warning_flags.iter().cloned());
+        args.extend(["-o".into(), output.into(), source.into()]);
+        args
+    }
+
+    //Compiles `source` into the executable `output`. The messages of the
+    //compiler are captured, so they can be reported if the compilation fails
+    pub fn compile(&self, source: &str, output: &str) -> JuvinilResult<()> {
+        let result = Command::new(&self.compiler)
+            .args(self.args(source, output))
+            .output()
+            .map_err(|_| JuvinilError::CompilerNotFound(self.compiler.clone()))?;
+
+        let stderr = String::from_utf8_lossy(&result.stderr)
+            .trim_end()
+            .to_string();
+
+        if !result.status.success() {
+            return Err(JuvinilError::ToolError(self.compiler.clone(), stderr));
+        }
+
+        //Warnings of the native compiler don't stop the compilation
+        if !stderr.is_empty() {
+            tracing::warn!("{}", stderr);
+        }
+
+        Ok(())
+    }
+}
+
+//Assembler and linker that build the assembly of the x86-64 backend.
+//Its runtime makes system calls itself, so nothing else is linked
+#[derive(Debug, Clone)]
+pub struct Assembler {
+    assembler: String,
+    linker: String,
+}
+
+impl Assembler {
+    //Finds `as` and `ld`, which only build a working
+    //program when running on x86-64 Linux
+    pub fn detect() -> JuvinilResult<Self> {
+        if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+            return Err(JuvinilError::UnsupportedPlatform("asm".into()));
+        }
+
+        for tool in ["as", "ld"] {
+            if !is_installed(tool) {
+                return Err(JuvinilError::CompilerNotFound(tool.into()));
+            }
+        }
+
+        Ok(Assembler {
+            assembler: "as".into(),
+            linker: "ld".into(),
+        })
+    }
+
+    pub fn assembler(&self) -> &str {
+        &self.assembler
+    }
+
+    //Assembles `source` into an object file next to `output`,
+    //which is then linked into the executable `output`
+    pub fn build(&self, source: &str, output: &str) -> JuvinilResult<()> {
+        let object = format!("{}.o", output);
+        let result = run_tool(&self.assembler, &["--64", "-o", &object, source])
+            .and_then(|_| run_tool(&self.linker, &["-o", output, &object]));
+        let _ = std::fs::remove_file(&object);
+
+        result
+    }
+}
+
+//Runs a tool of the toolchain, reporting what it printed if it fails
+fn run_tool(tool: &str, args: &[&str]) -> JuvinilResult<()> {
+    let result = Command::new(tool)
+        .args(args)
+        .output()
+        .map_err(|_| JuvinilError::CompilerNotFound(tool.into()))?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(JuvinilError::ToolError(
+            tool.into(),
+            stderr.trim_end().to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+//Path of an executable, with the suffix of the current platform
+//(`.exe` on Windows and none elsewhere)
+pub fn executable_path(path: &str) -> String {
+    format!("{}{}", path, std::env::consts::EXE_SUFFIX)
+}
+
+fn detect_from(
+    requested: Option<&str>,
+    variable: &str,
+    candidates: &[&str],
+) -> JuvinilResult<Toolchain> {
+    let configured = requested
+        .map(String::from)
+        .or_else(|| std::env::var(variable).ok())
+        .filter(|compiler| !compiler.trim().is_empty());
+
+    let compiler = match configured {
+        Some(compiler) if is_installed(&compiler) => compiler,
+        Some(compiler) => return Err(JuvinilError::CompilerNotFound(compiler)),
+        None => candidates
+            .iter()
+            .find(|candidate| is_installed(candidate))
+            .map(|candidate| candidate.to_string())
+            .ok_or(JuvinilError::CompilerNotFound(candidates.join(", ")))?,
+    };
+
+    Ok(Toolchain::new(&compiler))
+}
+
+//A compiler is installed if it can be started
+fn is_installed(compiler: &str) -> bool {
+    Command::new(compiler)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}